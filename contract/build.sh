@@ -1,6 +1,8 @@
 #!/bin/bash
 set -e
 
-cargo build --target wasm32-unknown-unknown --release 
+cargo build --target wasm32-unknown-unknown --release -p keypom-multichain-trial-accounts -p mock-mpc -p mock-wallet
 mkdir -p ../out
-cp target/wasm32-unknown-unknown/release/*.wasm ../out/fastauth.wasm
+cp ../target/wasm32-unknown-unknown/release/keypom_multichain_trial_accounts.wasm ../out/fastauth.wasm
+cp ../target/wasm32-unknown-unknown/release/mock_mpc.wasm ../out/mock_mpc.wasm
+cp ../target/wasm32-unknown-unknown/release/mock_wallet.wasm ../out/mock_wallet.wasm
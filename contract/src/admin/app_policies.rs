@@ -0,0 +1,140 @@
+// admin/app_policies.rs
+use crate::*;
+
+#[near]
+impl Contract {
+    /// Sets the policies an app's session keys are held to.
+    /// Callable by the owner, the oracle or the account that created the app's trial data.
+    /// The caller pays for any storage the new data adds and is refunded what it frees.
+    #[payable]
+    pub fn set_trial_data(&mut self, app_id: AppID, trial_data: TrialData) {
+        self.assert_can_manage_app(&app_id);
+        trial_data.assert_valid();
+
+        let initial_storage = env::storage_usage();
+
+        self.internal_set_trial_data(app_id.clone(), trial_data);
+        self.trial_data_by_app.flush();
+
+        self.adjust_deposit(initial_storage, env::storage_usage());
+        env::log_str(&format!("Trial data set for app {}", app_id));
+    }
+
+    /// Removes an app's policies, refunding the freed storage to the caller.
+    pub fn remove_trial_data(&mut self, app_id: AppID) {
+        self.assert_can_manage_app(&app_id);
+
+        let initial_storage = env::storage_usage();
+
+        self.internal_remove_trial_data(&app_id);
+        self.trial_data_by_app.flush();
+
+        self.adjust_deposit(initial_storage, env::storage_usage());
+        env::log_str(&format!("Trial data removed for app {}", app_id));
+    }
+
+    fn assert_can_manage_app(&self, app_id: &AppID) {
+        let predecessor = env::predecessor_account_id();
+        let is_creator = self
+            .internal_get_trial_data(app_id)
+            .map(|trial_data| trial_data.creator_account_id == predecessor)
+            .unwrap_or(false);
+
+        require!(
+            is_creator || predecessor == self.owner_id || predecessor == self.oracle_account_id,
+            "Only owner, oracle or app creator can manage trial data"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::mock::MockAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    fn trial_data(allowed_methods: usize) -> TrialData {
+        TrialData {
+            constraints_by_chain_id: HashMap::from([(
+                ChainId::from("NEAR"),
+                ChainConstraints::NEAR(NearConstraints {
+                    allowed_methods: (0..allowed_methods)
+                        .map(|i| format!("method_{}", i))
+                        .collect(),
+                    allowed_contracts: vec![accounts(5)],
+                    max_gas: None,
+                    max_deposit: None,
+                    initial_deposit: NearToken::from_near(0),
+                }),
+            )]),
+            usage_constraints: None,
+            interaction_limits: None,
+            exit_conditions: None,
+            expiration_time: None,
+            creator_account_id: accounts(4),
+            typed_data_constraints: None,
+            personal_sign_format: None,
+            bitcoin_constraints: None,
+            solana_constraints: None,
+        }
+    }
+
+    fn call_as_creator(deposit: NearToken) {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(deposit)
+            .build());
+    }
+
+    /// Amounts transferred back to the caller by the receipts created so far
+    fn refunds() -> Vec<u128> {
+        get_created_receipts()
+            .into_iter()
+            .filter(|receipt| receipt.receiver_id == accounts(4))
+            .flat_map(|receipt| receipt.actions)
+            .filter_map(|action| match action {
+                MockAction::Transfer { deposit, .. } => Some(deposit.as_yoctonear()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn setup() -> Contract {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(NearToken::from_near(1))
+            .build());
+        let mut contract = Contract::new(accounts(2), accounts(1), accounts(3));
+        contract.set_trial_data("app".to_string(), trial_data(1));
+        contract
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient deposit")]
+    fn growing_trial_data_must_be_paid_for() {
+        let mut contract = setup();
+        call_as_creator(NearToken::from_yoctonear(0));
+        contract.set_trial_data("app".to_string(), trial_data(100));
+    }
+
+    #[test]
+    fn freed_storage_is_refunded() {
+        let mut contract = setup();
+        call_as_creator(NearToken::from_near(1));
+        contract.set_trial_data("app".to_string(), trial_data(100));
+        let paid = NearToken::from_near(1).as_yoctonear() - refunds()[0];
+        assert!(paid > 0);
+
+        call_as_creator(NearToken::from_yoctonear(0));
+        contract.set_trial_data("app".to_string(), trial_data(1));
+        assert_eq!(refunds(), vec![paid]);
+
+        call_as_creator(NearToken::from_yoctonear(0));
+        contract.remove_trial_data("app".to_string());
+        assert!(refunds()[0] > 0);
+        assert!(contract.get_trial_data("app".to_string()).is_none());
+    }
+}
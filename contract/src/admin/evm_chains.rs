@@ -0,0 +1,68 @@
+// admin/evm_chains.rs
+use crate::*;
+use perform_actions::evm::transaction::EvmTransaction;
+
+#[near]
+impl Contract {
+    /// Adds a supported EVM chain or replaces its configuration
+    pub fn set_evm_chain(&mut self, chain: EvmChain) {
+        self.assert_owner();
+
+        env::log_str(&format!(
+            "EVM chain {} ({}) configured",
+            chain.chain_id, chain.name
+        ));
+        self.evm_chains.insert(chain.chain_id, chain);
+    }
+
+    /// Enables or disables signing for a supported EVM chain
+    pub fn set_evm_chain_enabled(&mut self, chain_id: u64, enabled: bool) {
+        self.assert_owner();
+
+        let chain = self
+            .evm_chains
+            .get_mut(&chain_id)
+            .or_fail(FastAuthError::ChainNotSupported);
+        chain.enabled = enabled;
+    }
+
+    /// Removes an EVM chain from the registry
+    pub fn remove_evm_chain(&mut self, chain_id: u64) {
+        self.assert_owner();
+
+        self.evm_chains
+            .remove(&chain_id)
+            .or_fail(FastAuthError::ChainNotSupported);
+    }
+
+    /// Returns the registry entry for a chain, panicking unless it is enabled.
+    pub(crate) fn assert_evm_chain_enabled(&self, chain_id: u64) -> &EvmChain {
+        let chain = self
+            .evm_chains
+            .get(&chain_id)
+            .or_fail(FastAuthError::ChainNotSupported);
+        ensure(chain.enabled, FastAuthError::ChainDisabled);
+        chain
+    }
+
+    /// Checks a transaction's chain, fees, gas limit and value against the registry.
+    pub(crate) fn assert_evm_transaction_allowed(&self, evm_transaction: &EvmTransaction) {
+        let chain = self.assert_evm_chain_enabled(evm_transaction.chain_id());
+
+        ensure(
+            chain.is_fee_allowed(
+                evm_transaction.max_fee_per_gas(),
+                evm_transaction.max_priority_fee_per_gas(),
+            ),
+            FastAuthError::FeeExceedsCap,
+        );
+        ensure(
+            chain.is_gas_limit_allowed(evm_transaction.gas_limit()),
+            FastAuthError::GasLimitExceedsCap,
+        );
+        ensure(
+            chain.is_value_allowed(evm_transaction.value()),
+            FastAuthError::ValueExceedsCap,
+        );
+    }
+}
@@ -0,0 +1,9 @@
+// admin/mod.rs
+//! Module for contract administration, including ownership, emergency controls and upgrades.
+
+pub mod app_policies;
+pub mod evm_chains;
+pub mod mpc;
+pub mod owner;
+pub mod pause;
+pub mod upgrade;
@@ -0,0 +1,51 @@
+// admin/mpc.rs
+use crate::*;
+
+#[near]
+impl Contract {
+    /// Points the contract at a different MPC signer
+    pub fn set_mpc_contract(&mut self, mpc_contract: AccountId) {
+        self.assert_owner();
+
+        env::log_str(&format!(
+            "MPC contract changed from {} to {}",
+            self.mpc_contract, mpc_contract
+        ));
+        self.mpc_contract = mpc_contract;
+    }
+
+    /// Replaces the request schema, domains, deposit and gas used for `sign` calls
+    pub fn set_mpc_config(&mut self, config: MpcConfig) {
+        self.assert_owner();
+        ensure(
+            config.sign_gas.as_gas() > 0,
+            FastAuthError::InvalidArgument("sign_gas must be positive".into()),
+        );
+
+        self.mpc_config = config;
+    }
+
+    /// Calls the MPC contract's `sign` with the configured deposit and gas.
+    fn call_mpc_sign(&self, args: serde_json::Value) -> Promise {
+        Promise::new(self.mpc_contract.clone()).function_call(
+            "sign".to_string(),
+            near_sdk::serde_json::to_vec(&args).unwrap(),
+            self.mpc_config.sign_deposit,
+            self.mpc_config.sign_gas,
+        )
+    }
+
+    /// Asks the MPC contract to sign a hashed payload with the key derived from `path`.
+    pub(crate) fn request_mpc_signature(
+        &self,
+        hashed_payload: [u8; 32],
+        path: &MpcPath,
+    ) -> Promise {
+        self.call_mpc_sign(self.mpc_config.ecdsa_sign_args(hashed_payload, path))
+    }
+
+    /// Asks the MPC contract to sign a message with the ed25519 key derived from `path`.
+    pub(crate) fn request_mpc_eddsa_signature(&self, message: &[u8], path: &MpcPath) -> Promise {
+        self.call_mpc_sign(self.mpc_config.eddsa_sign_args(message, path))
+    }
+}
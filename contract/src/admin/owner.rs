@@ -0,0 +1,39 @@
+// admin/owner.rs
+use crate::*;
+
+#[near]
+impl Contract {
+    /// Transfers ownership of the contract to a new account
+    pub fn set_owner(&mut self, new_owner_id: AccountId) {
+        self.assert_owner();
+
+        env::log_str(&format!(
+            "Ownership transferred from {} to {}",
+            self.owner_id, new_owner_id
+        ));
+        self.owner_id = new_owner_id;
+    }
+
+    /// Replaces the oracle allowed to activate accounts and add session keys
+    pub fn set_oracle(&mut self, oracle_account_id: AccountId) {
+        self.assert_owner();
+
+        self.oracle_account_id = oracle_account_id;
+    }
+
+    /// Sets the account named accounts are created under, e.g. `near` for top-level
+    /// names. The parent must expose a registrar `create_account` the contract can call.
+    /// `None` creates them as sub-accounts of the contract.
+    pub fn set_named_account_parent(&mut self, parent: Option<AccountId>) {
+        self.assert_owner();
+
+        self.named_account_parent = parent;
+    }
+
+    pub(crate) fn assert_owner(&self) {
+        require!(
+            env::predecessor_account_id() == self.owner_id,
+            "Only owner can call this method"
+        );
+    }
+}
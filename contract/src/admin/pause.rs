@@ -0,0 +1,80 @@
+// admin/pause.rs
+use crate::*;
+
+#[near]
+impl Contract {
+    /// Halts all signing for every app and user. Deposits and views are unaffected.
+    pub fn pause(&mut self) {
+        self.assert_owner();
+
+        self.paused = true;
+        env::log_str("Contract paused");
+    }
+
+    /// Lifts the global pause
+    pub fn unpause(&mut self) {
+        self.assert_owner();
+
+        self.paused = false;
+        env::log_str("Contract unpaused");
+    }
+
+    /// Halts signing for every session key belonging to an app
+    pub fn pause_app(&mut self, app_id: AppID) {
+        self.assert_owner();
+
+        self.paused_apps.insert(app_id.clone());
+        env::log_str(&format!("App {} paused", app_id));
+    }
+
+    pub fn unpause_app(&mut self, app_id: AppID) {
+        self.assert_owner();
+
+        self.paused_apps.remove(&app_id);
+        env::log_str(&format!("App {} unpaused", app_id));
+    }
+
+    /// Halts signing for a single user across all apps
+    pub fn freeze_user(&mut self, path: MpcPath) {
+        self.assert_owner();
+
+        self.frozen_users.insert(path.clone());
+        env::log_str(&format!("User {} frozen", path));
+    }
+
+    pub fn unfreeze_user(&mut self, path: MpcPath) {
+        self.assert_owner();
+
+        self.frozen_users.remove(&path);
+        env::log_str(&format!("User {} unfrozen", path));
+    }
+
+    /// Removes a session key immediately, e.g. when a batch of keys has leaked.
+    /// Callable by the owner or the oracle.
+    pub fn revoke_session_key(&mut self, public_key: SessionKey) {
+        let predecessor = env::predecessor_account_id();
+        require!(
+            predecessor == self.owner_id || predecessor == self.oracle_account_id,
+            "Only owner or oracle can revoke session keys"
+        );
+
+        let key_usage = self
+            .internal_remove_key_usage(&public_key)
+            .or_fail(FastAuthError::SessionKeyNotFound);
+        self.session_keys
+            .remove(&(key_usage.path.clone(), key_usage.app_id.clone()));
+        self.internal_unindex_session_key(&key_usage.path, &key_usage.app_id);
+
+        env::log_str(&format!(
+            "Session key {} revoked for app {}",
+            public_key, key_usage.app_id
+        ));
+    }
+
+    /// Panics if signing is halted globally, for the app or for the user
+    pub(crate) fn assert_not_paused(&self, app_id: &AppID, path: &MpcPath) {
+        ensure(!self.paused, FastAuthError::ContractPaused);
+        ensure(!self.paused_apps.contains(app_id), FastAuthError::AppPaused);
+        ensure(!self.frozen_users.contains(path), FastAuthError::UserFrozen);
+    }
+}
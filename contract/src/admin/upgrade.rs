@@ -0,0 +1,222 @@
+// admin/upgrade.rs
+use crate::*;
+
+#[near]
+impl Contract {
+    /// Deploys the contract code passed as raw input to this account and migrates the state.
+    /// Called with the wasm bytes as the function call arguments.
+    pub fn upgrade(&self) -> Promise {
+        self.assert_owner();
+
+        let code = env::input().expect("Missing contract code");
+
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call_weight(
+                "migrate".to_string(),
+                vec![],
+                NearToken::from_yoctonear(0),
+                Gas::from_tgas(20),
+                GasWeight(1),
+            )
+    }
+
+    /// Upgrades the stored state from any previous layout to the current one.
+    /// Callable by the contract itself (as part of `upgrade`) or by the owner.
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let old_state = VersionedContract::load();
+
+        let predecessor = env::predecessor_account_id();
+        require!(
+            predecessor == env::current_account_id() || old_state.owner_id() == Some(&predecessor),
+            "Only owner can migrate the contract"
+        );
+
+        let contract = old_state.into_current();
+        StateVersion::CURRENT.write();
+
+        env::log_str(&format!(
+            "Migrated contract state to {:?}",
+            StateVersion::CURRENT
+        ));
+        contract
+    }
+
+    /// Adds users and apps stored before the enumeration indexes existed to them. The
+    /// legacy maps can't be iterated, so the owner passes entries in batches: each user's
+    /// path with the apps they hold session keys for, and apps with a balance but no users.
+    /// Every entry is checked against the stored bundles, session keys and balances.
+    /// The attached deposit pays for the indexes' storage and the rest is refunded.
+    #[payable]
+    pub fn backfill_indexes(&mut self, users: Vec<(MpcPath, Vec<AppID>)>, apps: Vec<AppID>) {
+        self.assert_owner();
+
+        let initial_storage = env::storage_usage();
+
+        for (path, app_ids) in users {
+            ensure(
+                self.internal_get_bundle(&path).is_some(),
+                FastAuthError::UserNotFound,
+            );
+            self.paths.insert(path.clone());
+
+            for app_id in app_ids {
+                ensure(
+                    self.session_keys
+                        .contains_key(&(path.clone(), app_id.clone())),
+                    FastAuthError::SessionKeyNotFound,
+                );
+                self.internal_index_session_key(&path, &app_id);
+            }
+        }
+
+        for app_id in apps {
+            ensure(
+                self.app_balances.contains_key(&app_id),
+                FastAuthError::InvalidArgument(format!("App {} has no balance", app_id)),
+            );
+            self.apps.insert(app_id);
+        }
+
+        self.flush_indexes();
+        self.adjust_deposit(initial_storage, env::storage_usage());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    const SESSION_KEY: &str = "ed25519:AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9";
+
+    /// Contract holding a user and an app that predate the indexes
+    fn setup() -> Contract {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(NearToken::from_near(1))
+            .build());
+        let mut contract = Contract::new(accounts(2), accounts(1), accounts(3));
+
+        contract.internal_set_bundle(
+            "user-path".to_string(),
+            Bundle {
+                mpc_key: SESSION_KEY.parse().unwrap(),
+                eth_address: accounts(4),
+                path: "user-path".to_string(),
+                ed25519_key: None,
+            },
+        );
+        contract.session_keys.insert(
+            ("user-path".to_string(), "app".to_string()),
+            SESSION_KEY.parse().unwrap(),
+        );
+        contract
+            .app_balances
+            .insert("app".to_string(), NearToken::from_near(1));
+        contract
+            .app_balances
+            .insert("unused-app".to_string(), NearToken::from_near(1));
+        contract
+    }
+
+    fn write_state(state: &impl near_sdk::borsh::BorshSerialize, version: StateVersion) {
+        env::state_write(state);
+        version.write();
+    }
+
+    fn migrate_as_contract() -> Contract {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .build());
+        let contract = Contract::migrate();
+        assert_eq!(StateVersion::read(), StateVersion::CURRENT);
+        contract
+    }
+
+    #[test]
+    fn v1_state_is_migrated_to_the_current_layout() {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .build());
+        let mut app_balances = LookupMap::new(StorageKeys::AppBalances);
+        app_balances.insert("app".to_string(), NearToken::from_near(1));
+        app_balances.flush();
+        write_state(
+            &ContractV1 {
+                session_keys: LookupMap::new(StorageKeys::SessionKeys),
+                key_usage_by_pk: LookupMap::new(StorageKeys::KeyUsageByPK),
+                bundler: LookupMap::new(StorageKeys::Bundler),
+                app_balances,
+                oracle_account_id: accounts(1),
+                mpc_contract: accounts(3),
+            },
+            StateVersion::V1,
+        );
+
+        let contract = migrate_as_contract();
+        assert!(!contract.paused);
+        assert_eq!(contract.owner_id, accounts(0));
+        assert_eq!(contract.oracle_account_id, accounts(1));
+        assert_eq!(contract.mpc_contract, accounts(3));
+        assert_eq!(
+            contract.app_balances.get("app"),
+            Some(&NearToken::from_near(1))
+        );
+        assert!(contract.evm_chains.contains_key(&NEAR_EVM_CHAIN_ID));
+        assert_eq!(contract.mpc_config.sign_gas, MpcConfig::default().sign_gas);
+        assert_eq!(contract.named_account_parent, None);
+    }
+
+    #[test]
+    fn current_state_is_kept_as_is() {
+        let mut contract = setup();
+        contract.named_account_parent = Some(accounts(4));
+        write_state(&contract, StateVersion::CURRENT);
+        assert_eq!(
+            migrate_as_contract().named_account_parent,
+            Some(accounts(4))
+        );
+    }
+
+    #[test]
+    fn legacy_users_and_apps_are_indexed() {
+        let mut contract = setup();
+        assert_eq!(contract.get_total_app_users("app".to_string()), 0);
+
+        contract.backfill_indexes(
+            vec![("user-path".to_string(), vec!["app".to_string()])],
+            vec!["unused-app".to_string()],
+        );
+
+        assert_eq!(
+            contract.get_app_users("app".to_string(), None, None),
+            vec!["user-path".to_string()]
+        );
+        assert_eq!(
+            contract.get_apps(None, None),
+            vec!["app".to_string(), "unused-app".to_string()]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = ":1006:")]
+    fn session_keys_must_exist() {
+        let mut contract = setup();
+        contract.backfill_indexes(
+            vec![("user-path".to_string(), vec!["other-app".to_string()])],
+            vec![],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = ":1005:")]
+    fn users_must_exist() {
+        let mut contract = setup();
+        contract.backfill_indexes(vec![("nobody".to_string(), vec![])], vec![]);
+    }
+}
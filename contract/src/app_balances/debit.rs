@@ -13,18 +13,49 @@ impl Contract {
 
         current_balance = current_balance
             .checked_add(env::attached_deposit())
-            .expect("Balance overflow");
+            .or_fail(FastAuthError::BalanceOverflow);
 
         // Check balance
-        require!(
+        ensure(
             current_balance >= amount,
-            "Insufficient app balance for transaction costs."
+            FastAuthError::InsufficientAppBalance,
         );
 
         current_balance = current_balance
             .checked_sub(amount)
-            .expect("Balance overflow");
+            .or_fail(FastAuthError::BalanceOverflow);
 
         self.app_balances.insert(app_id, current_balance);
     }
+
+    /// Takes `amount` from the app's balance for costs the app sponsors. Unlike `debit`,
+    /// the attached deposit isn't counted: it belongs to the caller.
+    pub(crate) fn charge_app(&mut self, amount: NearToken, app_id: &AppID) {
+        let current_balance = self
+            .app_balances
+            .get(app_id)
+            .cloned()
+            .unwrap_or(NearToken::from_yoctonear(0));
+
+        let new_balance = current_balance
+            .checked_sub(amount)
+            .or_fail(FastAuthError::InsufficientAppBalance);
+
+        self.app_balances.insert(app_id.clone(), new_balance);
+    }
+
+    /// Returns a charge to the app's balance when what it paid for didn't happen.
+    pub(crate) fn refund_app(&mut self, amount: NearToken, app_id: &AppID) {
+        let current_balance = self
+            .app_balances
+            .get(app_id)
+            .cloned()
+            .unwrap_or(NearToken::from_yoctonear(0));
+
+        let new_balance = current_balance
+            .checked_add(amount)
+            .or_fail(FastAuthError::BalanceOverflow);
+
+        self.app_balances.insert(app_id.clone(), new_balance);
+    }
 }
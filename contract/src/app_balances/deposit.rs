@@ -3,9 +3,12 @@ use crate::*;
 
 #[near]
 impl Contract {
+    /// Adds the attached deposit to the app's balance, less the storage a new app's
+    /// entries take up.
     #[payable]
     pub fn deposit(&mut self, app_id: AppID) {
         let deposit_amount = env::attached_deposit();
+        let initial_storage = env::storage_usage();
 
         // Retrieve current balance or initialize it to zero
         let current_balance = self
@@ -14,10 +17,22 @@ impl Contract {
             .cloned() // Clone the value to avoid borrowing issues
             .unwrap_or(NearToken::from_yoctonear(0));
 
+        // Write the entries first so their storage can be measured
+        self.app_balances.insert(app_id.clone(), current_balance);
+        self.apps.insert(app_id.clone());
+        self.app_balances.flush();
+        self.apps.flush();
+        let storage_cost = env::storage_byte_cost()
+            .saturating_mul(env::storage_usage().saturating_sub(initial_storage) as u128);
+
         // Calculate the new balance
         let new_balance = current_balance
-            .checked_add(deposit_amount)
-            .expect("Balance overflow");
+            .checked_add(
+                deposit_amount
+                    .checked_sub(storage_cost)
+                    .or_fail(FastAuthError::DepositBelowStorageCost),
+            )
+            .or_fail(FastAuthError::BalanceOverflow);
 
         // Update the balance
         self.app_balances.insert(app_id.clone(), new_balance);
@@ -1,5 +1,2 @@
 pub mod debit;
 pub mod deposit;
-
-pub use debit::*;
-pub use deposit::*;
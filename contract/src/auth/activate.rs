@@ -1,14 +1,33 @@
 // key_management/add_keys.rs
 use crate::*;
+use near_sdk::CurveType;
 
 #[near]
 impl Contract {
+    /// Activates a user. With a `username`, also creates `<username>.<parent>` as a
+    /// readable NEAR account controlled by the MPC key, paid for by `app_id`.
     #[payable]
-    pub fn activate_account(&mut self, mpc_key: PublicKey, eth_address: AccountId, path: String) {
-        require!(
+    pub fn activate_account(
+        &mut self,
+        mpc_key: PublicKey,
+        eth_address: AccountId,
+        path: String,
+        ed25519_key: Option<PublicKey>,
+        username: Option<String>,
+        app_id: Option<AppID>,
+    ) {
+        ensure(
             env::predecessor_account_id() == self.oracle_account_id,
-            "Only admin can add session keys"
+            FastAuthError::OracleOnly,
         );
+        if let Some(ed25519_key) = &ed25519_key {
+            assert_ed25519_key(ed25519_key);
+        }
+        let sponsor = username.as_ref().map(|_| {
+            app_id.or_fail(FastAuthError::InvalidArgument(
+                "app_id is required to create a named account".into(),
+            ))
+        });
 
         let initial_storage = env::storage_usage();
 
@@ -16,11 +35,19 @@ impl Contract {
             mpc_key: mpc_key.clone(),
             eth_address: eth_address.clone(), // Store the EVM address
             path: path.clone(),
+            ed25519_key,
         };
-        require!(
-            self.bundler.insert(path.clone(), bundle).is_none(),
-            "User already activated"
+        ensure(
+            self.internal_get_bundle(&path).is_none(),
+            FastAuthError::UserAlreadyActivated,
         );
+        ensure(
+            self.paths_by_eth_address
+                .insert(eth_address.clone(), path.clone())
+                .is_none(),
+            FastAuthError::EthAddressAlreadyLinked,
+        );
+        self.internal_set_bundle(path.clone(), bundle);
 
         // Use the passed-in EVM address
         let eth_implicit_account = eth_address.clone();
@@ -29,7 +56,43 @@ impl Contract {
         Promise::new(eth_implicit_account.clone()).transfer(NearToken::from_millinear(10)); // Attach 0.01 NEAR for account creation
 
         self.bundler.flush();
+        self.paths_by_eth_address.flush();
+        self.flush_indexes();
         // Adjust the deposit based on storage usage
         self.adjust_deposit(initial_storage, env::storage_usage());
+
+        // Measured separately, as the app pays for the named account's storage
+        if let (Some(username), Some(app_id)) = (username, sponsor) {
+            self.internal_create_named_account(&path, &username, &mpc_key, &app_id);
+        }
     }
+
+    /// Links the key derived for the user's path in the MPC's EdDSA domain,
+    /// for users activated before ed25519 chains were supported.
+    #[payable]
+    pub fn set_ed25519_key(&mut self, path: MpcPath, ed25519_key: PublicKey) {
+        ensure(
+            env::predecessor_account_id() == self.oracle_account_id,
+            FastAuthError::OracleOnly,
+        );
+        assert_ed25519_key(&ed25519_key);
+
+        let initial_storage = env::storage_usage();
+
+        let mut bundle = self
+            .internal_get_bundle(&path)
+            .or_fail(FastAuthError::UserNotFound);
+        bundle.ed25519_key = Some(ed25519_key);
+        self.internal_set_bundle(path, bundle);
+
+        self.bundler.flush();
+        self.adjust_deposit(initial_storage, env::storage_usage());
+    }
+}
+
+fn assert_ed25519_key(key: &PublicKey) {
+    ensure(
+        key.curve_type() == CurveType::ED25519,
+        FastAuthError::InvalidArgument("ed25519_key must be an ed25519 key".into()),
+    );
 }
@@ -2,4 +2,5 @@
 //! Module for trial account management, including creation, activation, deletion, and exit.
 
 pub mod activate;
+pub mod near_account;
 pub mod session_keys;
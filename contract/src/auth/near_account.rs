@@ -0,0 +1,266 @@
+// auth/near_account.rs
+use crate::*;
+use near_sdk::PromiseResult;
+
+#[near]
+impl Contract {
+    /// Links a named NEAR account whose full-access key is the user's MPC key, letting
+    /// the user's session keys have transactions and delegate actions signed for it.
+    /// The oracle attests to the key: the contract cannot read another account's keys.
+    #[payable]
+    pub fn link_near_account(&mut self, path: MpcPath, account_id: AccountId) {
+        ensure(
+            env::predecessor_account_id() == self.oracle_account_id,
+            FastAuthError::OracleOnly,
+        );
+        ensure(
+            self.internal_get_bundle(&path).is_some(),
+            FastAuthError::UserNotFound,
+        );
+
+        let initial_storage = env::storage_usage();
+        self.internal_link_near_account(&path, &account_id);
+        self.adjust_deposit(initial_storage, env::storage_usage());
+    }
+
+    /// Callback of the named account's creation. If it failed, e.g. because the account
+    /// already exists on chain, the username is unlinked and the app refunded.
+    #[private]
+    pub fn on_named_account_created(
+        &mut self,
+        path: MpcPath,
+        account_id: AccountId,
+        app_id: AppID,
+        cost: NearToken,
+    ) -> bool {
+        // Registrars return `false` rather than failing when they can't create the account
+        let created = match env::promise_result(0) {
+            PromiseResult::Successful(value) => value != b"false",
+            _ => false,
+        };
+
+        if !created {
+            env::log_str(&format!(
+                "Failed to create {}, refunding app {}",
+                account_id, app_id
+            ));
+            self.near_accounts.remove(&path);
+            self.paths_by_near_account.remove(&account_id);
+            self.refund_app(cost, &app_id);
+
+            self.near_accounts.flush();
+            self.paths_by_near_account.flush();
+        }
+
+        created
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_link_near_account(&mut self, path: &MpcPath, account_id: &AccountId) {
+        ensure(
+            !self.near_accounts.contains_key(path),
+            FastAuthError::NearAccountAlreadyLinked,
+        );
+        ensure(
+            self.paths_by_near_account
+                .insert(account_id.clone(), path.clone())
+                .is_none(),
+            FastAuthError::NearAccountAlreadyLinked,
+        );
+        self.near_accounts.insert(path.clone(), account_id.clone());
+
+        self.near_accounts.flush();
+        self.paths_by_near_account.flush();
+    }
+
+    /// Creates `<username>.<parent>` with the user's MPC key as its full-access key and
+    /// links it to the user. The app pays for the account's balance and the link's storage.
+    pub(crate) fn internal_create_named_account(
+        &mut self,
+        path: &MpcPath,
+        username: &str,
+        mpc_key: &PublicKey,
+        app_id: &AppID,
+    ) -> Promise {
+        assert_valid_username(username);
+        let parent = self.get_named_account_parent();
+        let account_id: AccountId = format!("{}.{}", username, parent)
+            .parse()
+            .or_fail(FastAuthError::InvalidUsername("Account ID too long".into()));
+
+        let initial_storage = env::storage_usage();
+        self.internal_link_near_account(path, &account_id);
+        let storage_cost = env::storage_byte_cost()
+            .saturating_mul((env::storage_usage() - initial_storage) as u128);
+        let cost = storage_cost.saturating_add(NAMED_ACCOUNT_INITIAL_BALANCE);
+        self.charge_app(cost, app_id);
+
+        // The contract can only create its own sub-accounts; other parents are registrars
+        let create = if parent == env::current_account_id() {
+            Promise::new(account_id.clone())
+                .create_account()
+                .add_full_access_key(mpc_key.clone())
+                .transfer(NAMED_ACCOUNT_INITIAL_BALANCE)
+        } else {
+            Promise::new(parent).function_call(
+                "create_account".to_string(),
+                near_sdk::serde_json::json!({
+                    "new_account_id": account_id,
+                    "new_public_key": mpc_key,
+                })
+                .to_string()
+                .into_bytes(),
+                NAMED_ACCOUNT_INITIAL_BALANCE,
+                Gas::from_tgas(50),
+            )
+        };
+
+        create.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(Gas::from_tgas(10))
+                .on_named_account_created(path.clone(), account_id, app_id.clone(), cost),
+        )
+    }
+
+    /// The user's named NEAR account and the MPC key that controls it
+    pub(crate) fn internal_get_near_account(&self, path: &MpcPath) -> (AccountId, PublicKey) {
+        let account_id = self
+            .near_accounts
+            .get(path)
+            .cloned()
+            .or_fail(FastAuthError::NearAccountNotLinked);
+        let bundle = self
+            .internal_get_bundle(path)
+            .or_fail(FastAuthError::UserNotFound);
+        (account_id, bundle.mpc_key)
+    }
+}
+
+/// Checks `username` is a single NEAR account ID part: lowercase letters and digits,
+/// with `-` or `_` separators that can't lead, trail or follow one another.
+pub(crate) fn assert_valid_username(username: &str) {
+    ensure(
+        username.len() >= MIN_USERNAME_LENGTH,
+        FastAuthError::InvalidUsername(format!(
+            "Must be at least {} characters",
+            MIN_USERNAME_LENGTH
+        )),
+    );
+    ensure(
+        username
+            .bytes()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || is_separator(c)),
+        FastAuthError::InvalidUsername("Only a-z, 0-9, - and _ are allowed".into()),
+    );
+
+    let bytes = username.as_bytes();
+    ensure(
+        !is_separator(bytes[0])
+            && !is_separator(bytes[bytes.len() - 1])
+            && !bytes
+                .windows(2)
+                .any(|pair| is_separator(pair[0]) && is_separator(pair[1])),
+        FastAuthError::InvalidUsername("Separators can't lead, trail or repeat".into()),
+    );
+}
+
+fn is_separator(c: u8) -> bool {
+    c == b'-' || c == b'_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    const APP_ID: &str = "app";
+    const MPC_KEY: &str = "secp256k1:43y5hhS5mWqG4QWeEBqmJfDae1rWstRUETCvcELhzai5ataDC38sCnahANBd5ALUWTMHhPRM74geUt9D3TE5QhG3";
+    const ETH_ADDRESS: &str = "0x4a62316623ad457f02cdc5d997ded67a383ec569";
+    const OTHER_ETH_ADDRESS: &str = "0x827c5d7bde400f1716255ae54bb8a55cb43d0324";
+
+    fn setup(app_balance: NearToken) -> Contract {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id("fastauth.near".parse().unwrap())
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(NearToken::from_near(1))
+            .build());
+        let mut contract = Contract::new(accounts(2), accounts(1), accounts(3));
+        contract
+            .app_balances
+            .insert(APP_ID.to_string(), app_balance);
+        contract
+    }
+
+    fn activate(contract: &mut Contract, path: &str, eth_address: &str, username: &str) {
+        contract.activate_account(
+            MPC_KEY.parse().unwrap(),
+            eth_address.parse().unwrap(),
+            path.to_string(),
+            None,
+            Some(username.to_string()),
+            Some(APP_ID.to_string()),
+        );
+    }
+
+    #[test]
+    fn usernames_follow_account_id_rules() {
+        for username in ["alice", "al", "alice-01", "a_b-c"] {
+            assert_valid_username(username);
+        }
+    }
+
+    #[test]
+    fn invalid_usernames_are_rejected() {
+        for username in [
+            "a",
+            "Alice",
+            "alice.near",
+            "-alice",
+            "alice_",
+            "al--ice",
+            "al ice",
+        ] {
+            let result = std::panic::catch_unwind(|| assert_valid_username(username));
+            assert!(result.is_err(), "{} should be rejected", username);
+        }
+    }
+
+    #[test]
+    fn activation_links_a_named_sub_account_paid_by_the_app() {
+        let mut contract = setup(NearToken::from_near(1));
+        activate(&mut contract, "user-path", ETH_ADDRESS, "alice");
+
+        let account_id: AccountId = "alice.fastauth.near".parse().unwrap();
+        assert_eq!(
+            contract.get_near_account("user-path".to_string()),
+            Some(account_id.clone())
+        );
+        assert_eq!(
+            contract
+                .get_bundle_by_near_account(account_id)
+                .map(|bundle| bundle.path),
+            Some("user-path".to_string())
+        );
+
+        let charged =
+            NearToken::from_near(1).saturating_sub(contract.get_app_balance(APP_ID.to_string()));
+        assert!(charged > NAMED_ACCOUNT_INITIAL_BALANCE);
+    }
+
+    #[test]
+    #[should_panic(expected = ":1010:")]
+    fn usernames_are_unique() {
+        let mut contract = setup(NearToken::from_near(1));
+        activate(&mut contract, "user-path", ETH_ADDRESS, "alice");
+        activate(&mut contract, "other-path", OTHER_ETH_ADDRESS, "alice");
+    }
+
+    #[test]
+    #[should_panic(expected = ":3001:")]
+    fn app_must_afford_the_named_account() {
+        let mut contract = setup(NearToken::from_millinear(1));
+        activate(&mut contract, "user-path", ETH_ADDRESS, "alice");
+    }
+}
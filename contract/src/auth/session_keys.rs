@@ -4,18 +4,21 @@ use crate::*;
 #[near]
 impl Contract {
     #[payable]
-    pub fn add_session_key(&mut self, public_key: PublicKey, path: MpcPath, app_id: AppID) {
-        require!(
+    pub fn add_session_key(&mut self, public_key: SessionKey, path: MpcPath, app_id: AppID) {
+        ensure(
             env::predecessor_account_id() == self.oracle_account_id,
-            "Only oracle can add session keys"
+            FastAuthError::OracleOnly,
+        );
+        ensure(
+            !self.frozen_users.contains(&path),
+            FastAuthError::UserFrozen,
         );
 
         let initial_storage = env::storage_usage();
 
         // Remove existing session key for user and app
-        if let Some(existing_pk) = self.session_keys.get(&(path.clone(), app_id.clone())) {
-            self.key_usage_by_pk.remove(existing_pk);
-            self.session_keys.remove(&(path.clone(), app_id.clone()));
+        if let Some(existing_pk) = self.session_keys.remove(&(path.clone(), app_id.clone())) {
+            self.internal_remove_key_usage(&existing_pk);
         }
 
         let key_usage = KeyUsage {
@@ -26,11 +29,67 @@ impl Contract {
 
         self.session_keys
             .insert((path.clone(), app_id.clone()), public_key.clone());
-        self.key_usage_by_pk.insert(public_key, key_usage);
+        // Reserve the key's message nonce so its storage is paid for here
+        if !self.message_nonces.contains_key(&public_key) {
+            self.message_nonces.insert(public_key.clone(), 0);
+        }
+        self.internal_set_key_usage(public_key, key_usage);
+        self.internal_index_session_key(&path, &app_id);
 
         self.session_keys.flush();
         self.key_usage_by_pk.flush();
+        self.message_nonces.flush();
+        self.flush_indexes();
 
         self.adjust_deposit(initial_storage, env::storage_usage());
     }
+
+    /// Records that a user holds a session key for an app in the enumeration indexes
+    pub(crate) fn internal_index_session_key(&mut self, path: &MpcPath, app_id: &AppID) {
+        self.apps.insert(app_id.clone());
+
+        let apps = self.apps_by_path.entry(path.clone()).or_insert_with(|| {
+            IterableSet::new(StorageKeys::AppsByPathInner {
+                path_hash: env::sha256_array(path.as_bytes()),
+            })
+        });
+        apps.insert(app_id.clone());
+        apps.flush();
+
+        let paths = self.paths_by_app.entry(app_id.clone()).or_insert_with(|| {
+            IterableSet::new(StorageKeys::PathsByAppInner {
+                app_hash: env::sha256_array(app_id.as_bytes()),
+            })
+        });
+        paths.insert(path.clone());
+        paths.flush();
+    }
+
+    /// Removes a user's session key for an app from the enumeration indexes
+    pub(crate) fn internal_unindex_session_key(&mut self, path: &MpcPath, app_id: &AppID) {
+        if let Some(apps) = self.apps_by_path.get_mut(path) {
+            apps.remove(app_id);
+            apps.flush();
+        }
+        if let Some(paths) = self.paths_by_app.get_mut(app_id) {
+            paths.remove(path);
+            paths.flush();
+        }
+    }
+
+    /// Records `nonce` as used by the session key, which must be above every nonce the
+    /// key has signed a message-signing payload with. Entries outlive the key, so its
+    /// old signatures can't be replayed if it is added again.
+    pub(crate) fn internal_use_message_nonce(&mut self, session_key: &SessionKey, nonce: u64) {
+        let last_nonce = self.message_nonces.get(session_key).copied().unwrap_or(0);
+        ensure(nonce > last_nonce, FastAuthError::StaleNonce);
+        self.message_nonces.insert(session_key.clone(), nonce);
+    }
+
+    pub(crate) fn flush_indexes(&mut self) {
+        self.paths.flush();
+        self.apps.flush();
+        self.apps_by_path.flush();
+        self.paths_by_app.flush();
+    }
 }
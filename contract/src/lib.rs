@@ -1,8 +1,8 @@
 // lib.rs
 use near_sdk::json_types::{Base58CryptoHash, U64};
-use near_sdk::store::LookupMap;
+use near_sdk::store::{IterableMap, IterableSet, LookupMap, LookupSet};
 use near_sdk::{
-    env, near, require, AccountId, Allowance, BorshStorageKey, Gas, GasWeight, NearToken,
+    env, near, require, AccountId, BorshStorageKey, CryptoHash, Gas, GasWeight, NearToken,
     PanicOnDefault, Promise, PublicKey,
 };
 
@@ -17,6 +17,7 @@ use omni_transaction::{
 };
 use std::collections::HashMap;
 
+pub mod admin;
 pub mod app_balances;
 pub mod auth;
 pub mod models;
@@ -24,6 +25,7 @@ pub mod trial_user;
 pub mod utils;
 pub mod views;
 
+pub use admin::*;
 pub use app_balances::*;
 pub use auth::*;
 pub use models::*;
@@ -34,29 +36,75 @@ pub use utils::*;
 #[derive(PanicOnDefault)]
 pub struct Contract {
     // Keys
-    pub session_keys: LookupMap<(MpcPath, AppID), PublicKey>,
-    pub key_usage_by_pk: LookupMap<PublicKey, KeyUsage>,
-    pub bundler: LookupMap<MpcPath, Bundle>,
+    pub session_keys: LookupMap<(MpcPath, AppID), SessionKey>,
+    pub key_usage_by_pk: LookupMap<SessionKey, VersionedKeyUsage>,
+    pub bundler: LookupMap<MpcPath, VersionedBundle>,
+    // Last nonce each session key signed a message-signing payload with
+    pub message_nonces: LookupMap<SessionKey, u64>,
+    pub paths_by_eth_address: LookupMap<AccountId, MpcPath>,
+    // Named NEAR accounts whose full-access key is the user's MPC key
+    pub near_accounts: LookupMap<MpcPath, AccountId>,
+    pub paths_by_near_account: LookupMap<AccountId, MpcPath>,
 
     // Apps
     pub app_balances: LookupMap<AppID, NearToken>,
+    pub trial_data_by_app: LookupMap<AppID, VersionedTrialData>,
+
+    // Enumeration indexes
+    pub paths: IterableSet<MpcPath>,
+    pub apps: IterableSet<AppID>,
+    pub apps_by_path: LookupMap<MpcPath, IterableSet<AppID>>,
+    pub paths_by_app: LookupMap<AppID, IterableSet<MpcPath>>,
+
+    // Emergency controls
+    pub paused: bool,
+    pub paused_apps: LookupSet<AppID>,
+    pub frozen_users: LookupSet<MpcPath>,
+
+    // Supported EVM chains
+    pub evm_chains: IterableMap<u64, EvmChain>,
 
     // Admin
+    pub owner_id: AccountId,
     pub oracle_account_id: AccountId,
     pub mpc_contract: AccountId,
+    pub mpc_config: MpcConfig,
+    // Account usernames are created under; the contract itself if unset
+    pub named_account_parent: Option<AccountId>,
 }
 
 #[near]
 impl Contract {
     #[init]
-    pub fn new(oracle_account_id: AccountId, mpc_contract: AccountId) -> Self {
+    pub fn new(owner_id: AccountId, oracle_account_id: AccountId, mpc_contract: AccountId) -> Self {
+        StateVersion::CURRENT.write();
+
+        let mut evm_chains = IterableMap::new(StorageKeys::EvmChains);
+        evm_chains.insert(NEAR_EVM_CHAIN_ID, EvmChain::near_evm());
+
         Self {
             session_keys: LookupMap::new(StorageKeys::SessionKeys),
-            key_usage_by_pk: LookupMap::new(StorageKeys::KeyUsageByPK),
-            bundler: LookupMap::new(StorageKeys::Bundler),
+            key_usage_by_pk: LookupMap::new(StorageKeys::VersionedKeyUsageByPK),
+            bundler: LookupMap::new(StorageKeys::VersionedBundler),
+            message_nonces: LookupMap::new(StorageKeys::MessageNonces),
+            paths_by_eth_address: LookupMap::new(StorageKeys::PathsByEthAddress),
+            near_accounts: LookupMap::new(StorageKeys::NearAccounts),
+            paths_by_near_account: LookupMap::new(StorageKeys::PathsByNearAccount),
             app_balances: LookupMap::new(StorageKeys::AppBalances),
+            trial_data_by_app: LookupMap::new(StorageKeys::TrialDataById),
+            paths: IterableSet::new(StorageKeys::Paths),
+            apps: IterableSet::new(StorageKeys::Apps),
+            apps_by_path: LookupMap::new(StorageKeys::AppsByPath),
+            paths_by_app: LookupMap::new(StorageKeys::PathsByApp),
+            paused: false,
+            paused_apps: LookupSet::new(StorageKeys::PausedApps),
+            frozen_users: LookupSet::new(StorageKeys::FrozenUsers),
+            evm_chains,
+            owner_id,
             oracle_account_id,
             mpc_contract,
+            mpc_config: MpcConfig::default(),
+            named_account_parent: None,
         }
     }
 }
@@ -24,6 +24,7 @@ pub struct NearAction {
 pub struct EvmAction {
     pub chain_id: u64, // Chain ID for EVM
     pub method_name: String,
+    pub selector: [u8; 4], // First 4 bytes of keccak256 of the canonical signature
     pub contract_address: Address,
     pub gas_limit: u128,
     pub value: U128, // Value in wei
@@ -0,0 +1,33 @@
+// models/bitcoin.rs
+use crate::*;
+use near_sdk::json_types::U64;
+
+/// Restricts which Bitcoin transactions an app's session keys may have signed.
+/// Change paid back to the user's own P2WPKH script is always allowed and not counted.
+#[derive(Clone)]
+#[near(serializers = [json, borsh])]
+pub struct BitcoinConstraints {
+    /// Hex-encoded output scripts that may be paid. Any script if unset.
+    pub allowed_scripts: Option<Vec<String>>,
+    /// Cap on the satoshis leaving the user's wallet: payments to scripts other than
+    /// the user's own plus the fee
+    pub max_amount: Option<U64>,
+}
+
+impl BitcoinConstraints {
+    pub fn is_script_allowed(&self, script_pubkey: &[u8]) -> bool {
+        match &self.allowed_scripts {
+            Some(scripts) => scripts.iter().any(|script| {
+                hex::decode(script.trim_start_matches("0x")).as_deref() == Ok(script_pubkey)
+            }),
+            None => true,
+        }
+    }
+
+    pub fn is_amount_allowed(&self, amount: u64) -> bool {
+        match &self.max_amount {
+            Some(max_amount) => amount <= max_amount.0,
+            None => true,
+        }
+    }
+}
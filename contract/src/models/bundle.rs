@@ -7,4 +7,20 @@ pub struct Bundle {
     pub eth_address: AccountId,
     // represents hash of Google ID
     pub path: MpcPath,
+    /// Key derived for `path` in the MPC's EdDSA domain, used on ed25519 chains such as Solana
+    #[serde(default)]
+    pub ed25519_key: Option<PublicKey>,
+}
+
+impl Bundle {
+    /// Raw 32 bytes of the user's ed25519 key, which double as their Solana address.
+    pub fn ed25519_key_bytes(&self) -> [u8; 32] {
+        let key = self
+            .ed25519_key
+            .as_ref()
+            .or_fail(FastAuthError::Ed25519KeyNotLinked);
+        key.as_bytes()[1..]
+            .try_into()
+            .or_fail(FastAuthError::InvalidKeyLength)
+    }
 }
@@ -1,3 +1,4 @@
+use ethereum_types::U256;
 use near_sdk::json_types::U128;
 use omni_transaction::evm::types::Address;
 
@@ -24,11 +25,112 @@ pub struct NearConstraints {
 #[derive(Clone)]
 #[near(serializers = [json, borsh])]
 pub struct EvmConstraints {
-    pub allowed_methods: Vec<String>,    // Function signatures or names
+    pub allowed_methods: Vec<String>, // Canonical signatures or 0x-prefixed selectors
     pub allowed_contracts: Vec<Address>, // Ethereum addresses
-    pub max_gas: Option<u64>,            // Gas limit
-    pub max_value: Option<U128>,         // Value in wei
-    pub initial_deposit: String,         // Value in wei
+    pub max_gas: Option<u64>,         // Gas limit
+    pub max_value: Option<U128>,      // Value in wei
+    pub initial_deposit: String,      // Value in wei
+    #[serde(default)]
+    pub allowed_tokens: Vec<EvmTokenRule>, // Tokens typed token actions may move
+}
+
+impl EvmConstraints {
+    /// Checks a function selector against `allowed_methods`. Entries are either a
+    /// canonical signature such as `transfer(address,uint256)` or a selector such as
+    /// `0xa9059cbb`; bare names match nothing, as they cannot tell overloads apart.
+    pub fn is_selector_allowed(&self, selector: &[u8; 4]) -> bool {
+        self.allowed_methods
+            .iter()
+            .any(|entry| method_selector(entry).as_ref() == Some(selector))
+    }
+
+    /// Rejects `allowed_methods` entries that stand for no selector, such as bare names.
+    pub fn assert_valid(&self) {
+        for entry in &self.allowed_methods {
+            ensure(
+                method_selector(entry).is_some(),
+                FastAuthError::InvalidArgument(format!(
+                    "EVM method {} must be a canonical signature or a 4-byte selector",
+                    entry
+                )),
+            );
+        }
+        for rule in &self.allowed_tokens {
+            rule.assert_valid();
+        }
+    }
+}
+
+/// Selector an `allowed_methods` entry stands for.
+fn method_selector(entry: &str) -> Option<[u8; 4]> {
+    let entry = entry.trim();
+    if let Some(hex_selector) = entry.strip_prefix("0x") {
+        return hex::decode(hex_selector).ok()?.try_into().ok();
+    }
+    if !entry.contains('(') {
+        return None;
+    }
+
+    let signature: String = entry.chars().filter(|c| !c.is_whitespace()).collect();
+    let hash = env::keccak256_array(signature.as_bytes());
+    Some([hash[0], hash[1], hash[2], hash[3]])
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[near(serializers = [json, borsh])]
+pub enum TokenStandard {
+    Erc20,
+    Erc721,
+    Erc1155,
+}
+
+/// A token typed token actions may be performed on, with its caps.
+#[derive(Clone)]
+#[near(serializers = [json, borsh])]
+pub struct EvmTokenRule {
+    pub token: Address,
+    pub standard: TokenStandard,
+    /// Cap on a single transfer or approval, as a decimal string. ERC-20
+    /// approvals are rejected for tokens without a cap.
+    pub max_amount: Option<String>,
+    /// Token ids that may be moved, as decimal strings. Any id if unset.
+    pub allowed_token_ids: Option<Vec<String>>,
+}
+
+impl EvmTokenRule {
+    /// Rejects a cap or token ids that aren't decimal integers, so they can't fail
+    /// later when a session key's action is checked against them.
+    pub fn assert_valid(&self) {
+        if let Some(max_amount) = &self.max_amount {
+            parse_decimal(max_amount, "max_amount");
+        }
+        for token_id in self.allowed_token_ids.iter().flatten() {
+            parse_decimal(token_id, "token id");
+        }
+    }
+
+    pub fn is_amount_allowed(&self, amount: U256) -> bool {
+        match &self.max_amount {
+            Some(max_amount) => amount <= parse_decimal(max_amount, "max_amount"),
+            None => true,
+        }
+    }
+
+    pub fn is_token_id_allowed(&self, token_id: U256) -> bool {
+        match &self.allowed_token_ids {
+            Some(token_ids) => token_ids
+                .iter()
+                .any(|id| parse_decimal(id, "token id") == token_id),
+            None => true,
+        }
+    }
+}
+
+fn parse_decimal(value: &str, field: &str) -> U256 {
+    U256::from_dec_str(value).or_fail(FastAuthError::InvalidArgument(format!(
+        "Invalid {} {}",
+        field, value
+    )))
 }
 
 #[derive(Clone)]
@@ -42,9 +144,9 @@ pub enum ExtChainConstraints {
 #[derive(Clone)]
 #[near(serializers = [json, borsh])]
 pub struct ExtEvmConstraints {
-    pub allowed_methods: Vec<String>,   // Function signatures or names
+    pub allowed_methods: Vec<String>, // Canonical signatures or selectors
     pub allowed_contracts: Vec<String>, // Ethereum addresses
-    pub max_gas: Option<u64>,           // Gas limit
-    pub max_value: Option<U128>,        // Value in wei
-    pub initial_deposit: String,        // Value in wei
+    pub max_gas: Option<u64>,         // Gas limit
+    pub max_value: Option<U128>,      // Value in wei
+    pub initial_deposit: String,      // Value in wei
 }
@@ -46,7 +46,7 @@ impl From<&str> for ChainId {
 // Implementing PartialOrd manually
 impl PartialOrd for ChainId {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.0.partial_cmp(&other.0)
+        Some(self.cmp(other))
     }
 }
 
@@ -1,7 +1,9 @@
 // models/constants.rs
+use near_sdk::NearToken;
+
 /// Access key methods name for trial accounts
 pub const TRIAL_ACCESS_KEY_METHODS: &str =
-    "create_trial,activate_trial,delete_trial,add_trial_keys,call_evm_contract,call_near_contract,exit_trial";
+    "create_trial,activate_trial,delete_trial,add_trial_keys,call_evm_contract,call_evm_token_action,call_near_contract,exit_trial";
 
 /// Length of an Ed25519 public key
 pub const ED25519_PUBLIC_KEY_LENGTH: usize = 32;
@@ -9,9 +11,37 @@ pub const ED25519_PUBLIC_KEY_LENGTH: usize = 32;
 /// Length of a SECP256K1 public key
 pub const SECP256K1_PUBLIC_KEY_LENGTH: usize = 64;
 
+/// Length of a compressed SEC1 P-256 public key
+pub const P256_PUBLIC_KEY_LENGTH: usize = 33;
+
+/// Order of the secp256k1 group, used to normalize signatures to low-s
+pub const SECP256K1_ORDER: &str =
+    "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141";
+
 /// Chain ID for NEAR on testnet
 pub const NEAR_EVM_CHAIN_ID: u64 = 398;
 
+/// EVM gas limit of a NEAR transaction carrying the 300 Tgas maximum
+pub const NEAR_EVM_MAX_GAS_LIMIT: u128 = 3_000_000;
+
+/// Most inputs a Bitcoin transaction may have, as each needs its own MPC signature call
+pub const MAX_BITCOIN_INPUTS: usize = 4;
+
+/// Default MPC signature domain of ed25519 keys
+pub const MPC_ED25519_DOMAIN_ID: u64 = 1;
+
+/// Largest serialized Solana transaction a validator accepts
+pub const SOLANA_MAX_TRANSACTION_SIZE: usize = 1232;
+
+/// Balance a named account is created with, enough for its storage
+pub const NAMED_ACCOUNT_INITIAL_BALANCE: NearToken = NearToken::from_millinear(10);
+
+/// Shortest username a named account may be created with
+pub const MIN_USERNAME_LENGTH: usize = 2;
+
+/// Number of entries returned by paginated views when no limit is given
+pub const DEFAULT_PAGE_LIMIT: u32 = 50;
+
 /// Type alias for Trial IDs
 pub type TrialId = u32;
 pub type UserId = u32;
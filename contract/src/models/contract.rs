@@ -9,4 +9,19 @@ pub enum StorageKeys {
     TrialDataById,
     KeyUsageByPK,
     Bundler,
+    PausedApps,
+    FrozenUsers,
+    VersionedKeyUsageByPK,
+    VersionedBundler,
+    Paths,
+    Apps,
+    AppsByPath,
+    AppsByPathInner { path_hash: CryptoHash },
+    PathsByApp,
+    PathsByAppInner { app_hash: CryptoHash },
+    PathsByEthAddress,
+    EvmChains,
+    NearAccounts,
+    PathsByNearAccount,
+    MessageNonces,
 }
@@ -0,0 +1,325 @@
+// models/errors.rs
+use crate::*;
+use std::fmt;
+
+/// Prefix of every panic message raised through `FastAuthError`
+pub const FASTAUTH_ERROR_PREFIX: &str = "FASTAUTH_ERR";
+
+/// Errors the contract fails with. Panics read `FASTAUTH_ERR:<code>:<message>`.
+///
+/// Codes are stable and grouped by area; never renumber or reuse one.
+/// - 1xxx: accounts and session keys
+/// - 2xxx: session signatures
+/// - 3xxx: app balances
+/// - 4xxx: app policy and emergency controls
+/// - 5xxx: EVM chain registry
+/// - 6xxx: malformed payloads
+/// - 7xxx: MPC signatures
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FastAuthError {
+    OracleOnly,
+    UserAlreadyActivated,
+    EthAddressAlreadyLinked,
+    UserFrozen,
+    UserNotFound,
+    SessionKeyNotFound,
+    SessionKeyAppMismatch,
+    Ed25519KeyNotLinked,
+    NearAccountNotLinked,
+    NearAccountAlreadyLinked,
+    InvalidUsername(String),
+
+    InvalidSignature,
+    InvalidSignatureLength,
+    SignatureKindMismatch,
+    InvalidKeyLength,
+    StaleNonce,
+
+    InsufficientAppBalance,
+    BalanceOverflow,
+    DepositBelowStorageCost,
+
+    TrialDataNotFound,
+    TrialExpired,
+    MethodNotAllowed,
+    ContractNotAllowed,
+    GasLimitExceeded,
+    DepositLimitExceeded,
+    TransactionLimitReached,
+    ContractPaused,
+    AppPaused,
+    TokenNotAllowed,
+    TokenStandardMismatch,
+    UnlimitedApproval,
+    ApprovalCapRequired,
+    TokenAmountExceedsCap,
+    TokenIdNotAllowed,
+    TypedDataNotEnabled,
+    TypedDataDomainNotAllowed,
+    TypedDataPrimaryTypeNotAllowed,
+    MessageSigningNotEnabled,
+    MessageFormatNotAllowed,
+    BitcoinSigningNotEnabled,
+    BitcoinScriptNotAllowed,
+    BitcoinAmountExceedsCap,
+    SolanaSigningNotEnabled,
+    SolanaProgramNotAllowed,
+    SolanaMintNotAllowed,
+    SolanaAmountExceedsCap,
+
+    ChainNotSupported,
+    ChainDisabled,
+    FeeExceedsCap,
+    GasLimitExceedsCap,
+    ValueExceedsCap,
+
+    InvalidPayload(String),
+    InvalidTypedData(String),
+    InvalidArgument(String),
+    EncodingFailed,
+    InvalidBitcoinTransaction(String),
+    UnsupportedBitcoinInput(String),
+    InvalidSolanaTransaction(String),
+    InvalidNearTransaction(String),
+
+    MpcSignatureFailed,
+    InvalidMpcSignature(String),
+}
+
+impl FastAuthError {
+    pub fn code(&self) -> u32 {
+        match self {
+            FastAuthError::OracleOnly => 1001,
+            FastAuthError::UserAlreadyActivated => 1002,
+            FastAuthError::EthAddressAlreadyLinked => 1003,
+            FastAuthError::UserFrozen => 1004,
+            FastAuthError::UserNotFound => 1005,
+            FastAuthError::SessionKeyNotFound => 1006,
+            FastAuthError::SessionKeyAppMismatch => 1007,
+            FastAuthError::Ed25519KeyNotLinked => 1008,
+            FastAuthError::NearAccountNotLinked => 1009,
+            FastAuthError::NearAccountAlreadyLinked => 1010,
+            FastAuthError::InvalidUsername(_) => 1011,
+
+            FastAuthError::InvalidSignature => 2001,
+            FastAuthError::InvalidSignatureLength => 2002,
+            FastAuthError::SignatureKindMismatch => 2003,
+            FastAuthError::InvalidKeyLength => 2004,
+            FastAuthError::StaleNonce => 2005,
+
+            FastAuthError::InsufficientAppBalance => 3001,
+            FastAuthError::BalanceOverflow => 3002,
+            FastAuthError::DepositBelowStorageCost => 3003,
+
+            FastAuthError::TrialDataNotFound => 4001,
+            FastAuthError::TrialExpired => 4002,
+            FastAuthError::MethodNotAllowed => 4003,
+            FastAuthError::ContractNotAllowed => 4004,
+            FastAuthError::GasLimitExceeded => 4005,
+            FastAuthError::DepositLimitExceeded => 4006,
+            FastAuthError::TransactionLimitReached => 4007,
+            FastAuthError::ContractPaused => 4008,
+            FastAuthError::AppPaused => 4009,
+            FastAuthError::TokenNotAllowed => 4010,
+            FastAuthError::TokenStandardMismatch => 4011,
+            FastAuthError::UnlimitedApproval => 4012,
+            FastAuthError::ApprovalCapRequired => 4013,
+            FastAuthError::TokenAmountExceedsCap => 4014,
+            FastAuthError::TokenIdNotAllowed => 4015,
+            FastAuthError::TypedDataNotEnabled => 4016,
+            FastAuthError::TypedDataDomainNotAllowed => 4017,
+            FastAuthError::TypedDataPrimaryTypeNotAllowed => 4018,
+            FastAuthError::MessageSigningNotEnabled => 4019,
+            FastAuthError::MessageFormatNotAllowed => 4020,
+            FastAuthError::BitcoinSigningNotEnabled => 4021,
+            FastAuthError::BitcoinScriptNotAllowed => 4022,
+            FastAuthError::BitcoinAmountExceedsCap => 4023,
+            FastAuthError::SolanaSigningNotEnabled => 4024,
+            FastAuthError::SolanaProgramNotAllowed => 4025,
+            FastAuthError::SolanaMintNotAllowed => 4026,
+            FastAuthError::SolanaAmountExceedsCap => 4027,
+
+            FastAuthError::ChainNotSupported => 5001,
+            FastAuthError::ChainDisabled => 5002,
+            FastAuthError::FeeExceedsCap => 5003,
+            FastAuthError::GasLimitExceedsCap => 5004,
+            FastAuthError::ValueExceedsCap => 5005,
+
+            FastAuthError::InvalidPayload(_) => 6001,
+            FastAuthError::InvalidTypedData(_) => 6002,
+            FastAuthError::InvalidArgument(_) => 6003,
+            FastAuthError::EncodingFailed => 6004,
+            FastAuthError::InvalidBitcoinTransaction(_) => 6005,
+            FastAuthError::UnsupportedBitcoinInput(_) => 6006,
+            FastAuthError::InvalidSolanaTransaction(_) => 6007,
+            FastAuthError::InvalidNearTransaction(_) => 6008,
+
+            FastAuthError::MpcSignatureFailed => 7001,
+            FastAuthError::InvalidMpcSignature(_) => 7002,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            FastAuthError::OracleOnly => "Only oracle can call this method".to_string(),
+            FastAuthError::UserAlreadyActivated => "User already activated".to_string(),
+            FastAuthError::EthAddressAlreadyLinked => {
+                "Eth address already linked to a user".to_string()
+            }
+            FastAuthError::UserFrozen => "User is frozen".to_string(),
+            FastAuthError::UserNotFound => "User not found".to_string(),
+            FastAuthError::SessionKeyNotFound => "Public key not recognized".to_string(),
+            FastAuthError::SessionKeyAppMismatch => {
+                "Session key not associated with this app".to_string()
+            }
+            FastAuthError::Ed25519KeyNotLinked => "No ed25519 key linked to this user".to_string(),
+            FastAuthError::NearAccountNotLinked => {
+                "No NEAR account linked to this user".to_string()
+            }
+            FastAuthError::NearAccountAlreadyLinked => {
+                "NEAR account already linked to a user".to_string()
+            }
+            FastAuthError::InvalidUsername(reason) => format!("Invalid username: {}", reason),
+
+            FastAuthError::InvalidSignature => "Invalid signature".to_string(),
+            FastAuthError::InvalidSignatureLength => "Invalid signature length".to_string(),
+            FastAuthError::SignatureKindMismatch => {
+                "Signature kind does not match session key type".to_string()
+            }
+            FastAuthError::InvalidKeyLength => "Invalid key length".to_string(),
+            FastAuthError::StaleNonce => {
+                "Nonce must be above the session key's last message nonce".to_string()
+            }
+
+            FastAuthError::InsufficientAppBalance => {
+                "Insufficient app balance for transaction costs".to_string()
+            }
+            FastAuthError::BalanceOverflow => "Balance overflow".to_string(),
+            FastAuthError::DepositBelowStorageCost => {
+                "Deposit does not cover the storage of the app's balance".to_string()
+            }
+
+            FastAuthError::TrialDataNotFound => "Trial data not found for this app".to_string(),
+            FastAuthError::TrialExpired => "Trial period has expired".to_string(),
+            FastAuthError::MethodNotAllowed => "Method not allowed".to_string(),
+            FastAuthError::ContractNotAllowed => "Contract not allowed".to_string(),
+            FastAuthError::GasLimitExceeded => "Gas limit exceeded".to_string(),
+            FastAuthError::DepositLimitExceeded => "Deposit limit exceeded".to_string(),
+            FastAuthError::TransactionLimitReached => "Transaction limit reached".to_string(),
+            FastAuthError::ContractPaused => "Contract is paused".to_string(),
+            FastAuthError::AppPaused => "App is paused".to_string(),
+            FastAuthError::TokenNotAllowed => "Token not allowed".to_string(),
+            FastAuthError::TokenStandardMismatch => {
+                "Token standard does not match the allowed token".to_string()
+            }
+            FastAuthError::UnlimitedApproval => "Unlimited approvals are not allowed".to_string(),
+            FastAuthError::ApprovalCapRequired => {
+                "Approvals require an amount cap for the token".to_string()
+            }
+            FastAuthError::TokenAmountExceedsCap => "Token amount exceeds cap".to_string(),
+            FastAuthError::TokenIdNotAllowed => "Token id not allowed".to_string(),
+            FastAuthError::TypedDataNotEnabled => {
+                "Typed data signing not enabled for this app".to_string()
+            }
+            FastAuthError::TypedDataDomainNotAllowed => {
+                "Typed data domain not allowed for this app".to_string()
+            }
+            FastAuthError::TypedDataPrimaryTypeNotAllowed => {
+                "Typed data primary type not allowed for this app".to_string()
+            }
+            FastAuthError::MessageSigningNotEnabled => {
+                "Message signing not enabled for this app".to_string()
+            }
+            FastAuthError::MessageFormatNotAllowed => {
+                "Message format not allowed for this app".to_string()
+            }
+            FastAuthError::BitcoinSigningNotEnabled => {
+                "Bitcoin signing not enabled for this app".to_string()
+            }
+            FastAuthError::BitcoinScriptNotAllowed => {
+                "Bitcoin output script not allowed for this app".to_string()
+            }
+            FastAuthError::BitcoinAmountExceedsCap => "Bitcoin amount exceeds cap".to_string(),
+            FastAuthError::SolanaSigningNotEnabled => {
+                "Solana signing not enabled for this app".to_string()
+            }
+            FastAuthError::SolanaProgramNotAllowed => {
+                "Solana program not allowed for this app".to_string()
+            }
+            FastAuthError::SolanaMintNotAllowed => {
+                "Solana token mint not allowed for this app".to_string()
+            }
+            FastAuthError::SolanaAmountExceedsCap => "Solana amount exceeds cap".to_string(),
+
+            FastAuthError::ChainNotSupported => "EVM chain not supported".to_string(),
+            FastAuthError::ChainDisabled => "EVM chain is disabled".to_string(),
+            FastAuthError::FeeExceedsCap => "Fee per gas exceeds the chain's cap".to_string(),
+            FastAuthError::GasLimitExceedsCap => "Gas limit exceeds the chain's cap".to_string(),
+            FastAuthError::ValueExceedsCap => "Value exceeds the chain's cap".to_string(),
+
+            FastAuthError::InvalidPayload(detail) => format!("Invalid payload: {}", detail),
+            FastAuthError::InvalidTypedData(detail) => format!("Invalid typed data: {}", detail),
+            FastAuthError::InvalidArgument(detail) => format!("Invalid argument: {}", detail),
+            FastAuthError::EncodingFailed => "Failed to encode input".to_string(),
+            FastAuthError::InvalidBitcoinTransaction(detail) => {
+                format!("Invalid Bitcoin transaction: {}", detail)
+            }
+            FastAuthError::UnsupportedBitcoinInput(detail) => {
+                format!("Unsupported Bitcoin input: {}", detail)
+            }
+            FastAuthError::InvalidSolanaTransaction(detail) => {
+                format!("Invalid Solana transaction: {}", detail)
+            }
+            FastAuthError::InvalidNearTransaction(detail) => {
+                format!("Invalid NEAR transaction: {}", detail)
+            }
+
+            FastAuthError::MpcSignatureFailed => {
+                "Failed to get signature from MPC contract".to_string()
+            }
+            FastAuthError::InvalidMpcSignature(detail) => {
+                format!("Invalid MPC signature: {}", detail)
+            }
+        }
+    }
+
+    pub fn panic(&self) -> ! {
+        env::panic_str(&self.to_string())
+    }
+}
+
+impl fmt::Display for FastAuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}",
+            FASTAUTH_ERROR_PREFIX,
+            self.code(),
+            self.message()
+        )
+    }
+}
+
+/// `require!` failing with a `FastAuthError`.
+pub fn ensure(condition: bool, error: FastAuthError) {
+    if !condition {
+        error.panic();
+    }
+}
+
+/// `expect` failing with a `FastAuthError`.
+pub trait OrFastAuthError<T> {
+    fn or_fail(self, error: FastAuthError) -> T;
+}
+
+impl<T> OrFastAuthError<T> for Option<T> {
+    fn or_fail(self, error: FastAuthError) -> T {
+        self.unwrap_or_else(|| error.panic())
+    }
+}
+
+impl<T, E> OrFastAuthError<T> for Result<T, E> {
+    fn or_fail(self, error: FastAuthError) -> T {
+        self.unwrap_or_else(|_| error.panic())
+    }
+}
@@ -0,0 +1,57 @@
+// models/evm_chain.rs
+use crate::*;
+use near_sdk::json_types::U128;
+
+/// An EVM chain the contract will sign transactions for, with the caps
+/// every transaction on it must respect.
+#[derive(Clone)]
+#[near(serializers = [json, borsh])]
+pub struct EvmChain {
+    pub chain_id: u64,
+    pub name: String,
+    /// Cap on `max_fee_per_gas`, or on `gas_price` for legacy and access list transactions
+    pub max_fee_per_gas: U128,
+    /// Cap on `max_priority_fee_per_gas`
+    pub max_priority_fee_per_gas: U128,
+    pub max_gas_limit: U128,
+    /// Cap on the native value transferred, in wei
+    pub max_value: Option<U128>,
+    pub enabled: bool,
+}
+
+impl EvmChain {
+    /// The chain NEAR function calls and transfers are signed for through the
+    /// user's eth-implicit wallet. Fees are not charged by the wallet.
+    pub fn near_evm() -> Self {
+        EvmChain {
+            chain_id: NEAR_EVM_CHAIN_ID,
+            name: "NEAR".to_string(),
+            max_fee_per_gas: U128(1),
+            max_priority_fee_per_gas: U128(1),
+            max_gas_limit: U128(NEAR_EVM_MAX_GAS_LIMIT),
+            max_value: None,
+            enabled: true,
+        }
+    }
+
+    pub fn is_fee_allowed(
+        &self,
+        max_fee_per_gas: u128,
+        max_priority_fee_per_gas: Option<u128>,
+    ) -> bool {
+        max_fee_per_gas <= self.max_fee_per_gas.0
+            && max_priority_fee_per_gas
+                .is_none_or(|priority_fee| priority_fee <= self.max_priority_fee_per_gas.0)
+    }
+
+    pub fn is_gas_limit_allowed(&self, gas_limit: u128) -> bool {
+        gas_limit <= self.max_gas_limit.0
+    }
+
+    pub fn is_value_allowed(&self, value: u128) -> bool {
+        match &self.max_value {
+            Some(max_value) => value <= max_value.0,
+            None => true,
+        }
+    }
+}
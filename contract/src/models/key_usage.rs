@@ -37,3 +37,11 @@ pub struct KeyUsage {
     pub app_id: AppID, // Application identifier
     pub path: MpcPath,
 }
+
+/// A session key held by a user for an app, as returned by enumeration views.
+#[derive(Clone)]
+#[near(serializers = [json])]
+pub struct SessionKeyInfo {
+    pub app_id: AppID,
+    pub public_key: SessionKey,
+}
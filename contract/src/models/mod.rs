@@ -1,22 +1,40 @@
 // models/mod.rs
 pub mod action;
+pub mod bitcoin;
 pub mod bundle;
 pub mod chain_constraints;
 pub mod chain_id;
 pub mod constants;
 pub mod contract;
+pub mod errors;
+pub mod evm_chain;
 pub mod key_usage;
+pub mod mpc_config;
+pub mod personal_sign;
+pub mod session_key;
 pub mod sign_request;
+pub mod solana;
 pub mod trial_data;
+pub mod typed_data;
 pub mod usage_constraints;
+pub mod versioned;
 
 pub use action::*;
+pub use bitcoin::*;
 pub use bundle::*;
 pub use chain_constraints::*;
 pub use chain_id::*;
 pub use constants::*;
 pub use contract::*;
+pub use errors::*;
+pub use evm_chain::*;
 pub use key_usage::*;
+pub use mpc_config::*;
+pub use personal_sign::*;
+pub use session_key::*;
 pub use sign_request::*;
+pub use solana::*;
 pub use trial_data::*;
+pub use typed_data::*;
 pub use usage_constraints::*;
+pub use versioned::*;
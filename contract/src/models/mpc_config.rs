@@ -0,0 +1,74 @@
+// models/mpc_config.rs
+use crate::*;
+
+/// Shape of the arguments the MPC contract's `sign` method expects for ECDSA requests.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[near(serializers = [json, borsh])]
+pub enum MpcRequestSchema {
+    /// `{"request": {"payload": [32 bytes], "path", "key_version"}}`
+    V1,
+    /// `{"request": {"payload_v2": {"Ecdsa": "<hex>"}, "path", "domain_id"}}`
+    V2,
+}
+
+/// How the contract talks to the MPC signer, configurable so an MPC upgrade
+/// does not need a redeploy of this contract.
+#[derive(Clone)]
+#[near(serializers = [json, borsh])]
+pub struct MpcConfig {
+    pub schema: MpcRequestSchema,
+    /// `key_version` of V1 requests, `domain_id` of V2 ECDSA requests
+    pub ecdsa_domain_id: u64,
+    /// `domain_id` of EdDSA requests, which only exist in the V2 schema
+    pub eddsa_domain_id: u64,
+    /// Deposit attached to every `sign` call; the MPC refunds any excess
+    pub sign_deposit: NearToken,
+    pub sign_gas: Gas,
+}
+
+impl Default for MpcConfig {
+    fn default() -> Self {
+        MpcConfig {
+            schema: MpcRequestSchema::V1,
+            ecdsa_domain_id: 0,
+            eddsa_domain_id: MPC_ED25519_DOMAIN_ID,
+            sign_deposit: NearToken::from_near(1),
+            sign_gas: Gas::from_tgas(50),
+        }
+    }
+}
+
+impl MpcConfig {
+    /// Arguments of a `sign` call for an ECDSA signature over a pre-hashed payload.
+    pub fn ecdsa_sign_args(&self, hashed_payload: [u8; 32], path: &MpcPath) -> serde_json::Value {
+        match self.schema {
+            MpcRequestSchema::V1 => {
+                let sign_request = SignRequest {
+                    payload: hashed_payload.to_vec(),
+                    path: path.clone(),
+                    key_version: self.ecdsa_domain_id as u32,
+                };
+                serde_json::json!({ "request": sign_request })
+            }
+            MpcRequestSchema::V2 => serde_json::json!({
+                "request": {
+                    "path": path,
+                    "payload_v2": { "Ecdsa": hex::encode(hashed_payload) },
+                    "domain_id": self.ecdsa_domain_id,
+                }
+            }),
+        }
+    }
+
+    /// Arguments of a `sign` call for an EdDSA signature over a message, which is
+    /// signed as is rather than pre-hashed.
+    pub fn eddsa_sign_args(&self, message: &[u8], path: &MpcPath) -> serde_json::Value {
+        serde_json::json!({
+            "request": {
+                "path": path,
+                "payload_v2": { "Eddsa": hex::encode(message) },
+                "domain_id": self.eddsa_domain_id,
+            }
+        })
+    }
+}
@@ -0,0 +1,22 @@
+// models/personal_sign.rs
+use crate::*;
+
+/// Restricts which messages an app's session keys may have signed with `personal_sign`.
+#[derive(Clone)]
+#[near(serializers = [json, borsh])]
+pub enum MessageFormat {
+    /// Any message
+    Any,
+    /// Messages starting with one of the prefixes
+    Prefixed(Vec<String>),
+    /// Sign-In with Ethereum (EIP-4361) messages for the app's domain
+    Siwe(SiweConstraints),
+}
+
+#[derive(Clone)]
+#[near(serializers = [json, borsh])]
+pub struct SiweConstraints {
+    pub domain: String,
+    pub allowed_chain_ids: Option<Vec<u64>>, // Any chain if unset
+    pub uri_prefix: Option<String>,          // Any URI if unset
+}
@@ -0,0 +1,149 @@
+// models/session_key.rs
+use crate::*;
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// Curve of a session key. The discriminants match `near_sdk::CurveType` so that
+/// ed25519 and secp256k1 session keys are stored exactly like a `PublicKey`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SessionKeyType {
+    Ed25519 = 0,
+    Secp256k1 = 1,
+    /// WebAuthn / passkey key on the NIST P-256 curve
+    P256 = 2,
+}
+
+impl SessionKeyType {
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(SessionKeyType::Ed25519),
+            1 => Some(SessionKeyType::Secp256k1),
+            2 => Some(SessionKeyType::P256),
+            _ => None,
+        }
+    }
+
+    fn prefix(&self) -> &'static str {
+        match self {
+            SessionKeyType::Ed25519 => "ed25519",
+            SessionKeyType::Secp256k1 => "secp256k1",
+            SessionKeyType::P256 => "p256",
+        }
+    }
+
+    fn key_length(&self) -> usize {
+        match self {
+            SessionKeyType::Ed25519 => ED25519_PUBLIC_KEY_LENGTH,
+            SessionKeyType::Secp256k1 => SECP256K1_PUBLIC_KEY_LENGTH,
+            SessionKeyType::P256 => P256_PUBLIC_KEY_LENGTH,
+        }
+    }
+}
+
+/// Public key a session is authorized with, serialized as `<curve>:<base58 key>`.
+///
+/// Borsh-encoded as the curve byte followed by the key bytes in a `Vec<u8>`, the same
+/// layout as `PublicKey`, so session keys stored before P-256 support remain readable.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[near(serializers = [borsh])]
+pub struct SessionKey {
+    data: Vec<u8>,
+}
+
+impl SessionKey {
+    pub fn key_type(&self) -> SessionKeyType {
+        SessionKeyType::from_byte(self.data[0]).expect("Invalid session key type")
+    }
+
+    /// Raw key bytes without the curve type prefix
+    pub fn key_data(&self) -> &[u8] {
+        &self.data[1..]
+    }
+}
+
+impl From<PublicKey> for SessionKey {
+    fn from(public_key: PublicKey) -> Self {
+        SessionKey {
+            data: public_key.as_bytes().to_vec(),
+        }
+    }
+}
+
+impl FromStr for SessionKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (prefix, encoded) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Missing curve prefix in session key {}", s))?;
+        let key_type = match prefix {
+            "ed25519" => SessionKeyType::Ed25519,
+            "secp256k1" => SessionKeyType::Secp256k1,
+            "p256" => SessionKeyType::P256,
+            _ => return Err(format!("Unknown session key curve {}", prefix)),
+        };
+
+        let key_data = bs58::decode(encoded)
+            .into_vec()
+            .map_err(|_| "Invalid base58 in session key".to_string())?;
+        if key_data.len() != key_type.key_length() {
+            return Err(format!(
+                "Invalid {} key length. Found: {}",
+                prefix,
+                key_data.len()
+            ));
+        }
+
+        let mut data = vec![key_type as u8];
+        data.extend(key_data);
+        Ok(SessionKey { data })
+    }
+}
+
+impl fmt::Display for SessionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}",
+            self.key_type().prefix(),
+            bs58::encode(self.key_data()).into_string()
+        )
+    }
+}
+
+impl Serialize for SessionKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for SessionKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Signature over a payload by a session key.
+#[derive(Clone)]
+#[near(serializers = [json])]
+#[serde(untagged)]
+pub enum SessionSignature {
+    /// ed25519 signature (64 bytes) or secp256k1 `r || s || v` signature (65 bytes)
+    Raw(Base64VecU8),
+    /// WebAuthn assertion produced by a passkey
+    WebAuthn(WebAuthnAssertion),
+}
+
+/// WebAuthn assertion whose challenge is the base64url-encoded SHA-256 of the payload.
+#[derive(Clone)]
+#[near(serializers = [json])]
+pub struct WebAuthnAssertion {
+    pub authenticator_data: Base64VecU8,
+    /// clientDataJSON exactly as returned by the authenticator
+    pub client_data_json: String,
+    /// DER-encoded ECDSA signature
+    pub signature: Base64VecU8,
+}
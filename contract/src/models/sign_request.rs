@@ -18,6 +18,148 @@ pub struct SignResult {
     pub recovery_id: u8,
 }
 
+/// `(r, s, recovery_id, public_key)` of a signature and the key it recovers to
+pub type RecoveredSignature = (Vec<u8>, Vec<u8>, u8, [u8; 64]);
+
+impl SignResult {
+    /// Splits the MPC signature into its 32-byte `r`, 32-byte `s` and recovery id.
+    pub fn to_rsv(&self) -> Result<(Vec<u8>, Vec<u8>, u8), FastAuthError> {
+        // Decode the compressed big_r point; r is its x coordinate
+        let compressed_point_bytes = hex::decode(&self.big_r.affine_point).map_err(|_| {
+            FastAuthError::InvalidMpcSignature("Failed to decode affine_point hex string".into())
+        })?;
+
+        if compressed_point_bytes.len() != 33 {
+            return Err(FastAuthError::InvalidMpcSignature(format!(
+                "Invalid compressed point length. Found: {}",
+                compressed_point_bytes.len()
+            )));
+        }
+
+        // Remove the first byte (prefix)
+        let r_bytes = compressed_point_bytes[1..].to_vec();
+
+        let s_bytes = hex::decode(&self.s.scalar).map_err(|_| {
+            FastAuthError::InvalidMpcSignature("Failed to decode s scalar hex string".into())
+        })?;
+
+        if s_bytes.len() != 32 {
+            return Err(FastAuthError::InvalidMpcSignature(format!(
+                "Invalid s length. Found: {}",
+                s_bytes.len()
+            )));
+        }
+
+        Ok((r_bytes, s_bytes, self.recovery_id))
+    }
+
+    /// Normalizes the signature to low-s with a 0/1 recovery id and recovers the
+    /// uncompressed key (without the 0x04 prefix) that produced it over `hash`.
+    /// Returns `(r, s, recovery_id, public_key)`.
+    pub fn recover(&self, hash: &[u8; 32]) -> Result<RecoveredSignature, FastAuthError> {
+        let (r_bytes, s_bytes, recovery_id) = self.to_rsv()?;
+
+        // Accept both raw (0/1) and Ethereum-style (27/28) recovery ids
+        let recovery_id = match recovery_id {
+            27 | 28 => recovery_id - 27,
+            v => v,
+        };
+        if recovery_id > 1 {
+            return Err(FastAuthError::InvalidMpcSignature(format!(
+                "Invalid recovery id {}",
+                recovery_id
+            )));
+        }
+
+        // Negating s recovers the same key with the other recovery id
+        let (s_bytes, negated) = normalize_low_s(&s_bytes);
+        let recovery_id = if negated {
+            recovery_id ^ 1
+        } else {
+            recovery_id
+        };
+
+        let mut signature = r_bytes.clone();
+        signature.extend_from_slice(&s_bytes);
+        let public_key = env::ecrecover(hash, &signature, recovery_id, true)
+            .ok_or_else(|| FastAuthError::InvalidMpcSignature("Failed to recover signer".into()))?;
+
+        Ok((r_bytes, s_bytes, recovery_id, public_key))
+    }
+
+    /// Recovers the signature over `hash` and checks it belongs to the user's eth-implicit
+    /// address, catching a misconfigured MPC contract or a path mix-up before anything is
+    /// relayed. Returns the normalized `(r, s, recovery_id)`.
+    pub fn verify_for_address(
+        &self,
+        hash: &[u8; 32],
+        eth_address: &AccountId,
+    ) -> Result<(Vec<u8>, Vec<u8>, u8), FastAuthError> {
+        let (r_bytes, s_bytes, recovery_id, public_key) = self.recover(hash)?;
+
+        let recovered_address = env::keccak256_array(&public_key);
+        if format!("0x{}", hex::encode(&recovered_address[12..])) != eth_address.as_str() {
+            return Err(FastAuthError::InvalidMpcSignature(
+                "Signature does not recover to the user's address".into(),
+            ));
+        }
+
+        Ok((r_bytes, s_bytes, recovery_id))
+    }
+
+    /// Verifies the signature as `verify_for_address` does and encodes it as 65 bytes
+    /// `r || s || v` with `v` in Ethereum's 27/28 form.
+    pub fn to_eth_signature(&self, hash: &[u8; 32], eth_address: &AccountId) -> Vec<u8> {
+        let (r_bytes, s_bytes, recovery_id) = self
+            .verify_for_address(hash, eth_address)
+            .unwrap_or_else(|e| e.panic());
+
+        let mut signature = r_bytes;
+        signature.extend(s_bytes);
+        signature.push(recovery_id + 27);
+        signature
+    }
+
+    /// Recovers the signature over `hash` and checks it was made by `public_key`, the
+    /// secp256k1 key of a NEAR account. Returns it as NEAR encodes secp256k1
+    /// signatures: 65 bytes `r || s || v` with `v` in 0/1.
+    pub fn to_near_signature(&self, hash: &[u8; 32], public_key: &PublicKey) -> [u8; 65] {
+        let (r_bytes, s_bytes, recovery_id, recovered) =
+            self.recover(hash).unwrap_or_else(|e| e.panic());
+        ensure(
+            public_key.as_bytes()[1..] == recovered[..],
+            FastAuthError::InvalidMpcSignature(
+                "Signature does not recover to the user's MPC key".into(),
+            ),
+        );
+
+        let mut signature = [0u8; 65];
+        signature[..32].copy_from_slice(&r_bytes);
+        signature[32..64].copy_from_slice(&s_bytes);
+        signature[64] = recovery_id;
+        signature
+    }
+}
+
+/// Signature returned by the MPC contract for a request in the EdDSA domain.
+#[derive(Debug)]
+#[near(serializers = [json])]
+pub struct Ed25519SignResult {
+    pub signature: Vec<u8>,
+}
+
+impl Ed25519SignResult {
+    pub fn to_bytes(&self) -> [u8; 64] {
+        self.signature
+            .as_slice()
+            .try_into()
+            .or_fail(FastAuthError::InvalidMpcSignature(format!(
+                "Invalid ed25519 signature length. Found: {}",
+                self.signature.len()
+            )))
+    }
+}
+
 #[derive(Debug)]
 #[near(serializers = [json])]
 pub struct AffinePoint {
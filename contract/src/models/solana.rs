@@ -0,0 +1,39 @@
+// models/solana.rs
+use crate::*;
+use near_sdk::json_types::U64;
+
+/// Restricts which Solana transactions an app's session keys may have signed.
+#[derive(Clone)]
+#[near(serializers = [json, borsh])]
+pub struct SolanaConstraints {
+    /// Base58 ids of the programs instructions may invoke, including the system program
+    pub allowed_programs: Vec<String>,
+    /// Base58 mints SPL transfers may move. Any mint if unset.
+    pub allowed_mints: Option<Vec<String>>,
+    /// Cap on the lamports a transaction may transfer
+    pub max_lamports: Option<U64>,
+}
+
+impl SolanaConstraints {
+    pub fn is_program_allowed(&self, program_id: &[u8; 32]) -> bool {
+        self.allowed_programs
+            .iter()
+            .any(|program| bs58::decode(program).into_vec().as_deref() == Ok(&program_id[..]))
+    }
+
+    pub fn is_mint_allowed(&self, mint: &[u8; 32]) -> bool {
+        match &self.allowed_mints {
+            Some(mints) => mints
+                .iter()
+                .any(|allowed| bs58::decode(allowed).into_vec().as_deref() == Ok(&mint[..])),
+            None => true,
+        }
+    }
+
+    pub fn is_lamports_allowed(&self, lamports: u64) -> bool {
+        match &self.max_lamports {
+            Some(max_lamports) => lamports <= max_lamports.0,
+            None => true,
+        }
+    }
+}
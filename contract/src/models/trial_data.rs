@@ -12,27 +12,43 @@ pub struct TrialData {
     pub exit_conditions: Option<ExitConditions>,
     pub expiration_time: Option<u64>,
     pub creator_account_id: AccountId,
+    pub typed_data_constraints: Option<TypedDataConstraints>,
+    pub personal_sign_format: Option<MessageFormat>,
+    pub bitcoin_constraints: Option<BitcoinConstraints>,
+    pub solana_constraints: Option<SolanaConstraints>,
 }
 
 impl TrialData {
+    /// Rejects policies that can't be enforced as written.
+    pub fn assert_valid(&self) {
+        for chain_constraints in self.constraints_by_chain_id.values() {
+            if let ChainConstraints::EVM(constraints) = chain_constraints {
+                constraints.assert_valid();
+            }
+        }
+    }
+
     /// Retrieves the chain constraints for a given chain ID.
     pub fn get_chain_constraints(&self, chain_id: &ChainId) -> Option<&ChainConstraints> {
         self.constraints_by_chain_id.get(chain_id)
     }
 
-    /// Checks if a method is allowed on a given chain.
+    /// Checks if a method is allowed on a given NEAR chain. EVM methods are matched by
+    /// selector instead, see `is_selector_allowed`.
     pub fn is_method_allowed(&self, method: &str, chain_id: &ChainId) -> bool {
-        if let Some(chain_constraints) = self.get_chain_constraints(chain_id) {
-            match chain_constraints {
-                ChainConstraints::NEAR(constraints) => {
-                    constraints.allowed_methods.contains(&method.to_string())
-                }
-                ChainConstraints::EVM(constraints) => {
-                    constraints.allowed_methods.contains(&method.to_string())
-                }
+        match self.get_chain_constraints(chain_id) {
+            Some(ChainConstraints::NEAR(constraints)) => {
+                constraints.allowed_methods.contains(&method.to_string())
             }
-        } else {
-            false
+            _ => false,
+        }
+    }
+
+    /// Checks if a function selector is allowed on a given EVM chain.
+    pub fn is_selector_allowed(&self, selector: &[u8; 4], chain_id: &ChainId) -> bool {
+        match self.get_chain_constraints(chain_id) {
+            Some(ChainConstraints::EVM(constraints)) => constraints.is_selector_allowed(selector),
+            _ => false,
         }
     }
 
@@ -40,17 +56,18 @@ impl TrialData {
     pub fn is_contract_allowed(&self, contract: &str, chain_id: &ChainId) -> bool {
         if let Some(chain_constraints) = self.get_chain_constraints(chain_id) {
             match chain_constraints {
-                ChainConstraints::NEAR(constraints) => constraints
-                    .allowed_contracts
-                    .contains(&contract.parse().unwrap()),
+                ChainConstraints::NEAR(constraints) => match contract.parse::<AccountId>() {
+                    Ok(account_id) => constraints.allowed_contracts.contains(&account_id),
+                    Err(_) => false,
+                },
                 ChainConstraints::EVM(constraints) => {
-                    // Assuming contract is a hex string representing the address
-                    let addr_str = contract.trim_start_matches("0x");
-                    let addr_bytes: [u8; 20] = match hex::decode(addr_str) {
-                        Ok(bytes) => bytes.try_into().expect("Invalid Ethereum address length"),
-                        Err(_) => {
-                            return false;
-                        }
+                    // Contracts are given as hex strings; anything else matches nothing
+                    let addr_bytes: [u8; 20] = match hex::decode(contract.trim_start_matches("0x"))
+                        .ok()
+                        .and_then(|bytes| bytes.try_into().ok())
+                    {
+                        Some(addr_bytes) => addr_bytes,
+                        None => return false,
                     };
                     let address = Address::from(addr_bytes);
                     constraints.allowed_contracts.contains(&address)
@@ -109,6 +126,17 @@ impl TrialData {
         }
     }
 
+    /// Retrieves the rule for a token typed token actions may move on an EVM chain.
+    pub fn get_token_rule(&self, token: &Address, chain_id: &ChainId) -> Option<&EvmTokenRule> {
+        match self.get_chain_constraints(chain_id) {
+            Some(ChainConstraints::EVM(constraints)) => constraints
+                .allowed_tokens
+                .iter()
+                .find(|rule| rule.token == *token),
+            _ => None,
+        }
+    }
+
     /// Checks if the trial has expired.
     pub fn has_expired(&self, current_timestamp: u64) -> bool {
         if let Some(expiration_time) = self.expiration_time {
@@ -131,3 +159,166 @@ impl TrialData {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    const GUESTBOOK_ADDRESS: &str = "0xcf90f19e1ed82ed7768a07983b3c397da3bbf21f";
+
+    fn trial_data() -> TrialData {
+        let mut constraints_by_chain_id = HashMap::new();
+        constraints_by_chain_id.insert(
+            ChainId::from("NEAR"),
+            ChainConstraints::NEAR(NearConstraints {
+                allowed_methods: vec!["add_message".to_string()],
+                allowed_contracts: vec!["guestbook.near-examples.testnet".parse().unwrap()],
+                max_gas: Some(Gas::from_tgas(100)),
+                max_deposit: Some(NearToken::from_near(1)),
+                initial_deposit: NearToken::from_near(0),
+            }),
+        );
+        constraints_by_chain_id.insert(
+            ChainId::from("1"),
+            ChainConstraints::EVM(EvmConstraints {
+                allowed_methods: vec!["transfer(address,uint256)".to_string()],
+                allowed_contracts: vec![Address::from(
+                    <[u8; 20]>::try_from(hex::decode(&GUESTBOOK_ADDRESS[2..]).unwrap()).unwrap(),
+                )],
+                max_gas: Some(100_000),
+                max_value: None,
+                initial_deposit: "0".to_string(),
+                allowed_tokens: vec![],
+            }),
+        );
+
+        TrialData {
+            constraints_by_chain_id,
+            usage_constraints: None,
+            interaction_limits: None,
+            exit_conditions: Some(ExitConditions {
+                transaction_limit: Some(3),
+                success_condition: None,
+                time_limit: None,
+            }),
+            expiration_time: Some(1_000),
+            creator_account_id: "app.near".parse().unwrap(),
+            typed_data_constraints: None,
+            personal_sign_format: None,
+            bitcoin_constraints: None,
+            solana_constraints: None,
+        }
+    }
+
+    #[test]
+    fn near_contracts_are_matched_by_account_id() {
+        let trial_data = trial_data();
+        let near = ChainId::from("NEAR");
+        assert!(trial_data.is_contract_allowed("guestbook.near-examples.testnet", &near));
+        assert!(!trial_data.is_contract_allowed("other.testnet", &near));
+        assert!(!trial_data.is_contract_allowed("Not An Account!", &near));
+        assert!(!trial_data.is_contract_allowed("guestbook.near-examples.testnet", &"5".into()));
+    }
+
+    #[test]
+    fn evm_contracts_are_matched_by_address() {
+        let trial_data = trial_data();
+        let mainnet = ChainId::from("1");
+        assert!(trial_data.is_contract_allowed(GUESTBOOK_ADDRESS, &mainnet));
+        assert!(trial_data.is_contract_allowed(&GUESTBOOK_ADDRESS[2..], &mainnet));
+        assert!(
+            !trial_data.is_contract_allowed("0x827c5d7bde400f1716255ae54bb8a55cb43d0324", &mainnet)
+        );
+        // Malformed addresses match nothing instead of panicking
+        assert!(!trial_data.is_contract_allowed("0x1234", &mainnet));
+        assert!(!trial_data.is_contract_allowed("0xnothex", &mainnet));
+    }
+
+    #[test]
+    fn methods_and_selectors_are_checked_per_chain() {
+        testing_env!(VMContextBuilder::new().build());
+        let trial_data = trial_data();
+        assert!(trial_data.is_method_allowed("add_message", &"NEAR".into()));
+        assert!(!trial_data.is_method_allowed("delete_message", &"NEAR".into()));
+        // transfer(address,uint256)
+        assert!(trial_data.is_selector_allowed(&[0xa9, 0x05, 0x9c, 0xbb], &"1".into()));
+        assert!(!trial_data.is_selector_allowed(&[0x09, 0x5e, 0xa7, 0xb3], &"1".into()));
+        assert!(!trial_data.is_selector_allowed(&[0xa9, 0x05, 0x9c, 0xbb], &"NEAR".into()));
+    }
+
+    #[test]
+    fn evm_methods_given_as_signatures_or_selectors_are_valid() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut trial_data = trial_data();
+        set_evm_methods(
+            &mut trial_data,
+            &["transfer(address,uint256)", "0x095ea7b3"],
+        );
+        trial_data.assert_valid();
+    }
+
+    #[test]
+    #[should_panic(expected = ":6003:")]
+    fn bare_evm_method_names_are_rejected() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut trial_data = trial_data();
+        set_evm_methods(&mut trial_data, &["transfer"]);
+        trial_data.assert_valid();
+    }
+
+    #[test]
+    #[should_panic(expected = ":6003:")]
+    fn token_caps_must_be_decimal_integers() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut trial_data = trial_data();
+        if let Some(ChainConstraints::EVM(constraints)) = trial_data
+            .constraints_by_chain_id
+            .get_mut(&ChainId::from("1"))
+        {
+            constraints.allowed_tokens.push(EvmTokenRule {
+                token: constraints.allowed_contracts[0],
+                standard: TokenStandard::Erc20,
+                max_amount: Some("1e18".to_string()),
+                allowed_token_ids: None,
+            });
+        }
+        trial_data.assert_valid();
+    }
+
+    fn set_evm_methods(trial_data: &mut TrialData, methods: &[&str]) {
+        if let Some(ChainConstraints::EVM(constraints)) = trial_data
+            .constraints_by_chain_id
+            .get_mut(&ChainId::from("1"))
+        {
+            constraints.allowed_methods = methods.iter().map(|m| m.to_string()).collect();
+        }
+    }
+
+    #[test]
+    fn gas_and_deposit_limits() {
+        let trial_data = trial_data();
+        let near = ChainId::from("NEAR");
+        assert!(trial_data.is_gas_within_limits(Gas::from_tgas(100).as_gas(), &near));
+        assert!(!trial_data.is_gas_within_limits(Gas::from_tgas(101).as_gas(), &near));
+        assert!(trial_data.is_deposit_within_limits(NearToken::from_near(1).as_yoctonear(), &near));
+        assert!(
+            !trial_data.is_deposit_within_limits(NearToken::from_near(1).as_yoctonear() + 1, &near)
+        );
+
+        let mainnet = ChainId::from("1");
+        assert!(!trial_data.is_gas_within_limits(100_001, &mainnet));
+        assert!(trial_data.is_deposit_within_limits(u128::MAX, &mainnet));
+        assert!(!trial_data.is_gas_within_limits(0, &"10".into()));
+    }
+
+    #[test]
+    fn expiration_and_transaction_limit() {
+        let trial_data = trial_data();
+        assert!(!trial_data.has_expired(999));
+        assert!(trial_data.has_expired(1_000));
+        assert!(trial_data.is_within_transaction_limit(2));
+        assert!(!trial_data.is_within_transaction_limit(3));
+    }
+}
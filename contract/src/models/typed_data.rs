@@ -0,0 +1,48 @@
+// models/typed_data.rs
+use crate::*;
+use omni_transaction::evm::types::Address;
+
+/// Restricts which EIP-712 messages an app's session keys may have signed.
+#[derive(Clone)]
+#[near(serializers = [json, borsh])]
+pub struct TypedDataConstraints {
+    pub allowed_domains: Vec<TypedDataDomainRule>,
+    pub allowed_primary_types: Option<Vec<String>>, // Any primary type if unset
+}
+
+/// An EIP-712 domain an app may sign for.
+#[derive(Clone)]
+#[near(serializers = [json, borsh])]
+pub struct TypedDataDomainRule {
+    pub chain_id: u64,
+    pub verifying_contract: Option<Address>, // Any verifying contract if unset
+}
+
+impl TypedDataConstraints {
+    /// Checks if a domain's chainId and verifyingContract match an allowed rule.
+    pub fn is_domain_allowed(
+        &self,
+        chain_id: Option<u64>,
+        verifying_contract: Option<&Address>,
+    ) -> bool {
+        let Some(chain_id) = chain_id else {
+            return false;
+        };
+
+        self.allowed_domains.iter().any(|rule| {
+            rule.chain_id == chain_id
+                && match &rule.verifying_contract {
+                    Some(allowed) => verifying_contract == Some(allowed),
+                    None => true,
+                }
+        })
+    }
+
+    /// Checks if a primary type may be signed.
+    pub fn is_primary_type_allowed(&self, primary_type: &str) -> bool {
+        match &self.allowed_primary_types {
+            Some(allowed) => allowed.iter().any(|t| t == primary_type),
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,283 @@
+// models/versioned.rs
+use crate::*;
+use near_sdk::borsh::{self, BorshDeserialize};
+
+/// Storage key holding the `StateVersion` of the contract state.
+/// Contracts deployed before versioning was introduced do not have it.
+pub const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+
+/// Storage key near-sdk keeps the `Contract` struct under
+const CONTRACT_STATE_KEY: &[u8] = b"STATE";
+
+/// Identifies the layout of the `Contract` struct stored under the state key.
+/// Every change to the layout adds a variant, a frozen snapshot of the previous layout
+/// and a conversion from it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[near(serializers = [borsh])]
+pub enum StateVersion {
+    V1,
+    V2,
+}
+
+impl StateVersion {
+    pub const CURRENT: StateVersion = StateVersion::V2;
+
+    /// Reads the stored state version, defaulting to `V1` for unversioned deployments.
+    pub fn read() -> Self {
+        env::storage_read(STATE_VERSION_KEY)
+            .map(|bytes| {
+                StateVersion::try_from_slice(&bytes).expect("Failed to deserialize state version")
+            })
+            .unwrap_or(StateVersion::V1)
+    }
+
+    pub fn write(&self) {
+        env::storage_write(
+            STATE_VERSION_KEY,
+            &borsh::to_vec(self).expect("Failed to serialize state version"),
+        );
+    }
+}
+
+/// Contract state layout prior to versioning (no owner or emergency controls).
+#[near(serializers = [borsh])]
+pub struct ContractV1 {
+    // `SessionKey` shares the borsh layout of the `PublicKey` stored by V1
+    pub session_keys: LookupMap<(MpcPath, AppID), SessionKey>,
+    pub key_usage_by_pk: LookupMap<SessionKey, KeyUsage>,
+    pub bundler: LookupMap<MpcPath, BundleV1>,
+    pub app_balances: LookupMap<AppID, NearToken>,
+    pub oracle_account_id: AccountId,
+    pub mpc_contract: AccountId,
+}
+
+impl From<ContractV1> for Contract {
+    fn from(old: ContractV1) -> Self {
+        // V1 only ever signed for NEAR through the eth-implicit wallet
+        let mut evm_chains = IterableMap::new(StorageKeys::EvmChains);
+        evm_chains.insert(NEAR_EVM_CHAIN_ID, EvmChain::near_evm());
+
+        Contract {
+            session_keys: old.session_keys,
+            // V1 entries are read through from the unversioned maps
+            key_usage_by_pk: LookupMap::new(StorageKeys::VersionedKeyUsageByPK),
+            bundler: LookupMap::new(StorageKeys::VersionedBundler),
+            message_nonces: LookupMap::new(StorageKeys::MessageNonces),
+            paths_by_eth_address: LookupMap::new(StorageKeys::PathsByEthAddress),
+            near_accounts: LookupMap::new(StorageKeys::NearAccounts),
+            paths_by_near_account: LookupMap::new(StorageKeys::PathsByNearAccount),
+            app_balances: old.app_balances,
+            trial_data_by_app: LookupMap::new(StorageKeys::TrialDataById),
+            // Entries written before the upgrade are added with `backfill_indexes`
+            paths: IterableSet::new(StorageKeys::Paths),
+            apps: IterableSet::new(StorageKeys::Apps),
+            apps_by_path: LookupMap::new(StorageKeys::AppsByPath),
+            paths_by_app: LookupMap::new(StorageKeys::PathsByApp),
+            paused: false,
+            paused_apps: LookupSet::new(StorageKeys::PausedApps),
+            frozen_users: LookupSet::new(StorageKeys::FrozenUsers),
+            evm_chains,
+            // V1 had no owner; it was upgraded by the contract account itself
+            owner_id: env::current_account_id(),
+            oracle_account_id: old.oracle_account_id,
+            mpc_contract: old.mpc_contract,
+            // The request schema and deposit V1 hardcoded
+            mpc_config: MpcConfig::default(),
+            named_account_parent: None,
+        }
+    }
+}
+
+/// Every contract state layout that may be found on chain.
+// Only ever built once, by `migrate`
+#[allow(clippy::large_enum_variant)]
+pub enum VersionedContract {
+    V1(ContractV1),
+    V2(Contract),
+}
+
+impl VersionedContract {
+    /// Loads the stored state using the layout recorded by its `StateVersion`.
+    pub fn load() -> Self {
+        let state = env::storage_read(CONTRACT_STATE_KEY).expect("Missing contract state");
+        match StateVersion::read() {
+            StateVersion::V1 => VersionedContract::V1(parse_state(&state)),
+            StateVersion::V2 => VersionedContract::V2(parse_state(&state)),
+        }
+    }
+
+    /// Account allowed to run the migration besides the contract itself.
+    pub fn owner_id(&self) -> Option<&AccountId> {
+        match self {
+            VersionedContract::V1(_) => None,
+            VersionedContract::V2(contract) => Some(&contract.owner_id),
+        }
+    }
+
+    /// Upgrades the loaded state to the current layout.
+    pub fn into_current(self) -> Contract {
+        match self {
+            VersionedContract::V1(old) => old.into(),
+            VersionedContract::V2(contract) => contract,
+        }
+    }
+}
+
+fn parse_state<T: BorshDeserialize>(state: &[u8]) -> T {
+    T::try_from_slice(state).expect("Failed to deserialize contract state")
+}
+
+/// Every `KeyUsage` layout that may be found in storage.
+#[derive(Clone)]
+#[near(serializers = [borsh])]
+pub enum VersionedKeyUsage {
+    V1(KeyUsage),
+}
+
+impl From<VersionedKeyUsage> for KeyUsage {
+    fn from(versioned: VersionedKeyUsage) -> Self {
+        match versioned {
+            VersionedKeyUsage::V1(key_usage) => key_usage,
+        }
+    }
+}
+
+impl From<KeyUsage> for VersionedKeyUsage {
+    fn from(key_usage: KeyUsage) -> Self {
+        VersionedKeyUsage::V1(key_usage)
+    }
+}
+
+/// `Bundle` layout prior to ed25519 keys.
+#[derive(Clone)]
+#[near(serializers = [borsh])]
+pub struct BundleV1 {
+    pub mpc_key: PublicKey,
+    pub eth_address: AccountId,
+    pub path: MpcPath,
+}
+
+impl From<BundleV1> for Bundle {
+    fn from(bundle: BundleV1) -> Self {
+        Bundle {
+            mpc_key: bundle.mpc_key,
+            eth_address: bundle.eth_address,
+            path: bundle.path,
+            ed25519_key: None,
+        }
+    }
+}
+
+/// Every `Bundle` layout that may be found in storage.
+#[derive(Clone)]
+#[near(serializers = [borsh])]
+pub enum VersionedBundle {
+    V1(Bundle),
+}
+
+impl From<VersionedBundle> for Bundle {
+    fn from(versioned: VersionedBundle) -> Self {
+        match versioned {
+            VersionedBundle::V1(bundle) => bundle,
+        }
+    }
+}
+
+impl From<Bundle> for VersionedBundle {
+    fn from(bundle: Bundle) -> Self {
+        VersionedBundle::V1(bundle)
+    }
+}
+
+/// Every `TrialData` layout that may be found in storage.
+#[derive(Clone)]
+#[near(serializers = [borsh])]
+pub enum VersionedTrialData {
+    V1(TrialData),
+}
+
+impl From<VersionedTrialData> for TrialData {
+    fn from(versioned: VersionedTrialData) -> Self {
+        match versioned {
+            VersionedTrialData::V1(trial_data) => trial_data,
+        }
+    }
+}
+
+impl From<TrialData> for VersionedTrialData {
+    fn from(trial_data: TrialData) -> Self {
+        VersionedTrialData::V1(trial_data)
+    }
+}
+
+/// Entries written before versioning live unversioned under the original prefixes.
+/// They are upgraded lazily: read through on lookup, moved on the next write.
+fn legacy_key_usage_by_pk() -> LookupMap<SessionKey, KeyUsage> {
+    LookupMap::new(StorageKeys::KeyUsageByPK)
+}
+
+fn legacy_bundler() -> LookupMap<MpcPath, BundleV1> {
+    LookupMap::new(StorageKeys::Bundler)
+}
+
+#[near]
+impl Contract {
+    pub(crate) fn internal_get_key_usage(&self, public_key: &SessionKey) -> Option<KeyUsage> {
+        match self.key_usage_by_pk.get(public_key) {
+            Some(versioned) => Some(versioned.clone().into()),
+            None => legacy_key_usage_by_pk().get(public_key).cloned(),
+        }
+    }
+
+    pub(crate) fn internal_set_key_usage(&mut self, public_key: SessionKey, key_usage: KeyUsage) {
+        let mut legacy = legacy_key_usage_by_pk();
+        if legacy.remove(&public_key).is_some() {
+            legacy.flush();
+        }
+        self.key_usage_by_pk.insert(public_key, key_usage.into());
+    }
+
+    pub(crate) fn internal_remove_key_usage(
+        &mut self,
+        public_key: &SessionKey,
+    ) -> Option<KeyUsage> {
+        let mut legacy = legacy_key_usage_by_pk();
+        let legacy_usage = legacy.remove(public_key);
+        legacy.flush();
+
+        self.key_usage_by_pk
+            .remove(public_key)
+            .map(KeyUsage::from)
+            .or(legacy_usage)
+    }
+
+    pub(crate) fn internal_get_bundle(&self, path: &MpcPath) -> Option<Bundle> {
+        match self.bundler.get(path) {
+            Some(versioned) => Some(versioned.clone().into()),
+            None => legacy_bundler().get(path).cloned().map(Bundle::from),
+        }
+    }
+
+    pub(crate) fn internal_set_bundle(&mut self, path: MpcPath, bundle: Bundle) {
+        let mut legacy = legacy_bundler();
+        if legacy.remove(&path).is_some() {
+            legacy.flush();
+        }
+        self.paths.insert(path.clone());
+        self.bundler.insert(path, bundle.into());
+    }
+
+    pub(crate) fn internal_get_trial_data(&self, app_id: &AppID) -> Option<TrialData> {
+        self.trial_data_by_app
+            .get(app_id)
+            .map(|versioned| versioned.clone().into())
+    }
+
+    pub(crate) fn internal_set_trial_data(&mut self, app_id: AppID, trial_data: TrialData) {
+        self.trial_data_by_app.insert(app_id, trial_data.into());
+    }
+
+    pub(crate) fn internal_remove_trial_data(&mut self, app_id: &AppID) -> Option<TrialData> {
+        self.trial_data_by_app.remove(app_id).map(TrialData::from)
+    }
+}
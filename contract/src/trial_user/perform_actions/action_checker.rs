@@ -1,61 +1,269 @@
 // trial_user/perform_actions/action_checker.rs
 use crate::*;
-use near_sdk::json_types::{Base64VecU8, U128};
-use perform_actions::near::call_fn::NearPayload;
-
-pub(crate) fn vec_to_64_byte_array(vec: Vec<u8>) -> Option<[u8; 64]> {
-    // Check if the string is exactly 64 bytes
-    if vec.len() != 64 {
-        return None;
-    }
-
-    // Explicitly import TryInto trait
-    use std::convert::TryInto;
-
-    let array: [u8; 64] = vec
-        .try_into() // Try to convert the Vec<u8> into a fixed-size array
-        .expect("Vec with incorrect length"); // This expect will never panic due to the above length check
-
-    Some(array)
-}
+use near_sdk::serde::Serialize;
+use perform_actions::session_signature::verify_session_signature;
 
 #[near]
 impl Contract {
     pub(crate) fn assert_valid_signature(
         &self,
-        payload: &NearPayload,
-        signature: &Base64VecU8,
-        session_key: &PublicKey,
+        payload: &impl Serialize,
+        signature: &SessionSignature,
+        session_key: &SessionKey,
         app_id: &AppID,
     ) {
         // Retrieve KeyUsage using session_key
         let key_usage = self
-            .key_usage_by_pk
-            .get(session_key)
-            .expect("Public key not recognized");
+            .internal_get_key_usage(session_key)
+            .or_fail(FastAuthError::SessionKeyNotFound);
 
         // Verify app_id
-        require!(
+        ensure(
             key_usage.app_id == *app_id,
-            "Session key not associated with this app"
+            FastAuthError::SessionKeyAppMismatch,
         );
 
         // Serialize the payload
         let payload_bytes =
             near_sdk::serde_json::to_vec(&payload).expect("Failed to serialize payload");
 
-        // Extract the raw key bytes without the curve type prefix
-        let key_bytes_without_prefix = &session_key.as_bytes()[1..];
-        let key_bytes_array: &[u8; 32] = key_bytes_without_prefix
-            .try_into()
-            .expect("Invalid key length");
+        verify_session_signature(&payload_bytes, signature, session_key)
+            .unwrap_or_else(|e| e.panic());
+    }
+
+    /// Checks that the session key may still sign for the app: signing isn't halted for
+    /// the app or the user, and the app's trial hasn't expired. Every signing entrypoint
+    /// goes through this before its own checks.
+    /// Returns the app's trial data and the key's usage for further checks.
+    pub(crate) fn assert_trial_active(
+        &self,
+        session_key: &SessionKey,
+        app_id: &AppID,
+    ) -> (TrialData, KeyUsage) {
+        let key_usage = self
+            .internal_get_key_usage(session_key)
+            .or_fail(FastAuthError::SessionKeyNotFound);
+
+        self.assert_not_paused(app_id, &key_usage.path);
+
+        let trial_data = self
+            .internal_get_trial_data(app_id)
+            .or_fail(FastAuthError::TrialDataNotFound);
+
+        ensure(
+            !trial_data.has_expired(env::block_timestamp()),
+            FastAuthError::TrialExpired,
+        );
+
+        (trial_data, key_usage)
+    }
+
+    /// Checks an action against the app's trial constraints for the session key.
+    /// Returns the app's trial data and the key's usage for further checks.
+    pub(crate) fn assert_action_allowed(
+        &self,
+        action: &Action,
+        session_key: &SessionKey,
+        app_id: &AppID,
+    ) -> (TrialData, KeyUsage) {
+        let (trial_data, key_usage) = self.assert_trial_active(session_key, app_id);
+
+        let (chain_id, contract, gas, deposit) = match action {
+            Action::NEAR(near_action) => {
+                let chain_id = ChainId::from("NEAR");
+                ensure(
+                    trial_data.is_method_allowed(&near_action.method_name, &chain_id),
+                    FastAuthError::MethodNotAllowed,
+                );
+                (
+                    chain_id,
+                    near_action.contract_id.to_string(),
+                    near_action.gas_attached.as_gas(),
+                    near_action.deposit_attached.as_yoctonear(),
+                )
+            }
+            Action::EVM(evm_action) => {
+                // EVM methods are matched on their selector so overloads can't be confused
+                let chain_id = ChainId(evm_action.chain_id.to_string());
+                ensure(
+                    trial_data.is_selector_allowed(&evm_action.selector, &chain_id),
+                    FastAuthError::MethodNotAllowed,
+                );
+                (
+                    chain_id,
+                    format!("0x{}", hex::encode(evm_action.contract_address)),
+                    u64::try_from(evm_action.gas_limit).unwrap_or(u64::MAX),
+                    evm_action.value.0,
+                )
+            }
+        };
+
+        ensure(
+            trial_data.is_contract_allowed(&contract, &chain_id),
+            FastAuthError::ContractNotAllowed,
+        );
+        ensure(
+            trial_data.is_gas_within_limits(gas, &chain_id),
+            FastAuthError::GasLimitExceeded,
+        );
+        ensure(
+            trial_data.is_deposit_within_limits(deposit, &chain_id),
+            FastAuthError::DepositLimitExceeded,
+        );
+
+        (trial_data, key_usage)
+    }
+
+    /// Checks an action sent directly by a trial key, whose access key on this contract
+    /// is the session key, against the constraints of the key's app.
+    pub(crate) fn assert_signer_action_allowed(&self, action: &Action) -> (TrialData, KeyUsage) {
+        let session_key = SessionKey::from(env::signer_account_pk());
+        let key_usage = self
+            .internal_get_key_usage(&session_key)
+            .or_fail(FastAuthError::SessionKeyNotFound);
+
+        self.assert_action_allowed(action, &session_key, &key_usage.app_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use near_sdk::json_types::Base64VecU8;
+    use near_sdk::serde_json::json;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    const APP_ID: &str = "app";
+    const ED25519_KEY: &str = "ed25519:AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9";
+    /// ed25519 signature of `{"message":"hello"}` by the key seeded with `[1; 32]`
+    const ED25519_SIGNATURE: &str =
+        "RBf/3SkfvNkUlQClozHdPhjzn1gdcYvZryCf/ymeENRUJrwOUfw7YdXpmI7oGigMcvGR0JTWJza4ONRL+G1MCw==";
+
+    fn setup() -> Contract {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(NearToken::from_near(1))
+            .build());
+        let mut contract = Contract::new(accounts(2), accounts(1), accounts(3));
+        contract.add_session_key(
+            ED25519_KEY.parse().unwrap(),
+            "user-path".to_string(),
+            APP_ID.to_string(),
+        );
+        contract
+    }
 
-        let sig_bytes =
-            vec_to_64_byte_array(signature.clone().into()).expect("Invalid signature length");
+    fn ed25519_signature() -> SessionSignature {
+        SessionSignature::Raw(Base64VecU8::from(
+            base64::engine::general_purpose::STANDARD
+                .decode(ED25519_SIGNATURE)
+                .unwrap(),
+        ))
+    }
+
+    #[test]
+    fn ed25519_session_signature_is_accepted() {
+        let contract = setup();
+        contract.assert_valid_signature(
+            &json!({ "message": "hello" }),
+            &ed25519_signature(),
+            &ED25519_KEY.parse().unwrap(),
+            &APP_ID.to_string(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = ":2001:")]
+    fn ed25519_signature_over_another_payload_is_rejected() {
+        let contract = setup();
+        contract.assert_valid_signature(
+            &json!({ "message": "goodbye" }),
+            &ed25519_signature(),
+            &ED25519_KEY.parse().unwrap(),
+            &APP_ID.to_string(),
+        );
+    }
 
-        // Verify the signature
-        let is_valid = env::ed25519_verify(&sig_bytes, &payload_bytes, key_bytes_array);
+    #[test]
+    #[should_panic(expected = ":1007:")]
+    fn session_key_of_another_app_is_rejected() {
+        let contract = setup();
+        contract.assert_valid_signature(
+            &json!({ "message": "hello" }),
+            &ed25519_signature(),
+            &ED25519_KEY.parse().unwrap(),
+            &"other-app".to_string(),
+        );
+    }
+
+    fn set_expiration_time(contract: &mut Contract, expiration_time: u64) {
+        contract.internal_set_trial_data(
+            APP_ID.to_string(),
+            TrialData {
+                constraints_by_chain_id: HashMap::new(),
+                usage_constraints: None,
+                interaction_limits: None,
+                exit_conditions: None,
+                expiration_time: Some(expiration_time),
+                creator_account_id: accounts(4),
+                typed_data_constraints: None,
+                personal_sign_format: None,
+                bitcoin_constraints: None,
+                solana_constraints: None,
+            },
+        );
+    }
+
+    #[test]
+    fn active_trial_is_accepted() {
+        let mut contract = setup();
+        set_expiration_time(&mut contract, 1_000);
+        let (_, key_usage) =
+            contract.assert_trial_active(&ED25519_KEY.parse().unwrap(), &APP_ID.to_string());
+        assert_eq!(key_usage.path, "user-path");
+    }
+
+    #[test]
+    #[should_panic(expected = ":4002:")]
+    fn expired_trial_is_rejected() {
+        let mut contract = setup();
+        set_expiration_time(&mut contract, 1_000);
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .block_timestamp(1_000)
+            .build());
+        contract.assert_trial_active(&ED25519_KEY.parse().unwrap(), &APP_ID.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = ":4009:")]
+    fn paused_app_is_rejected() {
+        let mut contract = setup();
+        set_expiration_time(&mut contract, 1_000);
+        contract.paused_apps.insert(APP_ID.to_string());
+        contract.assert_trial_active(&ED25519_KEY.parse().unwrap(), &APP_ID.to_string());
+    }
+
+    #[test]
+    fn message_nonces_are_reserved_and_must_increase() {
+        let mut contract = setup();
+        let session_key: SessionKey = ED25519_KEY.parse().unwrap();
+        assert_eq!(contract.message_nonces.get(&session_key), Some(&0));
+
+        contract.internal_use_message_nonce(&session_key, 1);
+        contract.internal_use_message_nonce(&session_key, 5);
+        assert_eq!(contract.message_nonces.get(&session_key), Some(&5));
+    }
 
-        require!(is_valid, "Invalid signature");
+    #[test]
+    #[should_panic(expected = ":2005:")]
+    fn replayed_message_nonce_is_rejected() {
+        let mut contract = setup();
+        let session_key: SessionKey = ED25519_KEY.parse().unwrap();
+        contract.internal_use_message_nonce(&session_key, 1);
+        contract.internal_use_message_nonce(&session_key, 1);
     }
 }
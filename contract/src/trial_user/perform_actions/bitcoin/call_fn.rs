@@ -0,0 +1,209 @@
+// trial_user/perform_actions/bitcoin/call_fn.rs
+use crate::*;
+use near_sdk::json_types::U64;
+use near_sdk::{CurveType, PromiseResult};
+use perform_actions::bitcoin::transaction::{
+    compress_public_key, encode_der_signature, hash160, is_p2tr_script, p2wpkh_script,
+    BitcoinTransaction,
+};
+
+/// Output spent by an input of the transaction being signed.
+#[derive(Clone)]
+#[near(serializers = [json])]
+pub struct BitcoinPrevout {
+    /// Amount in satoshis
+    pub value: U64,
+    /// Hex-encoded locking script
+    pub script_pubkey: String,
+}
+
+/// Payload a session key signs to have a Bitcoin transaction signed with the user's MPC key.
+#[derive(Clone)]
+#[near(serializers = [json])]
+pub struct BitcoinPayload {
+    /// Hex-encoded unsigned transaction in the legacy (non-witness) serialization
+    pub unsigned_tx: String,
+    /// Output spent by each input, in input order
+    pub prevouts: Vec<BitcoinPrevout>,
+}
+
+#[near]
+impl Contract {
+    /// Signs every input of a Bitcoin transaction spending the user's P2WPKH outputs.
+    /// Resolves to the hex-encoded finalized transaction, ready to be broadcast.
+    ///
+    /// Taproot (BIP-341) inputs are rejected: key-path spends need Schnorr signatures
+    /// and the MPC signer only produces ECDSA.
+    pub fn sign_bitcoin_transaction(
+        &mut self,
+        signature: SessionSignature,
+        payload: BitcoinPayload,
+        session_key: SessionKey,
+        app_id: AppID,
+    ) -> Promise {
+        self.assert_valid_signature(&payload, &signature, &session_key, &app_id);
+
+        let (trial_data, key_usage) = self.assert_trial_active(&session_key, &app_id);
+
+        let bundle: Bundle = self
+            .internal_get_bundle(&key_usage.path)
+            .or_fail(FastAuthError::UserNotFound);
+
+        let constraints = trial_data
+            .bitcoin_constraints
+            .or_fail(FastAuthError::BitcoinSigningNotEnabled);
+
+        // The user's Bitcoin key is the compressed form of their MPC key
+        ensure(
+            bundle.mpc_key.curve_type() == CurveType::SECP256K1,
+            FastAuthError::UnsupportedBitcoinInput("MPC key is not a secp256k1 key".into()),
+        );
+        let public_key = compress_public_key(&bundle.mpc_key.as_bytes()[1..]);
+        let pubkey_hash = hash160(&public_key);
+        let own_script = p2wpkh_script(&pubkey_hash);
+
+        let tx_bytes = hex::decode(payload.unsigned_tx.trim_start_matches("0x")).or_fail(
+            FastAuthError::InvalidBitcoinTransaction("Invalid hex".into()),
+        );
+        let transaction = BitcoinTransaction::parse(&tx_bytes)
+            .unwrap_or_else(|e| FastAuthError::InvalidBitcoinTransaction(e).panic());
+
+        ensure(
+            payload.prevouts.len() == transaction.inputs.len(),
+            FastAuthError::InvalidBitcoinTransaction("Expected one prevout per input".into()),
+        );
+        ensure(
+            transaction.inputs.len() <= MAX_BITCOIN_INPUTS,
+            FastAuthError::InvalidBitcoinTransaction(format!(
+                "At most {} inputs can be signed at once",
+                MAX_BITCOIN_INPUTS
+            )),
+        );
+
+        // A P2WPKH input is unlocked by its witness alone
+        ensure(
+            transaction
+                .inputs
+                .iter()
+                .all(|input| input.script_sig.is_empty()),
+            FastAuthError::UnsupportedBitcoinInput("P2WPKH inputs need an empty script_sig".into()),
+        );
+
+        // Only the user's own P2WPKH outputs can be spent
+        for prevout in &payload.prevouts {
+            let script = hex::decode(prevout.script_pubkey.trim_start_matches("0x")).or_fail(
+                FastAuthError::InvalidBitcoinTransaction("Invalid prevout script hex".into()),
+            );
+            if is_p2tr_script(&script) {
+                FastAuthError::UnsupportedBitcoinInput(
+                    "Taproot inputs need Schnorr signatures, which the MPC signer does not produce"
+                        .into(),
+                )
+                .panic();
+            }
+            ensure(
+                script == own_script,
+                FastAuthError::UnsupportedBitcoinInput(
+                    "Only P2WPKH outputs of the user's key can be spent".into(),
+                ),
+            );
+        }
+
+        // Check every payment other than change against the app's policy
+        let mut amount_sent: u64 = 0;
+        let mut total_output: u64 = 0;
+        for output in &transaction.outputs {
+            total_output = total_output.checked_add(output.value).or_fail(
+                FastAuthError::InvalidBitcoinTransaction("Output value overflow".into()),
+            );
+            if output.script_pubkey == own_script {
+                continue;
+            }
+            ensure(
+                constraints.is_script_allowed(&output.script_pubkey),
+                FastAuthError::BitcoinScriptNotAllowed,
+            );
+            amount_sent += output.value;
+        }
+
+        // The fee leaves the user's wallet too, so it counts toward the cap. Prevout values
+        // can't be understated: BIP-143 sighashes commit to them.
+        let total_input = payload
+            .prevouts
+            .iter()
+            .try_fold(0u64, |total, prevout| total.checked_add(prevout.value.0))
+            .or_fail(FastAuthError::InvalidBitcoinTransaction(
+                "Input value overflow".into(),
+            ));
+        let fee = total_input.checked_sub(total_output).or_fail(
+            FastAuthError::InvalidBitcoinTransaction("Outputs exceed inputs".into()),
+        );
+        amount_sent += fee;
+        ensure(
+            constraints.is_amount_allowed(amount_sent),
+            FastAuthError::BitcoinAmountExceedsCap,
+        );
+
+        // Request a signature over each input's BIP-143 sighash, joined into one callback
+        let sighashes: Vec<[u8; 32]> = payload
+            .prevouts
+            .iter()
+            .enumerate()
+            .map(|(index, prevout)| {
+                transaction.p2wpkh_sighash(index, &pubkey_hash, prevout.value.0)
+            })
+            .collect();
+        let sign_promise = sighashes
+            .iter()
+            .map(|sighash| self.request_mpc_signature(*sighash, &bundle.path))
+            .reduce(|joined, promise| joined.and(promise))
+            .or_fail(FastAuthError::InvalidBitcoinTransaction(
+                "Transaction has no inputs".into(),
+            ));
+
+        sign_promise.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(Gas::from_tgas(20))
+                .on_sign_bitcoin_txn(transaction, sighashes, public_key),
+        )
+    }
+
+    /// Callback checking each MPC signature against the user's key and
+    /// assembling the P2WPKH witnesses, one per input
+    #[private]
+    pub fn on_sign_bitcoin_txn(
+        &self,
+        transaction: BitcoinTransaction,
+        sighashes: Vec<[u8; 32]>,
+        public_key: Vec<u8>,
+    ) -> String {
+        ensure(
+            env::promise_results_count() as usize == transaction.inputs.len(),
+            FastAuthError::MpcSignatureFailed,
+        );
+
+        let witnesses: Vec<Vec<Vec<u8>>> = (0..transaction.inputs.len())
+            .map(|index| {
+                let signature: SignResult = match env::promise_result(index as u64) {
+                    PromiseResult::Successful(bytes) => near_sdk::serde_json::from_slice(&bytes)
+                        .or_fail(FastAuthError::InvalidMpcSignature(
+                            "Failed to parse sign result".into(),
+                        )),
+                    _ => FastAuthError::MpcSignatureFailed.panic(),
+                };
+                let (r_bytes, s_bytes, _, recovered) = signature
+                    .recover(&sighashes[index])
+                    .unwrap_or_else(|e| e.panic());
+                ensure(
+                    compress_public_key(&recovered) == public_key,
+                    FastAuthError::InvalidMpcSignature(
+                        "Signature does not recover to the user's key".into(),
+                    ),
+                );
+                vec![encode_der_signature(&r_bytes, &s_bytes), public_key.clone()]
+            })
+            .collect();
+
+        hex::encode(transaction.serialize_with_witnesses(&witnesses))
+    }
+}
@@ -0,0 +1,2 @@
+pub mod call_fn;
+pub mod transaction;
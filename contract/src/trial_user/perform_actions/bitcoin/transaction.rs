@@ -0,0 +1,394 @@
+// trial_user/perform_actions/bitcoin/transaction.rs
+use crate::*;
+
+/// Sighash type appended to every signature: commit to all inputs and outputs
+pub const SIGHASH_ALL: u8 = 0x01;
+
+#[derive(Clone)]
+#[near(serializers = [json])]
+pub struct BitcoinInput {
+    /// Previous transaction id in internal (little-endian) byte order
+    pub prev_txid: [u8; 32],
+    pub prev_vout: u32,
+    pub script_sig: Vec<u8>,
+    pub sequence: u32,
+}
+
+#[derive(Clone)]
+#[near(serializers = [json])]
+pub struct BitcoinOutput {
+    pub value: u64,
+    pub script_pubkey: Vec<u8>,
+}
+
+/// An unsigned Bitcoin transaction.
+#[derive(Clone)]
+#[near(serializers = [json])]
+pub struct BitcoinTransaction {
+    pub version: i32,
+    pub inputs: Vec<BitcoinInput>,
+    pub outputs: Vec<BitcoinOutput>,
+    pub lock_time: u32,
+}
+
+impl BitcoinTransaction {
+    /// Parses a transaction in the legacy (non-witness) serialization.
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader { bytes, position: 0 };
+
+        let version = reader.read_u32()? as i32;
+        let input_count = reader.read_varint()?;
+        if input_count == 0 {
+            return Err("Transaction has no inputs or uses the witness serialization".to_string());
+        }
+        let mut inputs = Vec::new();
+        for _ in 0..input_count {
+            let prev_txid = reader.read_array::<32>()?;
+            let prev_vout = reader.read_u32()?;
+            let script_length = reader.read_varint()?;
+            let script_sig = reader.read_bytes(script_length as usize)?.to_vec();
+            let sequence = reader.read_u32()?;
+            inputs.push(BitcoinInput {
+                prev_txid,
+                prev_vout,
+                script_sig,
+                sequence,
+            });
+        }
+
+        let output_count = reader.read_varint()?;
+        let mut outputs = Vec::new();
+        for _ in 0..output_count {
+            let value = reader.read_u64()?;
+            let script_length = reader.read_varint()?;
+            let script_pubkey = reader.read_bytes(script_length as usize)?.to_vec();
+            outputs.push(BitcoinOutput {
+                value,
+                script_pubkey,
+            });
+        }
+
+        let lock_time = reader.read_u32()?;
+        if reader.position != bytes.len() {
+            return Err("Unexpected trailing transaction bytes".to_string());
+        }
+
+        Ok(BitcoinTransaction {
+            version,
+            inputs,
+            outputs,
+            lock_time,
+        })
+    }
+
+    /// BIP-143 signature hash of a P2WPKH input for `SIGHASH_ALL`.
+    pub fn p2wpkh_sighash(
+        &self,
+        input_index: usize,
+        pubkey_hash: &[u8; 20],
+        amount: u64,
+    ) -> [u8; 32] {
+        let mut prevouts = Vec::new();
+        let mut sequences = Vec::new();
+        for input in &self.inputs {
+            prevouts.extend_from_slice(&input.prev_txid);
+            prevouts.extend_from_slice(&input.prev_vout.to_le_bytes());
+            sequences.extend_from_slice(&input.sequence.to_le_bytes());
+        }
+        let mut outputs = Vec::new();
+        for output in &self.outputs {
+            write_output(&mut outputs, output);
+        }
+
+        let input = &self.inputs[input_index];
+        let mut preimage = Vec::new();
+        preimage.extend_from_slice(&self.version.to_le_bytes());
+        preimage.extend_from_slice(&double_sha256(&prevouts));
+        preimage.extend_from_slice(&double_sha256(&sequences));
+        preimage.extend_from_slice(&input.prev_txid);
+        preimage.extend_from_slice(&input.prev_vout.to_le_bytes());
+        // scriptCode of P2WPKH is the P2PKH script of the key hash
+        preimage.extend_from_slice(&[0x19, 0x76, 0xa9, 0x14]);
+        preimage.extend_from_slice(pubkey_hash);
+        preimage.extend_from_slice(&[0x88, 0xac]);
+        preimage.extend_from_slice(&amount.to_le_bytes());
+        preimage.extend_from_slice(&input.sequence.to_le_bytes());
+        preimage.extend_from_slice(&double_sha256(&outputs));
+        preimage.extend_from_slice(&self.lock_time.to_le_bytes());
+        preimage.extend_from_slice(&(SIGHASH_ALL as u32).to_le_bytes());
+
+        double_sha256(&preimage)
+    }
+
+    /// Serializes the transaction with a witness stack per input (BIP-144).
+    pub fn serialize_with_witnesses(&self, witnesses: &[Vec<Vec<u8>>]) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        encoded.extend_from_slice(&self.version.to_le_bytes());
+        // Segwit marker and flag
+        encoded.extend_from_slice(&[0x00, 0x01]);
+
+        write_varint(&mut encoded, self.inputs.len() as u64);
+        for input in &self.inputs {
+            encoded.extend_from_slice(&input.prev_txid);
+            encoded.extend_from_slice(&input.prev_vout.to_le_bytes());
+            write_varint(&mut encoded, input.script_sig.len() as u64);
+            encoded.extend_from_slice(&input.script_sig);
+            encoded.extend_from_slice(&input.sequence.to_le_bytes());
+        }
+
+        write_varint(&mut encoded, self.outputs.len() as u64);
+        for output in &self.outputs {
+            write_output(&mut encoded, output);
+        }
+
+        for witness in witnesses {
+            write_varint(&mut encoded, witness.len() as u64);
+            for item in witness {
+                write_varint(&mut encoded, item.len() as u64);
+                encoded.extend_from_slice(item);
+            }
+        }
+
+        encoded.extend_from_slice(&self.lock_time.to_le_bytes());
+        encoded
+    }
+}
+
+/// `OP_0 <20-byte key hash>`
+pub fn p2wpkh_script(pubkey_hash: &[u8; 20]) -> Vec<u8> {
+    let mut script = vec![0x00, 0x14];
+    script.extend_from_slice(pubkey_hash);
+    script
+}
+
+/// `OP_1 <32-byte output key>`
+pub fn is_p2tr_script(script: &[u8]) -> bool {
+    script.len() == 34 && script[0] == 0x51 && script[1] == 0x20
+}
+
+/// HASH160 of a compressed public key
+pub fn hash160(data: &[u8]) -> [u8; 20] {
+    env::ripemd160_array(&env::sha256_array(data))
+}
+
+/// Compresses a 64-byte uncompressed secp256k1 key (without the 0x04 prefix).
+pub fn compress_public_key(uncompressed: &[u8]) -> Vec<u8> {
+    let prefix = if uncompressed[63] & 1 == 0 {
+        0x02
+    } else {
+        0x03
+    };
+    let mut compressed = vec![prefix];
+    compressed.extend_from_slice(&uncompressed[..32]);
+    compressed
+}
+
+/// DER-encodes an ECDSA signature, normalizing `s` to the lower half of the
+/// curve order as Bitcoin's standardness rules require, and appends the sighash type.
+pub fn encode_der_signature(r: &[u8], s: &[u8]) -> Vec<u8> {
+    let (s_bytes, _) = normalize_low_s(s);
+
+    let r_int = der_integer(r);
+    let s_int = der_integer(&s_bytes);
+
+    let mut encoded = vec![0x30, (r_int.len() + s_int.len()) as u8];
+    encoded.extend(r_int);
+    encoded.extend(s_int);
+    encoded.push(SIGHASH_ALL);
+    encoded
+}
+
+/// Minimal DER INTEGER for a big-endian unsigned value
+fn der_integer(value: &[u8]) -> Vec<u8> {
+    let start = value
+        .iter()
+        .position(|b| *b != 0)
+        .unwrap_or(value.len() - 1);
+    let mut digits = value[start..].to_vec();
+    // A set high bit would make the integer negative
+    if digits[0] & 0x80 != 0 {
+        digits.insert(0, 0);
+    }
+
+    let mut encoded = vec![0x02, digits.len() as u8];
+    encoded.extend(digits);
+    encoded
+}
+
+fn double_sha256(data: &[u8]) -> [u8; 32] {
+    env::sha256_array(&env::sha256_array(data))
+}
+
+fn write_output(encoded: &mut Vec<u8>, output: &BitcoinOutput) {
+    encoded.extend_from_slice(&output.value.to_le_bytes());
+    write_varint(encoded, output.script_pubkey.len() as u64);
+    encoded.extend_from_slice(&output.script_pubkey);
+}
+
+fn write_varint(encoded: &mut Vec<u8>, value: u64) {
+    match value {
+        0..=0xfc => encoded.push(value as u8),
+        0xfd..=0xffff => {
+            encoded.push(0xfd);
+            encoded.extend_from_slice(&(value as u16).to_le_bytes());
+        }
+        0x10000..=0xffff_ffff => {
+            encoded.push(0xfe);
+            encoded.extend_from_slice(&(value as u32).to_le_bytes());
+        }
+        _ => {
+            encoded.push(0xff);
+            encoded.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn read_bytes(&mut self, length: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| "Unexpected end of transaction".to_string())?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.read_bytes(N)?);
+        Ok(array)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.read_array::<4>()?))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.read_array::<8>()?))
+    }
+
+    fn read_varint(&mut self) -> Result<u64, String> {
+        match self.read_array::<1>()?[0] {
+            0xfd => Ok(u16::from_le_bytes(self.read_array::<2>()?) as u64),
+            0xfe => Ok(self.read_u32()? as u64),
+            0xff => self.read_u64(),
+            value => Ok(value as u64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Unsigned transaction of BIP-143's native P2WPKH example
+    const BIP143_UNSIGNED_TX: &str = "0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f0000000000eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac11000000";
+
+    fn bip143_transaction() -> BitcoinTransaction {
+        BitcoinTransaction::parse(&hex::decode(BIP143_UNSIGNED_TX).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn p2wpkh_sighash_matches_bip143_example() {
+        let transaction = bip143_transaction();
+        assert_eq!(transaction.inputs.len(), 2);
+        assert_eq!(transaction.outputs.len(), 2);
+        assert_eq!(transaction.lock_time, 17);
+
+        let pubkey_hash: [u8; 20] = hex::decode("1d0f172a0ecb48aee1be1f2687d2963ae33f71a1")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(
+            hex::encode(transaction.p2wpkh_sighash(1, &pubkey_hash, 600_000_000)),
+            "c37af31116d1b27caf68aae9e3ac82f1477929014d5b917657d0eb49478cb670"
+        );
+    }
+
+    #[test]
+    fn witnesses_follow_the_outputs() {
+        let transaction = bip143_transaction();
+        let witnesses = vec![vec![], vec![vec![0xaa; 2], vec![0xbb]]];
+        let encoded = transaction.serialize_with_witnesses(&witnesses);
+
+        let unsigned = hex::decode(BIP143_UNSIGNED_TX).unwrap();
+        // Marker and flag follow the version
+        assert_eq!(&encoded[..6], &[0x01, 0x00, 0x00, 0x00, 0x00, 0x01]);
+        assert_eq!(
+            &encoded[6..unsigned.len() - 2],
+            &unsigned[4..unsigned.len() - 4]
+        );
+        assert_eq!(
+            &encoded[unsigned.len() - 2..],
+            &[0x00, 0x02, 0x02, 0xaa, 0xaa, 0x01, 0xbb, 0x11, 0x00, 0x00, 0x00]
+        );
+    }
+
+    #[test]
+    fn trailing_bytes_are_rejected() {
+        let mut bytes = hex::decode(BIP143_UNSIGNED_TX).unwrap();
+        bytes.push(0);
+        assert!(BitcoinTransaction::parse(&bytes).is_err());
+        assert!(BitcoinTransaction::parse(&bytes[..bytes.len() - 2]).is_err());
+    }
+
+    #[test]
+    fn der_integers_with_the_high_bit_set_are_padded() {
+        let mut s = [0u8; 32];
+        s[31] = 1;
+        let encoded = encode_der_signature(&[0x80; 32], &s);
+
+        let mut expected = vec![0x30, 0x26, 0x02, 0x21, 0x00];
+        expected.extend_from_slice(&[0x80; 32]);
+        expected.extend_from_slice(&[0x02, 0x01, 0x01, SIGHASH_ALL]);
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn der_signatures_use_low_s() {
+        // Curve order minus one normalizes to one
+        let high_s =
+            hex::decode("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364140")
+                .unwrap();
+        let mut low_s = [0u8; 32];
+        low_s[31] = 1;
+        assert_eq!(
+            encode_der_signature(&[0x11; 32], &high_s),
+            encode_der_signature(&[0x11; 32], &low_s)
+        );
+    }
+
+    #[test]
+    fn varints_switch_width_at_their_boundaries() {
+        let cases: [(u64, &[u8]); 7] = [
+            (0xfc, &[0xfc]),
+            (0xfd, &[0xfd, 0xfd, 0x00]),
+            (0xffff, &[0xfd, 0xff, 0xff]),
+            (0x1_0000, &[0xfe, 0x00, 0x00, 0x01, 0x00]),
+            (0xffff_ffff, &[0xfe, 0xff, 0xff, 0xff, 0xff]),
+            (
+                0x1_0000_0000,
+                &[0xff, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00],
+            ),
+            (0, &[0x00]),
+        ];
+        for (value, expected) in cases {
+            let mut encoded = Vec::new();
+            write_varint(&mut encoded, value);
+            assert_eq!(encoded, expected);
+
+            let mut reader = Reader {
+                bytes: &encoded,
+                position: 0,
+            };
+            assert_eq!(reader.read_varint(), Ok(value));
+        }
+    }
+}
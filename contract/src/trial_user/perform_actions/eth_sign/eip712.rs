@@ -0,0 +1,516 @@
+// trial_user/perform_actions/eth_sign/eip712.rs
+use crate::perform_actions::evm::integer::{self, IntegerError};
+use crate::*;
+use ethereum_types::U256;
+use near_sdk::serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+/// Name of the struct type describing the signing domain
+pub const EIP712_DOMAIN: &str = "EIP712Domain";
+
+/// Fields of `EIP712Domain` in the order the specification defines them
+const EIP712_DOMAIN_FIELDS: [(&str, &str); 5] = [
+    ("name", "string"),
+    ("version", "string"),
+    ("chainId", "uint256"),
+    ("verifyingContract", "address"),
+    ("salt", "bytes32"),
+];
+
+#[derive(Clone)]
+#[near(serializers = [json])]
+pub struct TypedDataField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+/// EIP-712 typed data, in the JSON shape used by `eth_signTypedData_v4`.
+#[derive(Clone)]
+#[near(serializers = [json])]
+#[serde(rename_all = "camelCase")]
+pub struct TypedData {
+    pub types: HashMap<String, Vec<TypedDataField>>,
+    pub primary_type: String,
+    pub domain: Map<String, Value>,
+    pub message: Value,
+}
+
+impl TypedData {
+    /// Computes `keccak256(0x19 0x01 || domainSeparator || hashStruct(message))`.
+    pub fn signing_hash(&self) -> Result<[u8; 32], String> {
+        let domain_separator = self.domain_separator()?;
+        let message_hash = self.hash_struct(&self.primary_type, &self.message)?;
+
+        let mut encoded = vec![0x19, 0x01];
+        encoded.extend_from_slice(&domain_separator);
+        encoded.extend_from_slice(&message_hash);
+        Ok(env::keccak256_array(&encoded))
+    }
+
+    pub fn domain_separator(&self) -> Result<[u8; 32], String> {
+        self.hash_struct(EIP712_DOMAIN, &Value::Object(self.domain.clone()))
+    }
+
+    /// The domain's `chainId`, if it is one of the hashed domain fields.
+    pub fn domain_chain_id(&self) -> Result<Option<u64>, String> {
+        self.hashed_domain_field("chainId")?
+            .map(|value| parse_integer(value, 64, false, "domain.chainId").map(|id| id.low_u64()))
+            .transpose()
+    }
+
+    /// The domain's `verifyingContract`, if it is one of the hashed domain fields.
+    pub fn domain_verifying_contract(&self) -> Result<Option<[u8; 20]>, String> {
+        self.hashed_domain_field("verifyingContract")?
+            .map(|value| parse_address(value, "domain.verifyingContract"))
+            .transpose()
+    }
+
+    /// A standard domain field's value, read only if `EIP712Domain` declares it with its
+    /// standard type, so that the signing hash binds whatever the allowlist checked.
+    /// A value the declared fields leave out of the hash is rejected.
+    fn hashed_domain_field(&self, name: &str) -> Result<Option<&Value>, String> {
+        let declared = self
+            .fields(EIP712_DOMAIN)?
+            .into_iter()
+            .find(|field| field.name == name);
+
+        match declared {
+            Some(field) => {
+                let standard_kind = EIP712_DOMAIN_FIELDS
+                    .iter()
+                    .find(|(field_name, _)| *field_name == name)
+                    .map(|(_, kind)| *kind);
+                if standard_kind != Some(field.kind.as_str()) {
+                    return Err(format!(
+                        "Unexpected type {} for domain.{}",
+                        field.kind, name
+                    ));
+                }
+                self.domain
+                    .get(name)
+                    .map(Some)
+                    .ok_or_else(|| format!("Missing field {}.{}", EIP712_DOMAIN, name))
+            }
+            None if self.domain.contains_key(name) => Err(format!(
+                "domain.{} is not declared in {}",
+                name, EIP712_DOMAIN
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Computes `keccak256(typeHash || encodeData(value))` for a struct type.
+    pub fn hash_struct(&self, type_name: &str, value: &Value) -> Result<[u8; 32], String> {
+        let object = value
+            .as_object()
+            .ok_or_else(|| format!("Expected an object for {}", type_name))?;
+
+        let type_hash = env::keccak256_array(self.encode_type(type_name)?.as_bytes());
+        let mut encoded = type_hash.to_vec();
+        for field in self.fields(type_name)? {
+            let field_value = object
+                .get(&field.name)
+                .ok_or_else(|| format!("Missing field {}.{}", type_name, field.name))?;
+            let path = format!("{}.{}", type_name, field.name);
+            encoded.extend_from_slice(&self.encode_value(&field.kind, field_value, &path)?);
+        }
+
+        Ok(env::keccak256_array(&encoded))
+    }
+
+    /// Encodes a struct type and, sorted by name, every struct type it references,
+    /// e.g. `Mail(Person from,Person to,string contents)Person(string name,address wallet)`.
+    pub fn encode_type(&self, type_name: &str) -> Result<String, String> {
+        let mut dependencies = BTreeSet::new();
+        self.collect_dependencies(type_name, &mut dependencies)?;
+        dependencies.remove(type_name);
+
+        let mut encoded = self.encode_single_type(type_name)?;
+        for dependency in dependencies {
+            encoded.push_str(&self.encode_single_type(&dependency)?);
+        }
+        Ok(encoded)
+    }
+
+    fn encode_single_type(&self, type_name: &str) -> Result<String, String> {
+        let fields: Vec<String> = self
+            .fields(type_name)?
+            .iter()
+            .map(|field| format!("{} {}", field.kind, field.name))
+            .collect();
+        Ok(format!("{}({})", type_name, fields.join(",")))
+    }
+
+    fn collect_dependencies(
+        &self,
+        type_name: &str,
+        found: &mut BTreeSet<String>,
+    ) -> Result<(), String> {
+        if found.contains(type_name) || !self.is_struct(type_name) {
+            return Ok(());
+        }
+        found.insert(type_name.to_string());
+
+        for field in self.fields(type_name)? {
+            self.collect_dependencies(base_type(&field.kind), found)?;
+        }
+        Ok(())
+    }
+
+    fn is_struct(&self, type_name: &str) -> bool {
+        type_name == EIP712_DOMAIN || self.types.contains_key(type_name)
+    }
+
+    /// Fields of a struct type. `EIP712Domain` may be omitted from `types`,
+    /// in which case it is made up of the domain fields that are present.
+    fn fields(&self, type_name: &str) -> Result<Vec<TypedDataField>, String> {
+        if let Some(fields) = self.types.get(type_name) {
+            return Ok(fields.clone());
+        }
+        if type_name == EIP712_DOMAIN {
+            return Ok(EIP712_DOMAIN_FIELDS
+                .iter()
+                .filter(|(name, _)| self.domain.contains_key(*name))
+                .map(|(name, kind)| TypedDataField {
+                    name: name.to_string(),
+                    kind: kind.to_string(),
+                })
+                .collect());
+        }
+        Err(format!("Unknown struct type {}", type_name))
+    }
+
+    /// Encodes a single value into its 32-byte slot.
+    fn encode_value(&self, kind: &str, value: &Value, path: &str) -> Result<[u8; 32], String> {
+        // Arrays (`T[]` or `T[n]`) hash the concatenation of their encoded elements
+        if let Some(open) = kind.rfind('[') {
+            let element_kind = &kind[..open];
+            let items = value
+                .as_array()
+                .ok_or_else(|| format!("Expected an array for {}", path))?;
+
+            let length = &kind[open + 1..kind.len() - 1];
+            if !length.is_empty() && length.parse::<usize>() != Ok(items.len()) {
+                return Err(format!("Expected {} items for {}", length, path));
+            }
+
+            let mut encoded = Vec::with_capacity(items.len() * 32);
+            for (index, item) in items.iter().enumerate() {
+                let item_path = format!("{}[{}]", path, index);
+                encoded.extend_from_slice(&self.encode_value(element_kind, item, &item_path)?);
+            }
+            return Ok(env::keccak256_array(&encoded));
+        }
+
+        if self.is_struct(kind) {
+            return self.hash_struct(kind, value);
+        }
+
+        match kind {
+            "string" => {
+                let s = value
+                    .as_str()
+                    .ok_or_else(|| format!("Expected a string for {}", path))?;
+                Ok(env::keccak256_array(s.as_bytes()))
+            }
+            "bytes" => Ok(env::keccak256_array(&parse_hex_bytes(value, path)?)),
+            "bool" => {
+                let b = value
+                    .as_bool()
+                    .ok_or_else(|| format!("Expected a bool for {}", path))?;
+                Ok(u256_to_word(U256::from(b as u8)))
+            }
+            "address" => {
+                let mut word = [0u8; 32];
+                word[12..].copy_from_slice(&parse_address(value, path)?);
+                Ok(word)
+            }
+            _ => {
+                if let Some(size) = kind.strip_prefix("bytes") {
+                    let size = parse_size(size, 1, 32, kind)?;
+                    let bytes = parse_hex_bytes(value, path)?;
+                    if bytes.len() != size {
+                        return Err(format!("Expected {} bytes for {}", size, path));
+                    }
+                    let mut word = [0u8; 32];
+                    word[..size].copy_from_slice(&bytes);
+                    Ok(word)
+                } else if let Some(bits) = kind.strip_prefix("uint") {
+                    let bits = parse_bits(bits, kind)?;
+                    Ok(u256_to_word(parse_integer(value, bits, false, path)?))
+                } else if let Some(bits) = kind.strip_prefix("int") {
+                    let bits = parse_bits(bits, kind)?;
+                    Ok(u256_to_word(parse_integer(value, bits, true, path)?))
+                } else {
+                    Err(format!("Unsupported type {} for {}", kind, path))
+                }
+            }
+        }
+    }
+}
+
+/// Strips every array suffix from a type, e.g. `Person[][2]` -> `Person`.
+fn base_type(kind: &str) -> &str {
+    kind.split('[').next().unwrap_or(kind)
+}
+
+fn parse_size(size: &str, min: usize, max: usize, kind: &str) -> Result<usize, String> {
+    match size.parse::<usize>() {
+        Ok(size) if (min..=max).contains(&size) => Ok(size),
+        _ => Err(format!("Unsupported type {}", kind)),
+    }
+}
+
+/// Bit width of `uintN`/`intN`, where a bare `uint`/`int` means 256.
+fn parse_bits(bits: &str, kind: &str) -> Result<usize, String> {
+    if bits.is_empty() {
+        return Ok(256);
+    }
+    match bits.parse::<usize>() {
+        Ok(bits) if integer::is_valid_integer_width(bits) => Ok(bits),
+        _ => Err(format!("Unsupported type {}", kind)),
+    }
+}
+
+fn u256_to_word(value: U256) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    value.to_big_endian(&mut word);
+    word
+}
+
+fn parse_hex_bytes(value: &Value, path: &str) -> Result<Vec<u8>, String> {
+    let s = value
+        .as_str()
+        .ok_or_else(|| format!("Expected a hex string for {}", path))?;
+    hex::decode(s.trim_start_matches("0x")).map_err(|_| format!("Invalid hex for {}", path))
+}
+
+fn parse_address(value: &Value, path: &str) -> Result<[u8; 20], String> {
+    parse_hex_bytes(value, path)?
+        .try_into()
+        .map_err(|_| format!("Address must be 20 bytes for {}", path))
+}
+
+/// Parses a `uintN` (`signed == false`) or `intN` value given as a JSON number or a
+/// decimal / `0x` hex string. Signed values come back in 256-bit two's complement.
+fn parse_integer(value: &Value, bits: usize, signed: bool, path: &str) -> Result<U256, String> {
+    let kind = format!("{}{}", if signed { "int" } else { "uint" }, bits);
+    let digits = match value {
+        Value::Number(n) if n.is_i64() || n.is_u64() => n.to_string(),
+        Value::String(s) => s.clone(),
+        _ => return Err(format!("Invalid integer for {}", path)),
+    };
+    let parsed = if signed {
+        integer::parse_int(&digits, bits)
+    } else {
+        integer::parse_uint(&digits, bits)
+    };
+    parsed.map_err(|error| match error {
+        IntegerError::UnsupportedWidth => format!("Unsupported type {}", kind),
+        IntegerError::Invalid => format!("Invalid integer for {}", path),
+        IntegerError::OutOfRange => format!("Value out of range for {} at {}", kind, path),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::serde_json::json;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    fn domain_type() -> Value {
+        json!([
+            { "name": "name", "type": "string" },
+            { "name": "version", "type": "string" },
+            { "name": "chainId", "type": "uint256" },
+            { "name": "verifyingContract", "type": "address" },
+        ])
+    }
+
+    fn domain() -> Value {
+        json!({
+            "name": "Ether Mail",
+            "version": "1",
+            "chainId": 1,
+            "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC",
+        })
+    }
+
+    /// The `Mail` example from the EIP-712 specification
+    fn mail() -> TypedData {
+        testing_env!(VMContextBuilder::new().build());
+        near_sdk::serde_json::from_value(json!({
+            "types": {
+                "EIP712Domain": domain_type(),
+                "Person": [
+                    { "name": "name", "type": "string" },
+                    { "name": "wallet", "type": "address" },
+                ],
+                "Mail": [
+                    { "name": "from", "type": "Person" },
+                    { "name": "to", "type": "Person" },
+                    { "name": "contents", "type": "string" },
+                ],
+            },
+            "primaryType": "Mail",
+            "domain": domain(),
+            "message": {
+                "from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+                "to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+                "contents": "Hello, Bob!",
+            },
+        }))
+        .unwrap()
+    }
+
+    fn hash(hex_hash: &str) -> [u8; 32] {
+        hex::decode(hex_hash).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn mail_golden_vector() {
+        let typed_data = mail();
+        assert_eq!(
+            typed_data.encode_type("Mail").unwrap(),
+            "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+        );
+        assert_eq!(
+            env::keccak256_array(typed_data.encode_type("Mail").unwrap().as_bytes()),
+            hash("a0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2")
+        );
+        assert_eq!(
+            typed_data.domain_separator().unwrap(),
+            hash("f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f")
+        );
+        assert_eq!(
+            typed_data.hash_struct("Mail", &typed_data.message).unwrap(),
+            hash("c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e")
+        );
+        assert_eq!(
+            typed_data.signing_hash().unwrap(),
+            hash("be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2")
+        );
+    }
+
+    /// The array example from MetaMask's `eth_signTypedData_v4` test suite
+    #[test]
+    fn arrays_of_structs_and_addresses() {
+        testing_env!(VMContextBuilder::new().build());
+        let typed_data: TypedData = near_sdk::serde_json::from_value(json!({
+            "types": {
+                "EIP712Domain": domain_type(),
+                "Person": [
+                    { "name": "name", "type": "string" },
+                    { "name": "wallets", "type": "address[]" },
+                ],
+                "Mail": [
+                    { "name": "from", "type": "Person" },
+                    { "name": "to", "type": "Person[]" },
+                    { "name": "contents", "type": "string" },
+                ],
+                "Group": [
+                    { "name": "name", "type": "string" },
+                    { "name": "members", "type": "Person[]" },
+                ],
+            },
+            "primaryType": "Mail",
+            "domain": domain(),
+            "message": {
+                "from": {
+                    "name": "Cow",
+                    "wallets": [
+                        "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826",
+                        "0xDeaDbeefdEAdbeefdEadbEEFdeadbeEFdEaDbeeF",
+                    ],
+                },
+                "to": [{
+                    "name": "Bob",
+                    "wallets": [
+                        "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB",
+                        "0xB0BdaBea57B0BDABeA57b0bdABEA57b0BDabEa57",
+                        "0xB0B0b0b0b0b0B000000000000000000000000000",
+                    ],
+                }],
+                "contents": "Hello, Bob!",
+            },
+        }))
+        .unwrap();
+
+        // Group isn't referenced by Mail, so it stays out of the encoding
+        assert_eq!(
+            typed_data.encode_type("Mail").unwrap(),
+            "Mail(Person from,Person[] to,string contents)Person(string name,address[] wallets)"
+        );
+        assert_eq!(
+            typed_data.hash_struct("Mail", &typed_data.message).unwrap(),
+            hash("eb4221181ff3f1a83ea7313993ca9218496e424604ba9492bb4052c03d5c3df8")
+        );
+        assert_eq!(
+            typed_data.signing_hash().unwrap(),
+            hash("a85c2e2b118698e88db68a8105b794a8cc7cec074e89ef991cb4f5f533819cc2")
+        );
+    }
+
+    #[test]
+    fn fixed_size_arrays_must_have_their_length() {
+        let mut typed_data = mail();
+        typed_data.types.insert(
+            "Mail".to_string(),
+            vec![TypedDataField {
+                name: "to".to_string(),
+                kind: "Person[2]".to_string(),
+            }],
+        );
+        typed_data.message = json!({ "to": [typed_data.message["to"].clone()] });
+        assert!(typed_data.signing_hash().is_err());
+    }
+
+    #[test]
+    fn domain_fields_are_read_from_the_hashed_domain() {
+        let typed_data = mail();
+        assert_eq!(typed_data.domain_chain_id(), Ok(Some(1)));
+        assert_eq!(typed_data.domain_verifying_contract(), Ok(Some([0xcc; 20])));
+
+        // Without a declared EIP712Domain, the fields present make up the domain
+        let mut typed_data = mail();
+        typed_data.types.remove(EIP712_DOMAIN);
+        assert_eq!(typed_data.domain_chain_id(), Ok(Some(1)));
+        assert_eq!(typed_data.domain_separator(), mail().domain_separator());
+    }
+
+    #[test]
+    fn undeclared_domain_fields_are_rejected() {
+        let mut typed_data = mail();
+        typed_data.types.insert(
+            EIP712_DOMAIN.to_string(),
+            vec![TypedDataField {
+                name: "name".to_string(),
+                kind: "string".to_string(),
+            }],
+        );
+        // chainId and verifyingContract are in the JSON but not in the signed hash
+        assert!(typed_data.domain_chain_id().is_err());
+        assert!(typed_data.domain_verifying_contract().is_err());
+
+        typed_data.domain.remove("chainId");
+        typed_data.domain.remove("verifyingContract");
+        assert_eq!(typed_data.domain_chain_id(), Ok(None));
+        assert_eq!(typed_data.domain_verifying_contract(), Ok(None));
+    }
+
+    #[test]
+    fn domain_fields_must_have_their_standard_types() {
+        let mut typed_data = mail();
+        typed_data
+            .types
+            .get_mut(EIP712_DOMAIN)
+            .unwrap()
+            .iter_mut()
+            .find(|field| field.name == "chainId")
+            .unwrap()
+            .kind = "string".to_string();
+        assert!(typed_data.domain_chain_id().is_err());
+    }
+}
@@ -0,0 +1,4 @@
+pub mod eip712;
+pub mod personal_sign;
+pub mod siwe;
+pub mod typed_data;
@@ -0,0 +1,52 @@
+// trial_user/perform_actions/eth_sign/personal_sign.rs
+use crate::*;
+
+/// Payload a session key signs to request a `personal_sign` signature from the user's EVM address.
+#[derive(Clone)]
+#[near(serializers = [json])]
+pub struct PersonalSignPayload {
+    pub message: String,
+    /// Above every nonce the session key has signed a message request with, so the
+    /// request can't be replayed
+    pub nonce: U64,
+}
+
+#[near]
+impl Contract {
+    /// Signs a message with the EIP-191 `personal_sign` prefix using the user's MPC key.
+    /// Resolves to the hex-encoded 65-byte `r || s || v` signature.
+    pub fn personal_sign(
+        &mut self,
+        signature: SessionSignature,
+        payload: PersonalSignPayload,
+        session_key: SessionKey,
+        app_id: AppID,
+    ) -> Promise {
+        self.assert_valid_signature(&payload, &signature, &session_key, &app_id);
+        let (trial_data, key_usage) = self.assert_trial_active(&session_key, &app_id);
+        self.internal_use_message_nonce(&session_key, payload.nonce.0);
+
+        let bundle: Bundle = self
+            .internal_get_bundle(&key_usage.path)
+            .or_fail(FastAuthError::UserNotFound);
+
+        // Check the message against the app's allowed format
+        let message_format = trial_data
+            .personal_sign_format
+            .or_fail(FastAuthError::MessageSigningNotEnabled);
+        ensure(
+            message_format.is_message_allowed(&payload.message, &bundle.eth_address),
+            FastAuthError::MessageFormatNotAllowed,
+        );
+
+        let hashed_payload = eip191_hash(payload.message.as_bytes());
+
+        // Call the MPC contract to get a signature
+        self.request_mpc_signature(hashed_payload, &bundle.path)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(Gas::from_tgas(10))
+                    .on_sign_eth_message(hashed_payload, bundle.eth_address),
+            )
+    }
+}
@@ -0,0 +1,304 @@
+// trial_user/perform_actions/eth_sign/siwe.rs
+use crate::*;
+
+const SIWE_HEADER_SUFFIX: &str = " wants you to sign in with your Ethereum account:";
+
+/// A Sign-In with Ethereum (EIP-4361) message.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SiweMessage {
+    pub domain: String,
+    pub address: String,
+    pub statement: Option<String>,
+    pub uri: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub nonce: String,
+    pub issued_at: String,
+    pub expiration_time: Option<String>,
+    pub not_before: Option<String>,
+    pub request_id: Option<String>,
+    pub resources: Vec<String>,
+}
+
+impl SiweMessage {
+    /// Parses a message following the EIP-4361 template.
+    pub fn parse(message: &str) -> Result<Self, String> {
+        let mut lines = message.split('\n');
+
+        let domain = lines
+            .next()
+            .and_then(|line| line.strip_suffix(SIWE_HEADER_SUFFIX))
+            .ok_or("Missing SIWE header")?
+            .to_string();
+        let address = lines.next().ok_or("Missing SIWE address")?.to_string();
+        if address.len() != 42 || !address.starts_with("0x") {
+            return Err("Invalid SIWE address".to_string());
+        }
+        if lines.next() != Some("") {
+            return Err("Expected blank line after SIWE address".to_string());
+        }
+
+        // The statement is optional and followed by a blank line when present
+        let mut next = lines.next().ok_or("Missing SIWE fields")?;
+        let mut statement = None;
+        if !next.starts_with("URI: ") {
+            if !next.is_empty() {
+                statement = Some(next.to_string());
+                if lines.next() != Some("") {
+                    return Err("Expected blank line after SIWE statement".to_string());
+                }
+            }
+            next = lines.next().ok_or("Missing SIWE fields")?;
+        }
+
+        let uri = required_field(Some(next), "URI")?;
+        let version = required_field(lines.next(), "Version")?;
+        let chain_id = required_field(lines.next(), "Chain ID")?
+            .parse::<u64>()
+            .map_err(|_| "Invalid SIWE chain ID".to_string())?;
+        let nonce = required_field(lines.next(), "Nonce")?;
+        let issued_at = required_field(lines.next(), "Issued At")?;
+
+        let mut message = SiweMessage {
+            domain,
+            address,
+            statement,
+            uri,
+            version,
+            chain_id,
+            nonce,
+            issued_at,
+            expiration_time: None,
+            not_before: None,
+            request_id: None,
+            resources: vec![],
+        };
+
+        // Remaining optional fields, in template order
+        let mut line = lines.next();
+        if let Some(value) = line.and_then(|l| l.strip_prefix("Expiration Time: ")) {
+            message.expiration_time = Some(value.to_string());
+            line = lines.next();
+        }
+        if let Some(value) = line.and_then(|l| l.strip_prefix("Not Before: ")) {
+            message.not_before = Some(value.to_string());
+            line = lines.next();
+        }
+        if let Some(value) = line.and_then(|l| l.strip_prefix("Request ID: ")) {
+            message.request_id = Some(value.to_string());
+            line = lines.next();
+        }
+        if line == Some("Resources:") {
+            line = lines.next();
+            while let Some(resource) = line.and_then(|l| l.strip_prefix("- ")) {
+                message.resources.push(resource.to_string());
+                line = lines.next();
+            }
+        }
+        if line.is_some() {
+            return Err("Unexpected trailing SIWE content".to_string());
+        }
+
+        Ok(message)
+    }
+}
+
+fn required_field(line: Option<&str>, name: &str) -> Result<String, String> {
+    line.and_then(|l| l.strip_prefix(name))
+        .and_then(|l| l.strip_prefix(": "))
+        .map(|value| value.to_string())
+        .ok_or_else(|| format!("Missing SIWE field {}", name))
+}
+
+impl MessageFormat {
+    /// Checks if a message may be signed for the user owning `eth_address`.
+    pub fn is_message_allowed(&self, message: &str, eth_address: &AccountId) -> bool {
+        match self {
+            MessageFormat::Any => true,
+            MessageFormat::Prefixed(prefixes) => prefixes
+                .iter()
+                .any(|prefix| message.starts_with(prefix.as_str())),
+            MessageFormat::Siwe(constraints) => {
+                let Ok(siwe) = SiweMessage::parse(message) else {
+                    return false;
+                };
+
+                siwe.domain == constraints.domain
+                    && siwe.version == "1"
+                    && siwe.address.to_lowercase() == eth_address.as_str()
+                    && constraints
+                        .allowed_chain_ids
+                        .as_ref()
+                        .is_none_or(|chain_ids| chain_ids.contains(&siwe.chain_id))
+                    && constraints
+                        .uri_prefix
+                        .as_ref()
+                        .is_none_or(|prefix| siwe.uri.starts_with(prefix.as_str()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+
+    /// The example message from EIP-4361
+    fn spec_message() -> String {
+        [
+            "service.invalid wants you to sign in with your Ethereum account:",
+            ADDRESS,
+            "",
+            "I accept the ServiceOrg Terms of Service: https://service.invalid/tos",
+            "",
+            "URI: https://service.invalid/login",
+            "Version: 1",
+            "Chain ID: 1",
+            "Nonce: 32891756",
+            "Issued At: 2021-09-30T16:25:24Z",
+            "Resources:",
+            "- ipfs://bafybeiemxf5abjwjbikoz4mc3a3dla6ual3jsgpdr4cjr3oz3evfyavhwq/",
+            "- https://example.com/my-web2-claim.json",
+        ]
+        .join("\n")
+    }
+
+    fn siwe_format(allowed_chain_ids: Option<Vec<u64>>, uri_prefix: Option<&str>) -> MessageFormat {
+        MessageFormat::Siwe(SiweConstraints {
+            domain: "service.invalid".to_string(),
+            allowed_chain_ids,
+            uri_prefix: uri_prefix.map(str::to_string),
+        })
+    }
+
+    fn user() -> AccountId {
+        ADDRESS.to_lowercase().parse().unwrap()
+    }
+
+    fn replace_line(message: &str, from: &str, to: &str) -> String {
+        message
+            .split('\n')
+            .map(|line| if line == from { to } else { line })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn spec_example_is_parsed() {
+        let siwe = SiweMessage::parse(&spec_message()).unwrap();
+        assert_eq!(siwe.domain, "service.invalid");
+        assert_eq!(siwe.address, ADDRESS);
+        assert_eq!(
+            siwe.statement.as_deref(),
+            Some("I accept the ServiceOrg Terms of Service: https://service.invalid/tos")
+        );
+        assert_eq!(siwe.uri, "https://service.invalid/login");
+        assert_eq!(siwe.chain_id, 1);
+        assert_eq!(siwe.nonce, "32891756");
+        assert_eq!(siwe.issued_at, "2021-09-30T16:25:24Z");
+        assert_eq!(siwe.resources.len(), 2);
+    }
+
+    #[test]
+    fn optional_fields_are_parsed_in_template_order() {
+        let message = [
+            "service.invalid wants you to sign in with your Ethereum account:",
+            ADDRESS,
+            "",
+            "URI: https://service.invalid/login",
+            "Version: 1",
+            "Chain ID: 10",
+            "Nonce: 32891756",
+            "Issued At: 2021-09-30T16:25:24Z",
+            "Expiration Time: 2021-10-30T16:25:24Z",
+            "Not Before: 2021-09-30T16:25:24Z",
+            "Request ID: 42",
+        ]
+        .join("\n");
+
+        let siwe = SiweMessage::parse(&message).unwrap();
+        assert_eq!(siwe.statement, None);
+        assert_eq!(siwe.chain_id, 10);
+        assert_eq!(
+            siwe.expiration_time.as_deref(),
+            Some("2021-10-30T16:25:24Z")
+        );
+        assert_eq!(siwe.not_before.as_deref(), Some("2021-09-30T16:25:24Z"));
+        assert_eq!(siwe.request_id.as_deref(), Some("42"));
+        assert!(siwe.resources.is_empty());
+
+        // Out of template order, a field is trailing content
+        let swapped = replace_line(
+            &message,
+            "Request ID: 42",
+            "Expiration Time: 2021-10-30T16:25:24Z",
+        );
+        assert!(SiweMessage::parse(&swapped).is_err());
+    }
+
+    #[test]
+    fn malformed_messages_are_rejected() {
+        let message = spec_message();
+        for broken in [
+            replace_line(&message, "Nonce: 32891756", ""),
+            replace_line(&message, "Chain ID: 1", "Chain ID: one"),
+            replace_line(&message, ADDRESS, "0xC02aaA39"),
+            replace_line(
+                &message,
+                "service.invalid wants you to sign in with your Ethereum account:",
+                "service.invalid wants you to sign in:",
+            ),
+            // Missing blank line after the address
+            message.replacen(&format!("{}\n\n", ADDRESS), &format!("{}\n", ADDRESS), 1),
+            format!("{}\nExtra line", message),
+            format!("{}\n", message),
+            "".to_string(),
+        ] {
+            assert!(
+                SiweMessage::parse(&broken).is_err(),
+                "accepted {:?}",
+                broken
+            );
+        }
+    }
+
+    #[test]
+    fn siwe_messages_must_match_the_app_and_user() {
+        let message = spec_message();
+        let format = siwe_format(Some(vec![1, 10]), Some("https://service.invalid/"));
+        assert!(format.is_message_allowed(&message, &user()));
+
+        // Another user's address
+        let other_user: AccountId = "0x0000000000000000000000000000000000000001"
+            .parse()
+            .unwrap();
+        assert!(!format.is_message_allowed(&message, &other_user));
+
+        // Another domain
+        let phishing = message.replacen("service.invalid wants", "evil.invalid wants", 1);
+        assert!(!format.is_message_allowed(&phishing, &user()));
+
+        // Chain and URI outside the app's constraints
+        assert!(!siwe_format(Some(vec![10]), None).is_message_allowed(&message, &user()));
+        assert!(!siwe_format(None, Some("https://other.invalid/"))
+            .is_message_allowed(&message, &user()));
+        assert!(siwe_format(None, None).is_message_allowed(&message, &user()));
+
+        // Unsupported version
+        let version_2 = replace_line(&message, "Version: 1", "Version: 2");
+        assert!(!format.is_message_allowed(&version_2, &user()));
+
+        // Plain text isn't a SIWE message
+        assert!(!format.is_message_allowed("Sign in to service.invalid", &user()));
+    }
+
+    #[test]
+    fn prefixed_messages_are_matched_by_prefix() {
+        let format = MessageFormat::Prefixed(vec!["Welcome to ".to_string()]);
+        assert!(format.is_message_allowed("Welcome to the app", &user()));
+        assert!(!format.is_message_allowed("Transfer everything", &user()));
+        assert!(MessageFormat::Any.is_message_allowed("Transfer everything", &user()));
+    }
+}
@@ -0,0 +1,96 @@
+// trial_user/perform_actions/eth_sign/typed_data.rs
+use crate::*;
+use near_sdk::PromiseError;
+use perform_actions::eth_sign::eip712::TypedData;
+
+/// Payload a session key signs to request an EIP-712 signature from the user's EVM address.
+#[derive(Clone)]
+#[near(serializers = [json])]
+pub struct TypedDataPayload {
+    /// EIP-712 typed data JSON, exactly as passed to `eth_signTypedData_v4`
+    pub typed_data: String,
+    /// Above every nonce the session key has signed a message request with, so the
+    /// request can't be replayed
+    pub nonce: U64,
+}
+
+#[near]
+impl Contract {
+    /// Signs EIP-712 typed data with the user's MPC key. Resolves to the hex-encoded
+    /// 65-byte `r || s || v` signature.
+    pub fn sign_typed_data(
+        &mut self,
+        signature: SessionSignature,
+        payload: TypedDataPayload,
+        session_key: SessionKey,
+        app_id: AppID,
+    ) -> Promise {
+        self.assert_valid_signature(&payload, &signature, &session_key, &app_id);
+        let (trial_data, key_usage) = self.assert_trial_active(&session_key, &app_id);
+        self.internal_use_message_nonce(&session_key, payload.nonce.0);
+
+        let bundle: Bundle = self
+            .internal_get_bundle(&key_usage.path)
+            .or_fail(FastAuthError::UserNotFound);
+
+        let typed_data: TypedData = near_sdk::serde_json::from_str(&payload.typed_data)
+            .unwrap_or_else(|e| FastAuthError::InvalidTypedData(e.to_string()).panic());
+
+        // Check the domain and primary type against the app's allowlist. Domain fields are
+        // read from the hashed domain only, and a rule's field the domain lacks fails the check
+        let constraints = trial_data
+            .typed_data_constraints
+            .or_fail(FastAuthError::TypedDataNotEnabled);
+
+        let chain_id = typed_data
+            .domain_chain_id()
+            .unwrap_or_else(|e| FastAuthError::InvalidTypedData(e).panic());
+        let verifying_contract = typed_data
+            .domain_verifying_contract()
+            .unwrap_or_else(|e| FastAuthError::InvalidTypedData(e).panic());
+        // A domain bound to a chain must be bound to a supported one
+        if let Some(chain_id) = chain_id {
+            self.assert_evm_chain_enabled(chain_id);
+        }
+        ensure(
+            constraints.is_domain_allowed(chain_id, verifying_contract.as_ref()),
+            FastAuthError::TypedDataDomainNotAllowed,
+        );
+        ensure(
+            constraints.is_primary_type_allowed(&typed_data.primary_type),
+            FastAuthError::TypedDataPrimaryTypeNotAllowed,
+        );
+
+        let hashed_payload = typed_data
+            .signing_hash()
+            .unwrap_or_else(|e| FastAuthError::InvalidTypedData(e).panic());
+
+        // Call the MPC contract to get a signature
+        self.request_mpc_signature(hashed_payload, &bundle.path)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(Gas::from_tgas(10))
+                    .on_sign_eth_message(hashed_payload, bundle.eth_address),
+            )
+    }
+
+    /// Callback checking the MPC signature against the user's address and
+    /// returning it as hex-encoded `r || s || v`
+    #[private]
+    pub fn on_sign_eth_message(
+        &self,
+        #[callback_result] call_result: Result<SignResult, PromiseError>,
+        hashed_payload: [u8; 32],
+        eth_address: AccountId,
+    ) -> String {
+        match call_result {
+            Ok(signature) => format!(
+                "0x{}",
+                hex::encode(signature.to_eth_signature(&hashed_payload, &eth_address))
+            ),
+            Err(_e) => {
+                FastAuthError::MpcSignatureFailed.panic();
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+// trial_user/perform_actions/evm/abi.rs
+use crate::*;
+use ethabi::param_type::Reader;
+use ethabi::{Function, Param, StateMutability};
+
+/// Parses a function from either a JSON ABI fragment, e.g.
+/// `{"type":"function","name":"transfer","inputs":[...],"outputs":[...],"stateMutability":"nonpayable"}`,
+/// or a canonical signature such as `transfer(address,uint256)`.
+pub fn parse_function(abi: &str) -> Function {
+    let abi = abi.trim();
+    if abi.starts_with('{') {
+        return near_sdk::serde_json::from_str(abi).unwrap_or_else(|e| {
+            FastAuthError::InvalidPayload(format!("Invalid ABI fragment: {}", e)).panic()
+        });
+    }
+
+    let invalid_signature = || FastAuthError::InvalidPayload("Invalid function signature".into());
+    let open = abi.find('(').unwrap_or_else(|| invalid_signature().panic());
+    ensure(abi.ends_with(')'), invalid_signature());
+    let name = abi[..open].trim();
+    ensure(!name.is_empty(), invalid_signature());
+
+    let inputs = split_top_level(&abi[open + 1..abi.len() - 1])
+        .into_iter()
+        .enumerate()
+        .map(|(index, kind)| Param {
+            name: format!("arg{}", index),
+            kind: Reader::read(&kind).unwrap_or_else(|_| {
+                FastAuthError::InvalidPayload(format!(
+                    "Invalid parameter type {} in signature",
+                    kind
+                ))
+                .panic()
+            }),
+            internal_type: None,
+        })
+        .collect();
+
+    #[allow(deprecated)]
+    Function {
+        name: name.to_string(),
+        inputs,
+        outputs: vec![],
+        constant: None,
+        state_mutability: StateMutability::NonPayable,
+    }
+}
+
+/// Splits a comma-separated type list on commas outside of tuples.
+fn split_top_level(types: &str) -> Vec<String> {
+    let types: String = types.chars().filter(|c| !c.is_whitespace()).collect();
+    if types.is_empty() {
+        return vec![];
+    }
+
+    let mut parts = vec![];
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (index, c) in types.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(types[start..index].to_string());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(types[start..].to_string());
+    parts
+}
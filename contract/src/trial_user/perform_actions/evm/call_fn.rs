@@ -1,143 +1,307 @@
-use crate::perform_actions::serialization::{SerializableParam, SerializableToken};
+use crate::perform_actions::evm::abi::parse_function;
+use crate::perform_actions::evm::serialization::{
+    tokenize_args, SerializableParam, SerializableToken,
+};
+use crate::perform_actions::evm::token::EvmTokenAction;
+use crate::perform_actions::evm::transaction::{
+    AccessListTransaction, EvmTransaction, EvmTxType, LegacyTransaction,
+};
 use crate::*;
-use env::keccak256;
 use ethabi::{Function, Param, StateMutability, Token};
+use ethereum_types::U256;
 use hex::FromHex;
 use near_sdk::json_types::U128;
 use near_sdk::PromiseError;
-use omni_transaction::evm::evm_transaction::EVMTransaction;
 use omni_transaction::evm::evm_transaction_builder::EVMTransactionBuilder;
-use omni_transaction::evm::types::{AccessList, Address, Signature};
+use omni_transaction::evm::types::{AccessList, Address};
 use omni_transaction::transaction_builder::TxBuilder;
 
+/// How the called function is given: a JSON ABI fragment or canonical signature, or a
+/// method name with its parameter types.
+#[derive(Clone)]
+#[near(serializers = [json])]
+#[serde(tag = "type")]
+pub enum EvmFunction {
+    Abi {
+        abi: String,
+    },
+    Method {
+        method_name: String,
+        #[serde(default)]
+        method_params: Vec<SerializableParam>,
+    },
+}
+
+impl EvmFunction {
+    pub fn to_function(self) -> Function {
+        match self {
+            EvmFunction::Abi { abi } => parse_function(&abi),
+            EvmFunction::Method {
+                method_name,
+                method_params,
+            } =>
+            {
+                #[allow(deprecated)]
+                Function {
+                    name: method_name,
+                    inputs: method_params.into_iter().map(Param::from).collect(),
+                    outputs: vec![],
+                    constant: Some(false),
+                    state_mutability: StateMutability::NonPayable,
+                }
+            }
+        }
+    }
+}
+
+/// Nonce, gas and fee arguments shared by every EVM sign request. `tx_type` defaults to
+/// EIP-1559.
+#[derive(Clone)]
+#[near(serializers = [json])]
+pub struct EvmTxParams {
+    pub gas_limit: U128,
+    pub nonce: U64,
+    #[serde(default)]
+    pub tx_type: EvmTxType,
+    /// Required for Legacy and AccessList
+    pub gas_price: Option<U128>,
+    /// Required for Eip1559
+    pub max_fee_per_gas: Option<U128>,
+    /// Required for Eip1559
+    pub max_priority_fee_per_gas: Option<U128>,
+    /// Used by AccessList and Eip1559
+    #[serde(default)]
+    pub access_list: AccessList,
+}
+
+impl EvmTxParams {
+    /// Builds a transaction of the requested type calling `to`.
+    pub fn build_transaction(
+        &self,
+        chain_id: u64,
+        to: Address,
+        value: u128,
+        input: Vec<u8>,
+    ) -> EvmTransaction {
+        match self.tx_type {
+            EvmTxType::Legacy => EvmTransaction::Legacy(LegacyTransaction {
+                chain_id,
+                nonce: self.nonce.0,
+                gas_price: required_fee(self.gas_price, "gas_price", "legacy"),
+                gas_limit: self.gas_limit.0,
+                to: Some(to),
+                value,
+                input,
+            }),
+            EvmTxType::AccessList => EvmTransaction::AccessList(AccessListTransaction {
+                chain_id,
+                nonce: self.nonce.0,
+                gas_price: required_fee(self.gas_price, "gas_price", "access list"),
+                gas_limit: self.gas_limit.0,
+                to: Some(to),
+                value,
+                input,
+                access_list: self.access_list.clone(),
+            }),
+            EvmTxType::Eip1559 => EvmTransaction::Eip1559(
+                EVMTransactionBuilder::new()
+                    .chain_id(chain_id)
+                    .nonce(self.nonce.0)
+                    .max_priority_fee_per_gas(required_fee(
+                        self.max_priority_fee_per_gas,
+                        "max_priority_fee_per_gas",
+                        "EIP-1559",
+                    ))
+                    .max_fee_per_gas(required_fee(
+                        self.max_fee_per_gas,
+                        "max_fee_per_gas",
+                        "EIP-1559",
+                    ))
+                    .gas_limit(self.gas_limit.0)
+                    .to(to)
+                    .value(value)
+                    .input(input)
+                    .access_list(self.access_list.clone())
+                    .build(),
+            ),
+        }
+    }
+}
+
+fn required_fee(fee: Option<U128>, name: &str, tx_type: &str) -> u128 {
+    fee.unwrap_or_else(|| {
+        FastAuthError::InvalidPayload(format!("{} is required for {} transactions", name, tx_type))
+            .panic()
+    })
+    .0
+}
+
 #[near]
 impl Contract {
+    /// Signs a contract call on an EVM chain with the user's MPC key. Resolves to the
+    /// hex-encoded signed transaction, ready to be broadcast.
     pub fn call_evm_contract(
         &mut self,
         chain_id: u64,
         contract_address: String,
-        method_name: String,
-        method_params: Vec<SerializableParam>,
+        function: EvmFunction,
         args: Vec<SerializableToken>,
-        gas_limit: U128,
         value: U128,
-        nonce: U64,
-        max_fee_per_gas: U128,
-        max_priority_fee_per_gas: U128,
-        access_list: AccessList,
+        tx: EvmTxParams,
     ) -> Promise {
-        // Parse the contract address
-        let addr_str = contract_address.trim_start_matches("0x");
-        let addr_bytes: [u8; 20] =
-            <[u8; 20]>::from_hex(addr_str).expect("Invalid Ethereum address in allowed_contracts");
-        let contract_address = Address::from(addr_bytes);
+        let contract_address = parse_contract_address(&contract_address);
+        let function = function.to_function();
 
         let action = Action::EVM(EvmAction {
             chain_id,
-            method_name: method_name.clone(),
+            method_name: function.name.clone(),
+            selector: function.short_signature(),
             contract_address,
-            gas_limit: gas_limit.0,
+            gas_limit: tx.gas_limit.0,
             value,
         });
 
-        let (trial_data, key_usage) = self.assert_action_allowed(&action);
+        let (trial_data, key_usage) = self.assert_signer_action_allowed(&action);
+
+        // Convert SerializableToken to ethabi::Token, checked against the declared inputs
+        let ethabi_args: Vec<Token> = tokenize_args(&function.inputs, args)
+            .unwrap_or_else(|e| FastAuthError::InvalidArgument(e.to_string()).panic());
 
+        // Encode the function call data
+        let input_data = function
+            .encode_input(&ethabi_args)
+            .or_fail(FastAuthError::EncodingFailed);
+
+        let evm_transaction = tx.build_transaction(chain_id, contract_address, value.0, input_data);
+
+        self.internal_sign_evm_transaction(evm_transaction, &trial_data, &key_usage)
+    }
+
+    /// Signs an ERC-20, ERC-721 or ERC-1155 transfer or approval encoded by the contract.
+    /// The token must be in the app's `allowed_tokens` for the chain and the amount within
+    /// its cap. Resolves to the hex-encoded signed transaction.
+    pub fn call_evm_token_action(
+        &mut self,
+        chain_id: u64,
+        token_address: String,
+        token_action: EvmTokenAction,
+        tx: EvmTxParams,
+    ) -> Promise {
+        let token_address = parse_contract_address(&token_address);
+
+        let action = Action::EVM(EvmAction {
+            chain_id,
+            method_name: token_action.method_name().to_string(),
+            selector: token_action.selector(),
+            contract_address: token_address,
+            gas_limit: tx.gas_limit.0,
+            value: U128(0),
+        });
+
+        let (trial_data, key_usage) = self.assert_signer_action_allowed(&action);
+
+        // Check the token and amounts against the app's token rules
+        let rule = trial_data
+            .get_token_rule(&token_address, &ChainId(chain_id.to_string()))
+            .or_fail(FastAuthError::TokenNotAllowed);
+        ensure(
+            rule.standard == token_action.standard(),
+            FastAuthError::TokenStandardMismatch,
+        );
+
+        if let Some(amount) = token_action.amount() {
+            if token_action.is_approval() {
+                ensure(amount != U256::MAX, FastAuthError::UnlimitedApproval);
+                ensure(
+                    rule.max_amount.is_some(),
+                    FastAuthError::ApprovalCapRequired,
+                );
+            }
+            ensure(
+                rule.is_amount_allowed(amount),
+                FastAuthError::TokenAmountExceedsCap,
+            );
+        }
+        if let Some(token_id) = token_action.token_id() {
+            ensure(
+                rule.is_token_id_allowed(token_id),
+                FastAuthError::TokenIdNotAllowed,
+            );
+        }
+
+        let evm_transaction =
+            tx.build_transaction(chain_id, token_address, 0, token_action.encode());
+
+        self.internal_sign_evm_transaction(evm_transaction, &trial_data, &key_usage)
+    }
+
+    /// Checks exit conditions and the chain registry, then requests the transaction's
+    /// signature, resolving to the signed transaction.
+    fn internal_sign_evm_transaction(
+        &self,
+        evm_transaction: EvmTransaction,
+        trial_data: &TrialData,
+        key_usage: &KeyUsage,
+    ) -> Promise {
         // Check exit conditions if any
         if let Some(exit_conditions) = &trial_data.exit_conditions {
             // Check transaction limit
             if let Some(transaction_limit) = exit_conditions.transaction_limit {
                 if key_usage.usage_stats.total_interactions > transaction_limit {
-                    env::panic_str("Transaction limit reached");
+                    FastAuthError::TransactionLimitReached.panic();
                 }
             }
             // Additional exit conditions can be checked here
         }
 
-        // Convert SerializableParamType to ethabi::ParamType
-        let ethabi_params: Vec<Param> = method_params.into_iter().map(|p| p.into()).collect();
-        // Convert SerializableToken to ethabi::Token
-        let ethabi_args: Vec<Token> = args.into_iter().map(|t| t.into()).collect();
-
-        // Build the function object
-        let function = Function {
-            name: method_name.clone(),
-            inputs: ethabi_params.clone(),
-            outputs: vec![], // Adjust if needed
-            constant: Some(false),
-            state_mutability: StateMutability::NonPayable,
-        };
+        self.assert_evm_transaction_allowed(&evm_transaction);
 
-        // Encode the function call data
-        let input_data = function
-            .encode_input(&ethabi_args)
-            .expect("Failed to encode input");
-
-        // Build the EVM transaction
-        let evm_transaction = EVMTransactionBuilder::new()
-            .chain_id(chain_id)
-            .nonce(nonce.0)
-            .max_priority_fee_per_gas(max_priority_fee_per_gas.0)
-            .max_fee_per_gas(max_fee_per_gas.0)
-            .gas_limit(gas_limit.0)
-            .to(contract_address)
-            .value(value.0)
-            .input(input_data.clone())
-            .access_list(access_list.clone())
-            .build();
-
-        let tx_bytes = evm_transaction.build_for_signing();
+        let bundle: Bundle = self
+            .internal_get_bundle(&key_usage.path)
+            .or_fail(FastAuthError::UserNotFound);
 
         // Compute the hash of the serialized transaction
-        let hashed_payload_vec = keccak256(&tx_bytes);
-        // Ensure the vector has the correct length
-        let hashed_payload: [u8; 32] = hashed_payload_vec
-            .try_into()
-            .expect("Hash output should be 32 bytes");
-
-        // Log the details
-        env::log_str(&format!(
-            "LOG_STR_CHAIN_ID: {:?}
-            LOG_STR_NONCE: {:?}
-            LOG_STR_MAX_PRIORITY_FEE_PER_GAS: {:?}
-            LOG_STR_MAX_FEE_PER_GAS: {:?}
-            LOG_STR_GAS_LIMIT: {:?}
-            LOG_STR_CONTRACT: {:?}
-            LOG_STR_VALUE: {:?}
-            LOG_STR_INPUT: {:?}
-            LOG_STR_ACCESS_LIST: {:?}
-            LOG_STR_FUNCTION: {:?}
-            LOG_STR_ABI_PARAMS: {:?}
-            LOG_STR_ABI_ARGS: {:?}
-            LOG_STR_HASH: {:?},
-            LOG_STR_TXN_BYTES: {:?}",
-            chain_id,
-            nonce.0,
-            max_priority_fee_per_gas.0,
-            max_fee_per_gas.0,
-            gas_limit.0,
-            contract_address,
-            value.0,
-            input_data,
-            access_list,
-            function,
-            ethabi_params,
-            ethabi_args,
-            hashed_payload,
-            tx_bytes
-        ));
-
-        let request_payload =
-            create_sign_request_from_transaction(hashed_payload, &env::signer_account_pk());
+        let hashed_payload = evm_transaction.signing_hash();
 
         // Call the MPC contract to get a signature
-        Promise::new(self.mpc_contract.clone())
-            .function_call_weight(
-                "sign".to_string(),
-                near_sdk::serde_json::to_vec(&request_payload).unwrap(),
-                NearToken::from_near(1),
-                Gas::from_tgas(30),
-                GasWeight(1),
+        self.request_mpc_signature(hashed_payload, &bundle.path)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(Gas::from_tgas(10))
+                    .on_sign_evm_call(evm_transaction, bundle.eth_address),
             )
-            .as_return()
     }
+
+    /// Callback checking the signature against the user's address and
+    /// returning the signed transaction as a hex string
+    #[private]
+    pub fn on_sign_evm_call(
+        &self,
+        #[callback_result] call_result: Result<SignResult, PromiseError>,
+        evm_transaction: EvmTransaction,
+        eth_address: AccountId,
+    ) -> String {
+        match call_result {
+            Ok(signature) => {
+                let (r_bytes, s_bytes, recovery_id) = signature
+                    .verify_for_address(&evm_transaction.signing_hash(), &eth_address)
+                    .unwrap_or_else(|e| e.panic());
+                let signed_tx_bytes =
+                    evm_transaction.build_with_signature(&r_bytes, &s_bytes, recovery_id);
+
+                format!("0x{}", hex::encode(signed_tx_bytes))
+            }
+            Err(_e) => {
+                FastAuthError::MpcSignatureFailed.panic();
+            }
+        }
+    }
+}
+
+fn parse_contract_address(contract_address: &str) -> Address {
+    let addr_str = contract_address.trim_start_matches("0x");
+    let addr_bytes: [u8; 20] = <[u8; 20]>::from_hex(addr_str).or_fail(
+        FastAuthError::InvalidPayload("Invalid contract address".into()),
+    );
+    Address::from(addr_bytes)
 }
@@ -0,0 +1,94 @@
+// trial_user/perform_actions/evm/integer.rs
+use ethereum_types::U256;
+
+/// Why a string couldn't be read as a `uintN` / `intN` value
+#[derive(Debug, PartialEq)]
+pub enum IntegerError {
+    /// N isn't a multiple of 8 between 8 and 256
+    UnsupportedWidth,
+    Invalid,
+    OutOfRange,
+}
+
+/// Whether `uintN` / `intN` is a valid Solidity type for N = `bits`.
+pub fn is_valid_integer_width(bits: usize) -> bool {
+    (8..=256).contains(&bits) && bits.is_multiple_of(8)
+}
+
+/// Parses a decimal or `0x` hex string, with an optional leading `-`, into its
+/// magnitude and whether it is negative.
+fn parse_magnitude(s: &str) -> Result<(U256, bool), IntegerError> {
+    let s = s.trim();
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, s),
+    };
+    let magnitude = match digits.strip_prefix("0x") {
+        Some(hex_digits) => U256::from_str_radix(hex_digits, 16).ok(),
+        None => U256::from_dec_str(digits).ok(),
+    };
+    magnitude
+        .map(|magnitude| (magnitude, negative))
+        .ok_or(IntegerError::Invalid)
+}
+
+/// Parses a `uintN` value, where N is `bits`.
+pub fn parse_uint(s: &str, bits: usize) -> Result<U256, IntegerError> {
+    if !is_valid_integer_width(bits) {
+        return Err(IntegerError::UnsupportedWidth);
+    }
+    let (magnitude, negative) = parse_magnitude(s)?;
+    if negative || magnitude.bits() > bits {
+        return Err(IntegerError::OutOfRange);
+    }
+    Ok(magnitude)
+}
+
+/// Parses an `intN` value, where N is `bits`, into its 256-bit two's complement
+/// representation.
+pub fn parse_int(s: &str, bits: usize) -> Result<U256, IntegerError> {
+    if !is_valid_integer_width(bits) {
+        return Err(IntegerError::UnsupportedWidth);
+    }
+    let (magnitude, negative) = parse_magnitude(s)?;
+    let limit = U256::one() << (bits - 1);
+    let in_range = if negative {
+        magnitude <= limit
+    } else {
+        magnitude < limit
+    };
+    if !in_range {
+        return Err(IntegerError::OutOfRange);
+    }
+
+    if negative {
+        Ok((!magnitude).overflowing_add(U256::one()).0)
+    } else {
+        Ok(magnitude)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn widths_must_be_whole_bytes_up_to_256_bits() {
+        assert_eq!(parse_int("0", 0), Err(IntegerError::UnsupportedWidth));
+        assert_eq!(parse_uint("0", 0), Err(IntegerError::UnsupportedWidth));
+        assert_eq!(parse_uint("0", 12), Err(IntegerError::UnsupportedWidth));
+        assert_eq!(parse_uint("0", 264), Err(IntegerError::UnsupportedWidth));
+        assert_eq!(parse_int("0", 8), Ok(U256::zero()));
+        assert_eq!(parse_uint("0", 256), Ok(U256::zero()));
+    }
+
+    #[test]
+    fn int256_bounds() {
+        let min = format!("-{}", U256::one() << 255);
+        assert_eq!(parse_int(&min, 256), Ok(U256::one() << 255));
+        assert_eq!(
+            parse_int(&(U256::one() << 255).to_string(), 256),
+            Err(IntegerError::OutOfRange)
+        );
+    }
+}
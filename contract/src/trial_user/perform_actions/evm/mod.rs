@@ -1,4 +1,9 @@
+pub mod abi;
 pub mod call_fn;
+pub mod integer;
+pub mod rlp;
 pub mod serialization;
+pub mod token;
+pub mod transaction;
 
 pub use serialization::*;
@@ -0,0 +1,106 @@
+// trial_user/perform_actions/evm/rlp.rs
+//! Minimal RLP encoder for the transaction types omni-transaction does not build.
+
+/// Encodes a byte string.
+pub fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    if bytes.len() == 1 && bytes[0] < 0x80 {
+        return bytes.to_vec();
+    }
+    let mut encoded = encode_length(bytes.len(), 0x80);
+    encoded.extend_from_slice(bytes);
+    encoded
+}
+
+/// Encodes an unsigned integer as its minimal big-endian byte string.
+pub fn encode_uint(value: u128) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    encode_bytes(trim_leading_zeros(&bytes))
+}
+
+/// Encodes a big-endian integer such as a signature's `r` or `s`.
+pub fn encode_uint_bytes(bytes: &[u8]) -> Vec<u8> {
+    encode_bytes(trim_leading_zeros(bytes))
+}
+
+/// Encodes a list from its already-encoded items.
+pub fn encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload: Vec<u8> = items.concat();
+    let mut encoded = encode_length(payload.len(), 0xc0);
+    encoded.extend(payload);
+    encoded
+}
+
+fn encode_length(length: usize, offset: u8) -> Vec<u8> {
+    if length < 56 {
+        return vec![offset + length as u8];
+    }
+    let length_bytes = (length as u64).to_be_bytes();
+    let length_bytes = trim_leading_zeros(&length_bytes);
+    let mut encoded = vec![offset + 55 + length_bytes.len() as u8];
+    encoded.extend_from_slice(length_bytes);
+    encoded
+}
+
+fn trim_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes
+        .iter()
+        .position(|byte| *byte != 0)
+        .unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_bytes_below_0x80_encode_as_themselves() {
+        assert_eq!(encode_bytes(&[0x00]), vec![0x00]);
+        assert_eq!(encode_bytes(&[0x7f]), vec![0x7f]);
+        assert_eq!(encode_bytes(&[0x80]), vec![0x81, 0x80]);
+    }
+
+    #[test]
+    fn empty_values_encode_as_empty_strings_and_lists() {
+        assert_eq!(encode_bytes(&[]), vec![0x80]);
+        assert_eq!(encode_uint(0), vec![0x80]);
+        assert_eq!(encode_list(&[]), vec![0xc0]);
+    }
+
+    #[test]
+    fn integers_drop_leading_zeros() {
+        assert_eq!(encode_uint(0x0f), vec![0x0f]);
+        assert_eq!(encode_uint(0x0400), vec![0x82, 0x04, 0x00]);
+        assert_eq!(
+            encode_uint_bytes(&[0, 0, 0x04, 0x00]),
+            vec![0x82, 0x04, 0x00]
+        );
+        assert_eq!(encode_uint_bytes(&[0; 32]), vec![0x80]);
+    }
+
+    #[test]
+    fn lengths_from_56_bytes_take_a_length_prefix() {
+        let mut expected = vec![0xb7];
+        expected.extend([0xaa; 55]);
+        assert_eq!(encode_bytes(&[0xaa; 55]), expected);
+
+        let mut expected = vec![0xb8, 56];
+        expected.extend([0xaa; 56]);
+        assert_eq!(encode_bytes(&[0xaa; 56]), expected);
+
+        let mut expected = vec![0xb9, 0x04, 0x00];
+        expected.extend([0xaa; 1024]);
+        assert_eq!(encode_bytes(&[0xaa; 1024]), expected);
+    }
+
+    #[test]
+    fn list_lengths_from_56_bytes_take_a_length_prefix() {
+        let short = encode_list(&[vec![0x01; 55]]);
+        assert_eq!(short[0], 0xf7);
+        assert_eq!(short.len(), 56);
+
+        let long = encode_list(&[vec![0x01; 56]]);
+        assert_eq!(long[..2], [0xf8, 56]);
+        assert_eq!(long.len(), 58);
+    }
+}
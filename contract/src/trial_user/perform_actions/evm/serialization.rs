@@ -1,7 +1,8 @@
 use ethabi::{Address, Param, ParamType, Token};
 use ethereum_types::U256;
-use std::str::FromStr;
+use std::fmt;
 
+use crate::perform_actions::evm::integer::{self, IntegerError};
 use crate::*;
 
 #[derive(Clone)]
@@ -76,74 +77,505 @@ impl From<SerializableParamType> for ParamType {
     }
 }
 
-impl From<SerializableToken> for Token {
-    fn from(st: SerializableToken) -> Self {
-        match st {
-            // Handle Address parsing
-            SerializableToken::Address(s) => {
-                env::log_str(&format!("Incoming Address value: {}", s));
-                let s = s.trim_start_matches("0x");
-                let bytes = hex::decode(s).expect("Invalid Address value");
+/// Why a `SerializableToken` could not be converted, with the path of the failing argument
+/// such as `to`, `ids[2]` or `order.1`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TokenConversionError {
+    InvalidHex {
+        path: String,
+    },
+    InvalidAddressLength {
+        path: String,
+        length: usize,
+    },
+    InvalidInteger {
+        path: String,
+        value: String,
+    },
+    IntegerOutOfRange {
+        path: String,
+        kind: String,
+    },
+    UnsupportedType {
+        path: String,
+        kind: String,
+    },
+    FixedBytesLength {
+        path: String,
+        expected: usize,
+        found: usize,
+    },
+    ArrayLength {
+        path: String,
+        expected: usize,
+        found: usize,
+    },
+    TypeMismatch {
+        path: String,
+        expected: String,
+        found: String,
+    },
+    ArgumentCount {
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for TokenConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenConversionError::InvalidHex { path } => write!(f, "Invalid hex for {}", path),
+            TokenConversionError::InvalidAddressLength { path, length } => {
+                write!(
+                    f,
+                    "Address must be 20 bytes for {}. Found: {}",
+                    path, length
+                )
+            }
+            TokenConversionError::InvalidInteger { path, value } => {
+                write!(f, "Invalid integer {} for {}", value, path)
+            }
+            TokenConversionError::IntegerOutOfRange { path, kind } => {
+                write!(f, "Value out of range for {} at {}", kind, path)
+            }
+            TokenConversionError::UnsupportedType { path, kind } => {
+                write!(f, "Unsupported type {} for {}", kind, path)
+            }
+            TokenConversionError::FixedBytesLength {
+                path,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Expected {} bytes for {}. Found: {}",
+                expected, path, found
+            ),
+            TokenConversionError::ArrayLength {
+                path,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Expected {} items for {}. Found: {}",
+                expected, path, found
+            ),
+            TokenConversionError::TypeMismatch {
+                path,
+                expected,
+                found,
+            } => write!(f, "Expected {} for {}. Found: {}", expected, path, found),
+            TokenConversionError::ArgumentCount { expected, found } => {
+                write!(f, "Expected {} arguments. Found: {}", expected, found)
+            }
+        }
+    }
+}
+
+impl TryFrom<SerializableToken> for Token {
+    type Error = TokenConversionError;
+
+    /// Converts without a declared type; integers are taken as 256 bits wide.
+    fn try_from(st: SerializableToken) -> Result<Self, Self::Error> {
+        st.into_token(None, "value")
+    }
+}
+
+/// Converts call arguments, checking each against its declared parameter type.
+pub fn tokenize_args(
+    params: &[Param],
+    args: Vec<SerializableToken>,
+) -> Result<Vec<Token>, TokenConversionError> {
+    if params.len() != args.len() {
+        return Err(TokenConversionError::ArgumentCount {
+            expected: params.len(),
+            found: args.len(),
+        });
+    }
+
+    params
+        .iter()
+        .zip(args)
+        .enumerate()
+        .map(|(index, (param, arg))| {
+            let path = if param.name.is_empty() {
+                format!("arg{}", index)
+            } else {
+                param.name.clone()
+            };
+            arg.into_token(Some(&param.kind), &path)
+        })
+        .collect()
+}
+
+impl SerializableToken {
+    fn kind_name(&self) -> &'static str {
+        match self {
+            SerializableToken::Address(_) => "Address",
+            SerializableToken::FixedBytes(_) => "FixedBytes",
+            SerializableToken::Bytes(_) => "Bytes",
+            SerializableToken::Int(_) => "Int",
+            SerializableToken::Uint(_) => "Uint",
+            SerializableToken::Bool(_) => "Bool",
+            SerializableToken::String(_) => "String",
+            SerializableToken::FixedArray(_) => "FixedArray",
+            SerializableToken::Array(_) => "Array",
+            SerializableToken::Tuple(_) => "Tuple",
+        }
+    }
+
+    /// Converts into an ethabi token, checking agreement with `kind` when it is given.
+    fn into_token(
+        self,
+        kind: Option<&ParamType>,
+        path: &str,
+    ) -> Result<Token, TokenConversionError> {
+        let mismatch =
+            |expected: &ParamType, token: &SerializableToken| TokenConversionError::TypeMismatch {
+                path: path.to_string(),
+                expected: expected.to_string(),
+                found: token.kind_name().to_string(),
+            };
+
+        match (self, kind) {
+            (SerializableToken::Address(s), None | Some(ParamType::Address)) => {
+                let bytes = decode_hex(&s, path)?;
                 if bytes.len() != 20 {
-                    panic!("Address must be 20 bytes");
+                    return Err(TokenConversionError::InvalidAddressLength {
+                        path: path.to_string(),
+                        length: bytes.len(),
+                    });
+                }
+                Ok(Token::Address(Address::from_slice(&bytes)))
+            }
+            (SerializableToken::FixedBytes(s), None | Some(ParamType::FixedBytes(_))) => {
+                let bytes = decode_hex(&s, path)?;
+                // Without a declared size, anything from bytes1 to bytes32 is accepted
+                let (valid, expected) = match kind {
+                    Some(ParamType::FixedBytes(size)) => (bytes.len() == *size, *size),
+                    _ => ((1..=32).contains(&bytes.len()), 32),
+                };
+                if !valid {
+                    return Err(TokenConversionError::FixedBytesLength {
+                        path: path.to_string(),
+                        expected,
+                        found: bytes.len(),
+                    });
                 }
-                env::log_str(&format!("Parsed Address value: {:?}", bytes));
-                Token::Address(Address::from_slice(&bytes))
-            }
-            // Handle FixedBytes parsing
-            SerializableToken::FixedBytes(s) => {
-                env::log_str(&format!("Incoming FixedBytes value: {}", s));
-                let s = s.trim_start_matches("0x");
-                let bytes = hex::decode(s).expect("Invalid FixedBytes value");
-                env::log_str(&format!("Parsed FixedBytes value: {:?}", bytes));
-                Token::FixedBytes(bytes)
-            }
-            // Handle Bytes parsing
-            SerializableToken::Bytes(s) => {
-                env::log_str(&format!("Incoming Bytes value: {}", s));
-                let s = s.trim_start_matches("0x");
-                let bytes = hex::decode(s).expect("Invalid Bytes value");
-                env::log_str(&format!("Parsed Bytes value: {:?}", bytes));
-                Token::Bytes(bytes)
-            }
-            // Handle Int parsing
-            SerializableToken::Int(s) => {
-                env::log_str(&format!("Incoming Int value: {}", s));
-                let value = if s.starts_with("0x") {
-                    U256::from_str_radix(&s[2..], 16).expect("Invalid Int value")
-                } else {
-                    U256::from_str_radix(&s, 10).expect("Invalid Int value")
+                Ok(Token::FixedBytes(bytes))
+            }
+            (SerializableToken::Bytes(s), None | Some(ParamType::Bytes)) => {
+                Ok(Token::Bytes(decode_hex(&s, path)?))
+            }
+            (SerializableToken::Int(s), None | Some(ParamType::Int(_))) => {
+                let bits = match kind {
+                    Some(ParamType::Int(bits)) => *bits,
+                    _ => 256,
+                };
+                Ok(Token::Int(parse_integer(&s, bits, true, path)?))
+            }
+            (SerializableToken::Uint(s), None | Some(ParamType::Uint(_))) => {
+                let bits = match kind {
+                    Some(ParamType::Uint(bits)) => *bits,
+                    _ => 256,
                 };
-                // Convert U256 to signed integer (assuming 256-bit signed integer)
-                let int_value = ethabi::Int::from(value);
-                env::log_str(&format!("Parsed Int value: {:?}", int_value));
-                Token::Int(int_value)
-            }
-            // Handle Uint parsing
-            SerializableToken::Uint(s) => {
-                env::log_str(&format!("Incoming Uint value: {}", s));
-                let value = if s.starts_with("0x") {
-                    U256::from_str_radix(&s[2..], 16).expect("Invalid Uint value")
-                } else {
-                    U256::from_str_radix(&s, 10).expect("Invalid Uint value")
+                Ok(Token::Uint(parse_integer(&s, bits, false, path)?))
+            }
+            (SerializableToken::Bool(b), None | Some(ParamType::Bool)) => Ok(Token::Bool(b)),
+            (SerializableToken::String(s), None | Some(ParamType::String)) => Ok(Token::String(s)),
+            (SerializableToken::FixedArray(tokens), None | Some(ParamType::FixedArray(_, _))) => {
+                let inner = match kind {
+                    Some(ParamType::FixedArray(inner, size)) => {
+                        if tokens.len() != *size {
+                            return Err(TokenConversionError::ArrayLength {
+                                path: path.to_string(),
+                                expected: *size,
+                                found: tokens.len(),
+                            });
+                        }
+                        Some(inner.as_ref())
+                    }
+                    _ => None,
                 };
-                env::log_str(&format!("Parsed Uint value: {}", value));
-                Token::Uint(value)
+                Ok(Token::FixedArray(convert_items(tokens, inner, path)?))
             }
-            SerializableToken::Bool(b) => Token::Bool(b),
-            SerializableToken::String(s) => Token::String(s),
-            // Handle FixedArray recursively
-            SerializableToken::FixedArray(tokens) => {
-                Token::FixedArray(tokens.into_iter().map(|t| t.into()).collect())
+            (SerializableToken::Array(tokens), None | Some(ParamType::Array(_))) => {
+                let inner = match kind {
+                    Some(ParamType::Array(inner)) => Some(inner.as_ref()),
+                    _ => None,
+                };
+                Ok(Token::Array(convert_items(tokens, inner, path)?))
             }
-            // Handle Array recursively
-            SerializableToken::Array(tokens) => {
-                Token::Array(tokens.into_iter().map(|t| t.into()).collect())
+            (SerializableToken::Tuple(tokens), None) => {
+                let converted = tokens
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, token)| token.into_token(None, &format!("{}.{}", path, index)))
+                    .collect::<Result<_, _>>()?;
+                Ok(Token::Tuple(converted))
             }
-            // Handle Tuple recursively
-            SerializableToken::Tuple(tokens) => {
-                Token::Tuple(tokens.into_iter().map(|t| t.into()).collect())
+            (SerializableToken::Tuple(tokens), Some(ParamType::Tuple(kinds))) => {
+                if tokens.len() != kinds.len() {
+                    return Err(TokenConversionError::ArrayLength {
+                        path: path.to_string(),
+                        expected: kinds.len(),
+                        found: tokens.len(),
+                    });
+                }
+                let converted = tokens
+                    .into_iter()
+                    .zip(kinds)
+                    .enumerate()
+                    .map(|(index, (token, kind))| {
+                        token.into_token(Some(kind), &format!("{}.{}", path, index))
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(Token::Tuple(converted))
             }
+            // Every variant converts when no type is declared
+            (token, Some(expected)) => Err(mismatch(expected, &token)),
         }
     }
 }
+
+fn convert_items(
+    tokens: Vec<SerializableToken>,
+    kind: Option<&ParamType>,
+    path: &str,
+) -> Result<Vec<Token>, TokenConversionError> {
+    tokens
+        .into_iter()
+        .enumerate()
+        .map(|(index, token)| token.into_token(kind, &format!("{}[{}]", path, index)))
+        .collect()
+}
+
+fn decode_hex(s: &str, path: &str) -> Result<Vec<u8>, TokenConversionError> {
+    hex::decode(s.trim_start_matches("0x")).map_err(|_| TokenConversionError::InvalidHex {
+        path: path.to_string(),
+    })
+}
+
+/// Parses a `uintN` (`signed == false`) or `intN` value, rejecting widths Solidity
+/// doesn't have before looking at the value.
+fn parse_integer(
+    s: &str,
+    bits: usize,
+    signed: bool,
+    path: &str,
+) -> Result<U256, TokenConversionError> {
+    let kind = format!("{}{}", if signed { "int" } else { "uint" }, bits);
+    let parsed = if signed {
+        integer::parse_int(s, bits)
+    } else {
+        integer::parse_uint(s, bits)
+    };
+    parsed.map_err(|error| match error {
+        IntegerError::UnsupportedWidth => TokenConversionError::UnsupportedType {
+            path: path.to_string(),
+            kind,
+        },
+        IntegerError::Invalid => TokenConversionError::InvalidInteger {
+            path: path.to_string(),
+            value: s.to_string(),
+        },
+        IntegerError::OutOfRange => TokenConversionError::IntegerOutOfRange {
+            path: path.to_string(),
+            kind,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethabi::{Function, StateMutability};
+
+    fn param(name: &str, kind: ParamType) -> Param {
+        Param {
+            name: name.to_string(),
+            kind,
+            internal_type: None,
+        }
+    }
+
+    #[test]
+    fn transfer_args_golden_vector() {
+        let inputs = vec![
+            param("to", ParamType::Address),
+            param("amount", ParamType::Uint(256)),
+        ];
+        let tokens = tokenize_args(
+            &inputs,
+            vec![
+                SerializableToken::Address(
+                    "0x827c5d7bde400f1716255ae54bb8a55cb43d0324".to_string(),
+                ),
+                SerializableToken::Uint("1000".to_string()),
+            ],
+        )
+        .unwrap();
+
+        #[allow(deprecated)]
+        let function = Function {
+            name: "transfer".to_string(),
+            inputs,
+            outputs: vec![],
+            constant: None,
+            state_mutability: StateMutability::NonPayable,
+        };
+        assert_eq!(
+            hex::encode(function.encode_input(&tokens).unwrap()),
+            concat!(
+                "a9059cbb",
+                "000000000000000000000000827c5d7bde400f1716255ae54bb8a55cb43d0324",
+                "00000000000000000000000000000000000000000000000000000000000003e8",
+            )
+        );
+    }
+
+    #[test]
+    fn integers_accept_decimal_and_hex() {
+        assert_eq!(
+            Token::try_from(SerializableToken::Uint("0xff".to_string())),
+            Ok(Token::Uint(U256::from(255)))
+        );
+        assert_eq!(
+            Token::try_from(SerializableToken::Uint("255".to_string())),
+            Ok(Token::Uint(U256::from(255)))
+        );
+    }
+
+    #[test]
+    fn negative_integers_use_twos_complement() {
+        let tokens = tokenize_args(
+            &[param("delta", ParamType::Int(8))],
+            vec![SerializableToken::Int("-1".to_string())],
+        )
+        .unwrap();
+        assert_eq!(tokens, vec![Token::Int(U256::MAX)]);
+
+        let tokens = tokenize_args(
+            &[param("delta", ParamType::Int(8))],
+            vec![SerializableToken::Int("-128".to_string())],
+        )
+        .unwrap();
+        assert_eq!(tokens, vec![Token::Int(U256::MAX - U256::from(127))]);
+    }
+
+    #[test]
+    fn integers_are_range_checked_against_their_width() {
+        assert!(tokenize_args(
+            &[param("amount", ParamType::Uint(8))],
+            vec![SerializableToken::Uint("255".to_string())],
+        )
+        .is_ok());
+        assert_eq!(
+            tokenize_args(
+                &[param("amount", ParamType::Uint(8))],
+                vec![SerializableToken::Uint("256".to_string())],
+            ),
+            Err(TokenConversionError::IntegerOutOfRange {
+                path: "amount".to_string(),
+                kind: "uint8".to_string(),
+            })
+        );
+        assert_eq!(
+            tokenize_args(
+                &[param("delta", ParamType::Int(8))],
+                vec![SerializableToken::Int("128".to_string())],
+            ),
+            Err(TokenConversionError::IntegerOutOfRange {
+                path: "delta".to_string(),
+                kind: "int8".to_string(),
+            })
+        );
+        assert!(matches!(
+            Token::try_from(SerializableToken::Uint("-1".to_string())),
+            Err(TokenConversionError::IntegerOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn integer_widths_solidity_lacks_are_rejected() {
+        for kind in [ParamType::Int(0), ParamType::Uint(12), ParamType::Int(264)] {
+            let signed = matches!(kind, ParamType::Int(_));
+            let token = if signed {
+                SerializableToken::Int("0".to_string())
+            } else {
+                SerializableToken::Uint("0".to_string())
+            };
+            assert_eq!(
+                tokenize_args(&[param("value", kind.clone())], vec![token]),
+                Err(TokenConversionError::UnsupportedType {
+                    path: "value".to_string(),
+                    kind: kind.to_string(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn errors_carry_the_argument_path() {
+        let kind = ParamType::Tuple(vec![
+            ParamType::Address,
+            ParamType::Array(Box::new(ParamType::Uint(256))),
+        ]);
+        let arg = SerializableToken::Tuple(vec![
+            SerializableToken::Address("0x827c5d7bde400f1716255ae54bb8a55cb43d0324".to_string()),
+            SerializableToken::Array(vec![
+                SerializableToken::Uint("1".to_string()),
+                SerializableToken::Bool(true),
+            ]),
+        ]);
+        assert_eq!(
+            tokenize_args(&[param("order", kind)], vec![arg]),
+            Err(TokenConversionError::TypeMismatch {
+                path: "order.1[1]".to_string(),
+                expected: "uint256".to_string(),
+                found: "Bool".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn byte_lengths_are_checked() {
+        assert_eq!(
+            Token::try_from(SerializableToken::Address("0x1234".to_string())),
+            Err(TokenConversionError::InvalidAddressLength {
+                path: "value".to_string(),
+                length: 2,
+            })
+        );
+        assert_eq!(
+            tokenize_args(
+                &[param("salt", ParamType::FixedBytes(4))],
+                vec![SerializableToken::FixedBytes("0x010203".to_string())],
+            ),
+            Err(TokenConversionError::FixedBytesLength {
+                path: "salt".to_string(),
+                expected: 4,
+                found: 3,
+            })
+        );
+        assert_eq!(
+            tokenize_args(
+                &[param("", ParamType::Bytes)],
+                vec![SerializableToken::Bytes("zz".to_string())],
+            ),
+            Err(TokenConversionError::InvalidHex {
+                path: "arg0".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn argument_count_must_match() {
+        assert_eq!(
+            tokenize_args(&[param("to", ParamType::Address)], vec![]),
+            Err(TokenConversionError::ArgumentCount {
+                expected: 1,
+                found: 0,
+            })
+        );
+    }
+}
@@ -0,0 +1,201 @@
+// trial_user/perform_actions/evm/token.rs
+use crate::perform_actions::evm::integer;
+use crate::*;
+use ethabi::{Address, Function, Param, ParamType, StateMutability, Token};
+use ethereum_types::U256;
+
+/// Token transfer or approval encoded by the contract instead of from a raw ABI.
+/// Addresses are hex strings; amounts and token ids are decimal or `0x` hex strings.
+#[derive(Clone)]
+#[near(serializers = [json])]
+#[serde(tag = "type")]
+pub enum EvmTokenAction {
+    Erc20Transfer {
+        to: String,
+        amount: String,
+    },
+    Erc20Approve {
+        spender: String,
+        amount: String,
+    },
+    Erc721TransferFrom {
+        from: String,
+        to: String,
+        token_id: String,
+    },
+    Erc1155SafeTransferFrom {
+        from: String,
+        to: String,
+        id: String,
+        amount: String,
+        /// Hex-encoded data forwarded to the receiver hook
+        #[serde(default)]
+        data: String,
+    },
+}
+
+impl EvmTokenAction {
+    pub fn standard(&self) -> TokenStandard {
+        match self {
+            EvmTokenAction::Erc20Transfer { .. } | EvmTokenAction::Erc20Approve { .. } => {
+                TokenStandard::Erc20
+            }
+            EvmTokenAction::Erc721TransferFrom { .. } => TokenStandard::Erc721,
+            EvmTokenAction::Erc1155SafeTransferFrom { .. } => TokenStandard::Erc1155,
+        }
+    }
+
+    /// Name of the token contract method the action calls
+    pub fn method_name(&self) -> &'static str {
+        match self {
+            EvmTokenAction::Erc20Transfer { .. } => "transfer",
+            EvmTokenAction::Erc20Approve { .. } => "approve",
+            EvmTokenAction::Erc721TransferFrom { .. } => "transferFrom",
+            EvmTokenAction::Erc1155SafeTransferFrom { .. } => "safeTransferFrom",
+        }
+    }
+
+    /// Canonical signature of the token contract method the action calls
+    pub fn signature(&self) -> &'static str {
+        match self {
+            EvmTokenAction::Erc20Transfer { .. } => "transfer(address,uint256)",
+            EvmTokenAction::Erc20Approve { .. } => "approve(address,uint256)",
+            EvmTokenAction::Erc721TransferFrom { .. } => "transferFrom(address,address,uint256)",
+            EvmTokenAction::Erc1155SafeTransferFrom { .. } => {
+                "safeTransferFrom(address,address,uint256,uint256,bytes)"
+            }
+        }
+    }
+
+    pub fn selector(&self) -> [u8; 4] {
+        let hash = env::keccak256_array(self.signature().as_bytes());
+        [hash[0], hash[1], hash[2], hash[3]]
+    }
+
+    pub fn is_approval(&self) -> bool {
+        matches!(self, EvmTokenAction::Erc20Approve { .. })
+    }
+
+    /// Amount moved or approved, if the standard has one.
+    pub fn amount(&self) -> Option<U256> {
+        match self {
+            EvmTokenAction::Erc20Transfer { amount, .. }
+            | EvmTokenAction::Erc20Approve { amount, .. }
+            | EvmTokenAction::Erc1155SafeTransferFrom { amount, .. } => {
+                Some(parse_uint(amount, "amount"))
+            }
+            EvmTokenAction::Erc721TransferFrom { .. } => None,
+        }
+    }
+
+    /// Token id moved, if the standard has one.
+    pub fn token_id(&self) -> Option<U256> {
+        match self {
+            EvmTokenAction::Erc721TransferFrom { token_id, .. } => {
+                Some(parse_uint(token_id, "token_id"))
+            }
+            EvmTokenAction::Erc1155SafeTransferFrom { id, .. } => Some(parse_uint(id, "id")),
+            _ => None,
+        }
+    }
+
+    /// ABI-encoded call data
+    pub fn encode(&self) -> Vec<u8> {
+        let (inputs, tokens) = match self {
+            EvmTokenAction::Erc20Transfer { to, amount } => (
+                vec![
+                    param("to", ParamType::Address),
+                    param("amount", ParamType::Uint(256)),
+                ],
+                vec![
+                    Token::Address(parse_address(to, "to")),
+                    Token::Uint(parse_uint(amount, "amount")),
+                ],
+            ),
+            EvmTokenAction::Erc20Approve { spender, amount } => (
+                vec![
+                    param("spender", ParamType::Address),
+                    param("amount", ParamType::Uint(256)),
+                ],
+                vec![
+                    Token::Address(parse_address(spender, "spender")),
+                    Token::Uint(parse_uint(amount, "amount")),
+                ],
+            ),
+            EvmTokenAction::Erc721TransferFrom { from, to, token_id } => (
+                vec![
+                    param("from", ParamType::Address),
+                    param("to", ParamType::Address),
+                    param("tokenId", ParamType::Uint(256)),
+                ],
+                vec![
+                    Token::Address(parse_address(from, "from")),
+                    Token::Address(parse_address(to, "to")),
+                    Token::Uint(parse_uint(token_id, "token_id")),
+                ],
+            ),
+            EvmTokenAction::Erc1155SafeTransferFrom {
+                from,
+                to,
+                id,
+                amount,
+                data,
+            } => (
+                vec![
+                    param("from", ParamType::Address),
+                    param("to", ParamType::Address),
+                    param("id", ParamType::Uint(256)),
+                    param("amount", ParamType::Uint(256)),
+                    param("data", ParamType::Bytes),
+                ],
+                vec![
+                    Token::Address(parse_address(from, "from")),
+                    Token::Address(parse_address(to, "to")),
+                    Token::Uint(parse_uint(id, "id")),
+                    Token::Uint(parse_uint(amount, "amount")),
+                    Token::Bytes(hex::decode(data.trim_start_matches("0x")).or_fail(
+                        FastAuthError::InvalidArgument("Invalid hex for data".into()),
+                    )),
+                ],
+            ),
+        };
+
+        #[allow(deprecated)]
+        let function = Function {
+            name: self.method_name().to_string(),
+            inputs,
+            outputs: vec![],
+            constant: None,
+            state_mutability: StateMutability::NonPayable,
+        };
+
+        function
+            .encode_input(&tokens)
+            .or_fail(FastAuthError::EncodingFailed)
+    }
+}
+
+fn param(name: &str, kind: ParamType) -> Param {
+    Param {
+        name: name.to_string(),
+        kind,
+        internal_type: None,
+    }
+}
+
+fn parse_address(s: &str, field: &str) -> Address {
+    let bytes = hex::decode(s.trim_start_matches("0x")).unwrap_or_else(|_| {
+        FastAuthError::InvalidArgument(format!("Invalid hex for {}", field)).panic()
+    });
+    ensure(
+        bytes.len() == 20,
+        FastAuthError::InvalidArgument(format!("Address must be 20 bytes for {}", field)),
+    );
+    Address::from_slice(&bytes)
+}
+
+pub(crate) fn parse_uint(s: &str, field: &str) -> U256 {
+    integer::parse_uint(s, 256).unwrap_or_else(|_| {
+        FastAuthError::InvalidArgument(format!("Invalid integer for {}", field)).panic()
+    })
+}
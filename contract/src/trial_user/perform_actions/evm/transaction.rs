@@ -0,0 +1,298 @@
+// trial_user/perform_actions/evm/transaction.rs
+use crate::perform_actions::evm::rlp::{encode_bytes, encode_list, encode_uint, encode_uint_bytes};
+use crate::*;
+use omni_transaction::evm::evm_transaction::EVMTransaction;
+use omni_transaction::evm::types::{AccessList, Address, Signature as OmniSignature};
+
+/// EIP-2718 type byte of access list transactions
+const ACCESS_LIST_TX_TYPE: u8 = 0x01;
+
+/// Envelope an EVM transaction is built and signed with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[near(serializers = [json])]
+pub enum EvmTxType {
+    /// Type 0 with EIP-155 replay protection
+    Legacy,
+    /// Type 1 (EIP-2930)
+    AccessList,
+    /// Type 2 (EIP-1559)
+    #[default]
+    Eip1559,
+}
+
+/// Type 0 transaction signed with EIP-155 replay protection.
+#[derive(Clone)]
+#[near(serializers = [json])]
+pub struct LegacyTransaction {
+    pub chain_id: u64,
+    pub nonce: u64,
+    pub gas_price: u128,
+    pub gas_limit: u128,
+    pub to: Option<Address>,
+    pub value: u128,
+    pub input: Vec<u8>,
+}
+
+/// Type 1 (EIP-2930) transaction.
+#[derive(Clone)]
+#[near(serializers = [json])]
+pub struct AccessListTransaction {
+    pub chain_id: u64,
+    pub nonce: u64,
+    pub gas_price: u128,
+    pub gas_limit: u128,
+    pub to: Option<Address>,
+    pub value: u128,
+    pub input: Vec<u8>,
+    pub access_list: AccessList,
+}
+
+/// An unsigned EVM transaction of any supported type.
+#[near(serializers = [json])]
+pub enum EvmTransaction {
+    Legacy(LegacyTransaction),
+    AccessList(AccessListTransaction),
+    Eip1559(EVMTransaction),
+}
+
+impl EvmTransaction {
+    /// Bytes whose keccak256 hash the MPC signs.
+    pub fn build_for_signing(&self) -> Vec<u8> {
+        match self {
+            EvmTransaction::Legacy(tx) => {
+                let mut fields = tx.common_fields();
+                // EIP-155: chain_id, 0, 0 take the place of v, r, s
+                fields.push(encode_uint(tx.chain_id as u128));
+                fields.push(encode_uint(0));
+                fields.push(encode_uint(0));
+                encode_list(&fields)
+            }
+            EvmTransaction::AccessList(tx) => {
+                let mut encoded = vec![ACCESS_LIST_TX_TYPE];
+                encoded.extend(encode_list(&tx.fields()));
+                encoded
+            }
+            EvmTransaction::Eip1559(tx) => tx.build_for_signing(),
+        }
+    }
+
+    pub fn chain_id(&self) -> u64 {
+        match self {
+            EvmTransaction::Legacy(tx) => tx.chain_id,
+            EvmTransaction::AccessList(tx) => tx.chain_id,
+            EvmTransaction::Eip1559(tx) => tx.chain_id,
+        }
+    }
+
+    /// The most the sender may pay per unit of gas: `gas_price` before EIP-1559.
+    pub fn max_fee_per_gas(&self) -> u128 {
+        match self {
+            EvmTransaction::Legacy(tx) => tx.gas_price,
+            EvmTransaction::AccessList(tx) => tx.gas_price,
+            EvmTransaction::Eip1559(tx) => tx.max_fee_per_gas,
+        }
+    }
+
+    /// Only EIP-1559 transactions set the block producer's tip separately.
+    pub fn max_priority_fee_per_gas(&self) -> Option<u128> {
+        match self {
+            EvmTransaction::Eip1559(tx) => Some(tx.max_priority_fee_per_gas),
+            _ => None,
+        }
+    }
+
+    pub fn gas_limit(&self) -> u128 {
+        match self {
+            EvmTransaction::Legacy(tx) => tx.gas_limit,
+            EvmTransaction::AccessList(tx) => tx.gas_limit,
+            EvmTransaction::Eip1559(tx) => tx.gas_limit,
+        }
+    }
+
+    pub fn value(&self) -> u128 {
+        match self {
+            EvmTransaction::Legacy(tx) => tx.value,
+            EvmTransaction::AccessList(tx) => tx.value,
+            EvmTransaction::Eip1559(tx) => tx.value,
+        }
+    }
+
+    /// keccak256 of the signing payload.
+    pub fn signing_hash(&self) -> [u8; 32] {
+        env::keccak256_array(&self.build_for_signing())
+    }
+
+    /// Serializes the signed transaction, encoding `v` as each type expects.
+    pub fn build_with_signature(&self, r: &[u8], s: &[u8], recovery_id: u8) -> Vec<u8> {
+        match self {
+            EvmTransaction::Legacy(tx) => {
+                // EIP-155: v = recovery_id + 35 + 2 * chain_id
+                let v = recovery_id as u128 + 35 + 2 * tx.chain_id as u128;
+                let mut fields = tx.common_fields();
+                fields.push(encode_uint(v));
+                fields.push(encode_uint_bytes(r));
+                fields.push(encode_uint_bytes(s));
+                encode_list(&fields)
+            }
+            EvmTransaction::AccessList(tx) => {
+                let mut fields = tx.fields();
+                fields.push(encode_uint(recovery_id as u128));
+                fields.push(encode_uint_bytes(r));
+                fields.push(encode_uint_bytes(s));
+
+                let mut encoded = vec![ACCESS_LIST_TX_TYPE];
+                encoded.extend(encode_list(&fields));
+                encoded
+            }
+            EvmTransaction::Eip1559(tx) => tx.build_with_signature(&OmniSignature {
+                v: recovery_id as u64,
+                r: r.to_vec(),
+                s: s.to_vec(),
+            }),
+        }
+    }
+}
+
+impl LegacyTransaction {
+    fn common_fields(&self) -> Vec<Vec<u8>> {
+        vec![
+            encode_uint(self.nonce as u128),
+            encode_uint(self.gas_price),
+            encode_uint(self.gas_limit),
+            encode_to(&self.to),
+            encode_uint(self.value),
+            encode_bytes(&self.input),
+        ]
+    }
+}
+
+impl AccessListTransaction {
+    fn fields(&self) -> Vec<Vec<u8>> {
+        vec![
+            encode_uint(self.chain_id as u128),
+            encode_uint(self.nonce as u128),
+            encode_uint(self.gas_price),
+            encode_uint(self.gas_limit),
+            encode_to(&self.to),
+            encode_uint(self.value),
+            encode_bytes(&self.input),
+            encode_access_list(&self.access_list),
+        ]
+    }
+}
+
+/// Contract creations have an empty `to`.
+fn encode_to(to: &Option<Address>) -> Vec<u8> {
+    match to {
+        Some(address) => encode_bytes(address),
+        None => encode_bytes(&[]),
+    }
+}
+
+fn encode_access_list(access_list: &AccessList) -> Vec<u8> {
+    let entries: Vec<Vec<u8>> = access_list
+        .iter()
+        .map(|(address, storage_keys)| {
+            let keys: Vec<Vec<u8>> = storage_keys.iter().map(|key| encode_bytes(key)).collect();
+            encode_list(&[encode_bytes(address), encode_list(&keys)])
+        })
+        .collect();
+    encode_list(&entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The example transaction from the EIP-155 specification
+    fn eip155_example() -> EvmTransaction {
+        EvmTransaction::Legacy(LegacyTransaction {
+            chain_id: 1,
+            nonce: 9,
+            gas_price: 20_000_000_000,
+            gas_limit: 21_000,
+            to: Some([0x35; 20]),
+            value: 1_000_000_000_000_000_000,
+            input: vec![],
+        })
+    }
+
+    /// `emptyEip2718Tx` from go-ethereum's transaction tests
+    fn eip2930_example(access_list: AccessList) -> EvmTransaction {
+        EvmTransaction::AccessList(AccessListTransaction {
+            chain_id: 1,
+            nonce: 3,
+            gas_price: 1,
+            gas_limit: 25_000,
+            to: Some(
+                hex::decode("095e7baea6a6c7c4c2dfeb977efac326af552d87")
+                    .unwrap()
+                    .try_into()
+                    .unwrap(),
+            ),
+            value: 10,
+            input: vec![0x55, 0x44],
+            access_list,
+        })
+    }
+
+    #[test]
+    fn legacy_transactions_match_the_eip155_example() {
+        let tx = eip155_example();
+        assert_eq!(
+            hex::encode(tx.build_for_signing()),
+            "ec098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a764000080018080"
+        );
+        assert_eq!(
+            hex::encode(tx.signing_hash()),
+            "daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53"
+        );
+
+        let r = hex::decode("28ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276")
+            .unwrap();
+        let s = hex::decode("67cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83")
+            .unwrap();
+        assert_eq!(
+            hex::encode(tx.build_with_signature(&r, &s, 0)),
+            "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+        );
+    }
+
+    #[test]
+    fn access_list_transactions_match_go_ethereum() {
+        let tx = eip2930_example(vec![]);
+        assert_eq!(
+            hex::encode(tx.build_for_signing()),
+            "01e00103018261a894095e7baea6a6c7c4c2dfeb977efac326af552d870a825544c0"
+        );
+        assert_eq!(
+            hex::encode(tx.signing_hash()),
+            "e01fdf57762fc853d613f3d4914bb1f7aeeddf0b01daf553dd2917686b58ca0b"
+        );
+
+        let r = hex::decode("c9519f4f2b30335884581971573fadf60c6204f59a911df35ee8a540456b2660")
+            .unwrap();
+        let s = hex::decode("32f1e8e2c5dd761f9e4f88f41c8310aeaba26a8bfcdacfedfa12ec3862d37521")
+            .unwrap();
+        assert_eq!(
+            hex::encode(tx.build_with_signature(&r, &s, 1)),
+            "01f8630103018261a894095e7baea6a6c7c4c2dfeb977efac326af552d870a825544c001a0c9519f4f2b30335884581971573fadf60c6204f59a911df35ee8a540456b2660a032f1e8e2c5dd761f9e4f88f41c8310aeaba26a8bfcdacfedfa12ec3862d37521"
+        );
+    }
+
+    #[test]
+    fn access_list_entries_are_encoded() {
+        let address = hex::decode("095e7baea6a6c7c4c2dfeb977efac326af552d87").unwrap();
+        let mut storage_key = [0u8; 32];
+        storage_key[31] = 1;
+        let tx = eip2930_example(vec![(address.try_into().unwrap(), vec![storage_key])]);
+        assert_eq!(
+            hex::encode(tx.build_for_signing()),
+            "01f8590103018261a894095e7baea6a6c7c4c2dfeb977efac326af552d870a825544f838f794095e7baea6a6c7c4c2dfeb977efac326af552d87e1a00000000000000000000000000000000000000000000000000000000000000001"
+        );
+        assert_eq!(
+            hex::encode(tx.signing_hash()),
+            "75e8fa7a1fc50c3d0a79843458ead2b4f54dee6951da81d320d152a81719763a"
+        );
+    }
+}
@@ -1,2 +1,8 @@
 pub mod action_checker;
+pub mod bitcoin;
+pub mod eth_sign;
+pub mod evm;
 pub mod near;
+pub mod session_signature;
+pub mod solana;
+pub mod webauthn;
@@ -1,17 +1,12 @@
 use crate::*;
-use base64;
-use env::keccak256;
-use ethabi::{Address, Function, Param, ParamType, StateMutability, Token};
-use ethereum_types::U256;
-use hex;
-use near_sdk::json_types::Base64VecU8;
-use near_sdk::{CurveType, PromiseError};
-use omni_transaction::evm::evm_transaction::EVMTransaction;
-use omni_transaction::evm::evm_transaction_builder::EVMTransactionBuilder;
-use omni_transaction::evm::types::Signature as OmniSignature;
-use omni_transaction::transaction_builder::TxBuilder;
-use sha3::{Digest, Keccak256};
-use std::convert::TryInto;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use near_sdk::{PromiseError, PromiseOrValue};
+use perform_actions::evm::transaction::EvmTransaction;
+use perform_actions::near::encoding::{
+    account_id_to_eth_address, build_evm_transaction, convert_deposit, encode_function_call,
+    encode_transfer, near_gas_to_evm_gas,
+};
 
 #[derive(Clone)]
 #[near(serializers = [json])]
@@ -29,11 +24,13 @@ pub enum NearAction {
     },
 }
 
+/// Payload a session key signs to have a NEAR action executed through the user's wallet.
+/// `nonce` is the wallet's current nonce.
 #[derive(Clone)]
 #[near(serializers = [json])]
 pub struct NearPayload {
-    action: NearAction,
-    nonce: U64,
+    pub action: NearAction,
+    pub nonce: U64,
 }
 
 #[near]
@@ -41,24 +38,23 @@ impl Contract {
     #[payable]
     pub fn execute_near_action(
         &mut self,
-        signature: Base64VecU8,
+        signature: SessionSignature,
         payload: NearPayload,
-        session_key: PublicKey,
+        session_key: SessionKey,
         app_id: AppID,
     ) -> Promise {
         self.assert_valid_signature(&payload, &signature, &session_key, &app_id);
 
         // Retrieve user and bundler info
         let key_usage = self
-            .key_usage_by_pk
-            .get(&session_key)
-            .expect("Public key not recognized");
+            .internal_get_key_usage(&session_key)
+            .or_fail(FastAuthError::SessionKeyNotFound);
+
+        self.assert_not_paused(&app_id, &key_usage.path);
 
         let bundle: Bundle = self
-            .bundler
-            .get(&key_usage.path)
-            .expect("User not found")
-            .clone();
+            .internal_get_bundle(&key_usage.path)
+            .or_fail(FastAuthError::UserNotFound);
 
         let NearPayload { action, nonce } = payload;
 
@@ -80,14 +76,14 @@ impl Contract {
             } => {
                 // Debit for the attached deposit to the function call
                 if !deposit.is_zero() {
-                    self.debit(deposit, app_id);
+                    self.debit(deposit, app_id.clone());
                 }
 
                 // Compute value_in_wei and yocto_near
-                let (value_in_wei, yocto_near) = Self::convert_deposit(deposit);
+                let (value_in_wei, yocto_near) = convert_deposit(deposit);
 
                 // Encode input data
-                let input_data = Self::encode_function_call(
+                let input_data = encode_function_call(
                     &contract_id,
                     &method_name,
                     &args,
@@ -96,10 +92,10 @@ impl Contract {
                 );
 
                 // Compute contract address
-                let contract_address = Self::account_id_to_eth_address(&contract_id);
+                let contract_address = account_id_to_eth_address(&contract_id);
 
                 // Convert NEAR gas to EVM gas
-                let evm_gas_limit = Self::near_gas_to_evm_gas(gas.as_gas());
+                let evm_gas_limit = near_gas_to_evm_gas(gas.as_gas());
 
                 (
                     contract_address,
@@ -116,21 +112,21 @@ impl Contract {
             } => {
                 // Debit for the attached deposit to the transfer
                 if !amount.is_zero() {
-                    self.debit(amount, app_id);
+                    self.debit(amount, app_id.clone());
                 }
 
                 // Compute value_in_wei and yocto_near
-                let (value_in_wei, yocto_near) = Self::convert_deposit(amount);
+                let (value_in_wei, yocto_near) = convert_deposit(amount);
 
                 // Encode input data
-                let input_data = Self::encode_transfer(&receiver_id, yocto_near);
+                let input_data = encode_transfer(&receiver_id, yocto_near);
 
                 // Compute contract address
-                let contract_address = Self::account_id_to_eth_address(&receiver_id);
+                let contract_address = account_id_to_eth_address(&receiver_id);
 
                 // Use default gas for transfer
                 let gas = Gas::from_tgas(5); // Adjust as needed
-                let evm_gas_limit = Self::near_gas_to_evm_gas(gas.as_gas());
+                let evm_gas_limit = near_gas_to_evm_gas(gas.as_gas());
 
                 (
                     contract_address,
@@ -143,34 +139,25 @@ impl Contract {
             }
         };
 
-        // Build the EVM transaction
-        let evm_transaction = Self::build_evm_transaction(
+        // Build the EVM transaction, paying the fees configured for the chain
+        let chain = self.assert_evm_chain_enabled(NEAR_EVM_CHAIN_ID);
+        let evm_transaction = build_evm_transaction(
             NEAR_EVM_CHAIN_ID,
+            chain.max_fee_per_gas.0,
+            chain.max_priority_fee_per_gas.0,
             nonce.0,
             evm_gas_limit,
             contract_address,
             value_in_wei,
             input_data.clone(),
         );
-
-        let tx_bytes = evm_transaction.build_for_signing();
+        self.assert_evm_transaction_allowed(&evm_transaction);
 
         // Compute the hash of the serialized transaction
-        let hashed_payload_vec = keccak256(&tx_bytes);
-        let hashed_payload: [u8; 32] = hashed_payload_vec
-            .try_into()
-            .expect("Hash output should be 32 bytes");
-
-        let request_payload = create_sign_request_from_transaction(hashed_payload, &bundle.path);
+        let hashed_payload = evm_transaction.signing_hash();
 
         // Call the MPC contract to get a signature
-        Promise::new(self.mpc_contract.clone())
-            .function_call(
-                "sign".to_string(),
-                near_sdk::serde_json::to_vec(&request_payload).unwrap(),
-                NearToken::from_near(1),
-                Gas::from_tgas(50),
-            )
+        self.request_mpc_signature(hashed_payload, &bundle.path)
             .then(
                 // Set a callback to handle the signature
                 Self::ext(env::current_account_id())
@@ -180,219 +167,162 @@ impl Contract {
                         bundle.eth_address,
                         target_account_id,
                         attached_deposit,
+                        app_id,
                     ),
             )
     }
 
-    /// Helper function to convert deposit into value_in_wei and yocto_near
-    fn convert_deposit(deposit: NearToken) -> (u128, u32) {
-        let deposit_yocto = deposit.as_yoctonear();
-        let value_in_wei = deposit_yocto / 1_000_000u128;
-        let yocto_near = (deposit_yocto % 1_000_000u128) as u32;
-        (value_in_wei, yocto_near)
-    }
-
-    /// Helper function to encode the function call input data
-    fn encode_function_call(
-        contract_id: &AccountId,
-        method_name: &str,
-        args: &[u8],
-        gas: u64,
-        yocto_near: u32,
-    ) -> Vec<u8> {
-        let function = Function {
-            name: "functionCall".to_string(),
-            inputs: vec![
-                Param {
-                    name: "receiver_id".to_string(),
-                    kind: ParamType::String,
-                    internal_type: None,
-                },
-                Param {
-                    name: "method_name".to_string(),
-                    kind: ParamType::String,
-                    internal_type: None,
-                },
-                Param {
-                    name: "args".to_string(),
-                    kind: ParamType::Bytes,
-                    internal_type: None,
-                },
-                Param {
-                    name: "gas".to_string(),
-                    kind: ParamType::Uint(64),
-                    internal_type: None,
-                },
-                Param {
-                    name: "yocto_near".to_string(),
-                    kind: ParamType::Uint(32),
-                    internal_type: None,
-                },
-            ],
-            outputs: vec![],
-            constant: None,
-            state_mutability: StateMutability::NonPayable,
+    /// Callback relaying the signed transaction to the user's wallet. If the MPC call
+    /// failed or its signature isn't the wallet's, the deposit debited from the app is
+    /// refunded and the callback resolves to `false` instead of failing, which would
+    /// revert the refund.
+    #[private]
+    pub fn on_sign_evm_txn(
+        &mut self,
+        #[callback_result] call_result: Result<SignResult, PromiseError>,
+        evm_transaction: EvmTransaction,
+        wallet_account_id: AccountId,
+        target_account_id: AccountId,
+        deposit: NearToken,
+        app_id: AppID,
+    ) -> PromiseOrValue<bool> {
+        // Reject signatures not made by the wallet's key before relaying
+        let verified = call_result
+            .map_err(|_| FastAuthError::MpcSignatureFailed)
+            .and_then(|signature| {
+                signature.verify_for_address(&evm_transaction.signing_hash(), &wallet_account_id)
+            });
+
+        let (r_bytes, s_bytes, recovery_id) = match verified {
+            Ok(rsv) => rsv,
+            Err(error) => {
+                env::log_str(&format!("{}, refunding app {}", error, app_id));
+                if !deposit.is_zero() {
+                    self.refund_app(deposit, &app_id);
+                }
+                return PromiseOrValue::Value(false);
+            }
         };
 
-        function
-            .encode_input(&[
-                Token::String(contract_id.clone().to_string()),
-                Token::String(method_name.to_string()),
-                Token::Bytes(args.to_vec()),
-                Token::Uint(U256::from(gas)),
-                Token::Uint(U256::from(yocto_near)),
-            ])
-            .expect("Failed to encode input")
-    }
+        // Construct the signed EVM transaction
+        let signed_tx_bytes = evm_transaction.build_with_signature(&r_bytes, &s_bytes, recovery_id);
 
-    /// Helper function to encode the transfer input data
-    fn encode_transfer(receiver_id: &AccountId, yocto_near: u32) -> Vec<u8> {
-        let function = Function {
-            name: "transfer".to_string(),
-            inputs: vec![
-                Param {
-                    name: "receiver_id".to_string(),
-                    kind: ParamType::String,
-                    internal_type: None,
-                },
-                Param {
-                    name: "yocto_near".to_string(),
-                    kind: ParamType::Uint(32),
-                    internal_type: None,
-                },
-            ],
-            outputs: vec![],
-            constant: None,
-            state_mutability: StateMutability::NonPayable,
-        };
+        // Convert the signed transaction to base64 string
+        let tx_bytes_b64 = STANDARD.encode(signed_tx_bytes);
 
-        function
-            .encode_input(&[
-                Token::String(receiver_id.clone().to_string()),
-                Token::Uint(U256::from(yocto_near)),
-            ])
-            .expect("Failed to encode input")
+        // Call rlp_execute on the wallet contract with the correct target
+        Promise::new(wallet_account_id)
+            .function_call(
+                "rlp_execute".to_string(),
+                near_sdk::serde_json::json!({
+                    "target": target_account_id,
+                    "tx_bytes_b64": tx_bytes_b64,
+                })
+                .to_string()
+                .into_bytes(),
+                deposit,
+                Gas::from_tgas(210),
+            )
+            .into()
     }
+}
 
-    /// Helper function to compute Ethereum address from account ID
-    fn account_id_to_eth_address(account_id: &AccountId) -> Address {
-        let hash = keccak256(account_id.as_bytes());
-        Address::from_slice(&hash[12..32]) // Last 20 bytes
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    const APP_ID: &str = "app";
+    const WALLET: &str = "0x0000000000000000000000000000000000000001";
+
+    fn setup() -> Contract {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .build());
+        let mut contract = Contract::new(accounts(2), accounts(1), accounts(3));
+        contract
+            .app_balances
+            .insert(APP_ID.to_string(), NearToken::from_near(1));
+        contract
     }
 
-    /// Helper function to build the EVM transaction
-    fn build_evm_transaction(
-        chain_id: u64,
-        nonce: u64,
-        gas_limit: u64,
-        to_address: Address,
-        value_in_wei: u128,
-        input_data: Vec<u8>,
-    ) -> EVMTransaction {
-        let max_fee_per_gas = 1u128;
-        let max_priority_fee_per_gas = 1u128;
-
-        EVMTransactionBuilder::new()
-            .chain_id(chain_id)
-            .nonce(nonce)
-            .max_priority_fee_per_gas(max_priority_fee_per_gas)
-            .max_fee_per_gas(max_fee_per_gas)
-            .gas_limit(gas_limit as u128)
-            .to(to_address.into())
-            .value(value_in_wei)
-            .input(input_data)
-            .build()
+    fn on_sign(
+        contract: &mut Contract,
+        call_result: Result<SignResult, PromiseError>,
+    ) -> PromiseOrValue<bool> {
+        let evm_transaction = build_evm_transaction(
+            NEAR_EVM_CHAIN_ID,
+            1,
+            1,
+            0,
+            21_000,
+            account_id_to_eth_address(&accounts(4)),
+            0,
+            vec![],
+        );
+        contract.on_sign_evm_txn(
+            call_result,
+            evm_transaction,
+            WALLET.parse().unwrap(),
+            accounts(4),
+            NearToken::from_millinear(300),
+            APP_ID.to_string(),
+        )
     }
 
-    const GAS_MULTIPLIER: u64 = 100_000_000;
-
-    /// Converts NEAR gas units to EVM gas units by dividing by GAS_MULTIPLIER
-    fn near_gas_to_evm_gas(near_gas: u64) -> u64 {
-        // Round up to ensure sufficient gas
-        (near_gas + Self::GAS_MULTIPLIER - 1) / Self::GAS_MULTIPLIER
+    fn app_balance(contract: &Contract) -> NearToken {
+        *contract.app_balances.get(&APP_ID.to_string()).unwrap()
     }
 
-    /// Callback function to handle the signature from the MPC contract
-    #[private]
-    pub fn on_sign_evm_txn(
-        &mut self,
-        #[callback_result] call_result: Result<SignResult, PromiseError>,
-        evm_transaction: EVMTransaction,
-        wallet_account_id: AccountId,
-        target_account_id: AccountId,
-        deposit: NearToken,
-    ) -> Promise {
-        match call_result {
-            Ok(signature) => {
-                env::log_str(&format!(
-                    "Signature received from MPC contract: {:?}",
-                    signature
-                ));
-
-                // Extract r
-                let affine_point_hex = &signature.big_r.affine_point;
-
-                // Decode the hex string to bytes
-                let compressed_point_bytes = hex::decode(affine_point_hex)
-                    .expect("Failed to decode affine_point hex string");
-
-                if compressed_point_bytes.len() != 33 {
-                    env::panic_str(&format!(
-                        "Invalid compressed point length. Found: {}",
-                        compressed_point_bytes.len()
-                    ));
-                }
+    #[test]
+    fn failed_mpc_call_refunds_the_app() {
+        let mut contract = setup();
 
-                // Remove the first byte (prefix)
-                let r_bytes = compressed_point_bytes[1..].to_vec();
+        let result = on_sign(&mut contract, Err(PromiseError::Failed));
 
-                if r_bytes.len() != 32 {
-                    env::panic_str(&format!(
-                        "Invalid r length after removing prefix. Found: {}",
-                        r_bytes.len()
-                    ));
-                }
+        assert!(matches!(result, PromiseOrValue::Value(false)));
+        assert_eq!(app_balance(&contract), NearToken::from_millinear(1300));
+    }
 
-                // Extract s
-                let s_bytes =
-                    hex::decode(&signature.s.scalar).expect("Failed to decode s scalar hex string");
+    #[test]
+    fn signatures_not_made_by_the_wallet_refund_the_app() {
+        let mut contract = setup();
+
+        // A well-formed signature recovering to some other key
+        let signature = SignResult {
+            big_r: AffinePoint {
+                affine_point: "0279BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798"
+                    .to_string(),
+            },
+            s: Scalar {
+                scalar: "01".repeat(32),
+            },
+            recovery_id: 0,
+        };
+        let result = on_sign(&mut contract, Ok(signature));
 
-                if s_bytes.len() != 32 {
-                    env::panic_str(&format!("Invalid s length. Found: {}", s_bytes.len()));
-                }
+        assert!(matches!(result, PromiseOrValue::Value(false)));
+        assert_eq!(app_balance(&contract), NearToken::from_millinear(1300));
+    }
 
-                // Extract v
-                let v = signature.recovery_id as u64;
-
-                // Construct OmniSignature
-                let omni_signature = OmniSignature {
-                    v,
-                    r: r_bytes,
-                    s: s_bytes,
-                };
-
-                // Construct the signed EVM transaction
-                let signed_tx_bytes = evm_transaction.build_with_signature(&omni_signature);
-
-                // Convert the signed transaction to base64 string
-                let tx_bytes_b64 = base64::encode(signed_tx_bytes);
-
-                // Call rlp_execute on the wallet contract with the correct target
-                Promise::new(wallet_account_id.clone()).function_call(
-                    "rlp_execute".to_string(),
-                    near_sdk::serde_json::json!({
-                        "target": target_account_id,
-                        "tx_bytes_b64": tx_bytes_b64,
-                    })
-                    .to_string()
-                    .into_bytes(),
-                    deposit,
-                    Gas::from_tgas(210),
-                )
-            }
-            Err(_e) => {
-                env::panic_str("Failed to get signature from MPC contract");
-            }
-        }
+    #[test]
+    fn malformed_signatures_refund_the_app() {
+        let mut contract = setup();
+
+        let signature = SignResult {
+            big_r: AffinePoint {
+                affine_point: "not hex".to_string(),
+            },
+            s: Scalar {
+                scalar: "01".repeat(32),
+            },
+            recovery_id: 0,
+        };
+        let result = on_sign(&mut contract, Ok(signature));
+
+        assert!(matches!(result, PromiseOrValue::Value(false)));
+        assert_eq!(app_balance(&contract), NearToken::from_millinear(1300));
     }
 }
@@ -0,0 +1,173 @@
+// trial_user/perform_actions/near/delegate.rs
+use crate::*;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use near_sdk::PromiseError;
+use omni_transaction::near::types::PublicKey as OmniPublicKey;
+use perform_actions::near::native::{append_signature, NativeNearAction};
+
+/// Prefix NEP-461 puts before a borsh-serialized `DelegateAction` when hashing it for
+/// signing, so the signature can't be replayed as a transaction: 2^30 + 366.
+const NEP_366_SIGNABLE_MESSAGE_PREFIX: u32 = (1 << 30) + 366;
+
+/// Payload a session key signs to have a `DelegateAction` signed for the user's named
+/// NEAR account. `nonce` must be above the MPC access key's current nonce and
+/// `max_block_height` bounds how long a relayer may hold on to it.
+#[derive(Clone)]
+#[near(serializers = [json])]
+pub struct DelegatePayload {
+    pub receiver_id: AccountId,
+    pub actions: Vec<NativeNearAction>,
+    pub nonce: U64,
+    pub max_block_height: U64,
+}
+
+/// NEP-366 `DelegateAction`, which omni-transaction doesn't model. Borsh-serializes
+/// exactly as nearcore's: `NonDelegateAction` is a plain `Action` on the wire, and
+/// omni's `Action` has no `Delegate` variant to nest.
+#[near(serializers = [borsh])]
+pub struct DelegateAction {
+    pub sender_id: AccountId,
+    pub receiver_id: AccountId,
+    pub actions: Vec<OmniAction>,
+    pub nonce: u64,
+    pub max_block_height: u64,
+    pub public_key: OmniPublicKey,
+}
+
+#[near]
+impl Contract {
+    /// Signs a NEP-366 `DelegateAction` from the user's linked NEAR account with its
+    /// MPC key. Resolves to the base64 borsh-serialized `SignedDelegateAction`, which
+    /// any relayer can wrap in a transaction and pay the gas for.
+    pub fn sign_delegate_action(
+        &mut self,
+        signature: SessionSignature,
+        payload: DelegatePayload,
+        session_key: SessionKey,
+        app_id: AppID,
+    ) -> Promise {
+        self.assert_valid_signature(&payload, &signature, &session_key, &app_id);
+
+        // Deposits come out of the user's own account, so nothing is debited from the app
+        let (_, key_usage) = self.assert_native_actions_allowed(
+            &payload.receiver_id,
+            &payload.actions,
+            &session_key,
+            &app_id,
+        );
+        ensure(
+            payload.max_block_height.0 > env::block_height(),
+            FastAuthError::InvalidNearTransaction("max_block_height has passed".into()),
+        );
+
+        let (sender_id, mpc_key) = self.internal_get_near_account(&key_usage.path);
+
+        let delegate_action = DelegateAction {
+            sender_id,
+            receiver_id: payload.receiver_id,
+            actions: payload
+                .actions
+                .iter()
+                .map(NativeNearAction::to_omni)
+                .collect(),
+            nonce: payload.nonce.0,
+            max_block_height: payload.max_block_height.0,
+            public_key: convert_pk_to_omni(&mpc_key),
+        };
+        let delegate_action_bytes =
+            near_sdk::borsh::to_vec(&delegate_action).or_fail(FastAuthError::EncodingFailed);
+
+        self.request_mpc_signature(
+            delegate_action_hash(&delegate_action_bytes),
+            &key_usage.path,
+        )
+        .then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(Gas::from_tgas(10))
+                .on_sign_delegate_action(delegate_action_bytes, mpc_key),
+        )
+    }
+
+    /// Callback checking the MPC signature against the account's key and returning
+    /// the signed delegate action as a base64 string
+    #[private]
+    pub fn on_sign_delegate_action(
+        &self,
+        #[callback_result] call_result: Result<SignResult, PromiseError>,
+        delegate_action_bytes: Vec<u8>,
+        public_key: PublicKey,
+    ) -> String {
+        match call_result {
+            Ok(sign_result) => {
+                let signature = sign_result
+                    .to_near_signature(&delegate_action_hash(&delegate_action_bytes), &public_key);
+
+                STANDARD.encode(append_signature(delegate_action_bytes, signature))
+            }
+            Err(_e) => {
+                FastAuthError::MpcSignatureFailed.panic();
+            }
+        }
+    }
+}
+
+/// Hash the sender's key signs for a borsh-serialized `DelegateAction` (NEP-366)
+pub fn delegate_action_hash(delegate_action_bytes: &[u8]) -> [u8; 32] {
+    let mut message = NEP_366_SIGNABLE_MESSAGE_PREFIX.to_le_bytes().to_vec();
+    message.extend_from_slice(delegate_action_bytes);
+    env::sha256_array(&message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delegate_action_hash_is_prefixed_with_the_nep_366_discriminant() {
+        let bytes = b"delegate action";
+
+        let mut expected = vec![0x6e, 0x01, 0x00, 0x40];
+        expected.extend_from_slice(bytes);
+
+        assert_eq!(delegate_action_hash(bytes), env::sha256_array(&expected));
+    }
+
+    /// Borsh bytes and NEP-461 hash of the same `DelegateAction` built with
+    /// near-primitives 0.26 (`DelegateAction::get_nep461_hash`)
+    #[test]
+    fn delegate_action_matches_near_primitives() {
+        let public_key: PublicKey = "ed25519:22skMptHjFWNyuEWY22ftn2AbLPSYpmYwGJRGwpNHbTV"
+            .parse()
+            .unwrap();
+        let actions = [
+            NativeNearAction::FunctionCall {
+                method_name: "add_message".to_string(),
+                args: br#"{"text":"hi"}"#.to_vec().into(),
+                gas: Gas::from_tgas(30),
+                deposit: NearToken::from_yoctonear(0),
+            },
+            NativeNearAction::Transfer {
+                amount: NearToken::from_near(1),
+            },
+        ];
+        let delegate_action = DelegateAction {
+            sender_id: "alice.near".parse().unwrap(),
+            receiver_id: "app.near".parse().unwrap(),
+            actions: actions.iter().map(NativeNearAction::to_omni).collect(),
+            nonce: 42,
+            max_block_height: 1_000,
+            public_key: convert_pk_to_omni(&public_key),
+        };
+
+        let bytes = near_sdk::borsh::to_vec(&delegate_action).unwrap();
+        assert_eq!(
+            hex::encode(&bytes),
+            "0a000000616c6963652e6e656172080000006170702e6e65617202000000020b0000006164645f6d6573736167650d0000007b2274657874223a226869227d00e057eb481b00000000000000000000000000000000000003000000a1edccce1bc2d30000000000002a00000000000000e803000000000000000f56a5f028dfc089ec7c39c1183b321b4d8f89ba5bec9e1762803cc2491f6ef8"
+        );
+        assert_eq!(
+            hex::encode(delegate_action_hash(&bytes)),
+            "cc33d6f024d8fc6f6354a2f96109e1338dc0fc8e094b0363a61dd2e33edd0c85"
+        );
+    }
+}
@@ -0,0 +1,289 @@
+// trial_user/perform_actions/near/encoding.rs
+//! Pure helpers turning a NEAR action into the EIP-1559 transaction the NEAR wallet
+//! contract (`rlp_execute`) expects. The wallet decodes the transaction's input as a
+//! call to `functionCall(string,string,bytes,uint64,uint32)` or `transfer(string,uint32)`.
+use crate::*;
+use ethabi::{Address, Function, Param, ParamType, StateMutability, Token};
+use ethereum_types::U256;
+use omni_transaction::evm::evm_transaction_builder::EVMTransactionBuilder;
+use omni_transaction::transaction_builder::TxBuilder;
+use perform_actions::evm::transaction::EvmTransaction;
+
+/// NEAR gas units per EVM gas unit on the wallet contract
+pub const GAS_MULTIPLIER: u64 = 100_000_000;
+
+/// Splits a deposit into the transaction value in wei (1 wei = 10^6 yocto)
+/// and the yoctoNEAR remainder passed to the wallet as `yocto_near`.
+pub fn convert_deposit(deposit: NearToken) -> (u128, u32) {
+    let deposit_yocto = deposit.as_yoctonear();
+    let value_in_wei = deposit_yocto / 1_000_000u128;
+    let yocto_near = (deposit_yocto % 1_000_000u128) as u32;
+    (value_in_wei, yocto_near)
+}
+
+/// Encodes the input data of a function call
+pub fn encode_function_call(
+    contract_id: &AccountId,
+    method_name: &str,
+    args: &[u8],
+    gas: u64,
+    yocto_near: u32,
+) -> Vec<u8> {
+    encode_wallet_call(
+        "functionCall",
+        vec![
+            param("receiver_id", ParamType::String),
+            param("method_name", ParamType::String),
+            param("args", ParamType::Bytes),
+            param("gas", ParamType::Uint(64)),
+            param("yocto_near", ParamType::Uint(32)),
+        ],
+        &[
+            Token::String(contract_id.to_string()),
+            Token::String(method_name.to_string()),
+            Token::Bytes(args.to_vec()),
+            Token::Uint(U256::from(gas)),
+            Token::Uint(U256::from(yocto_near)),
+        ],
+    )
+}
+
+/// Encodes the input data of a transfer
+pub fn encode_transfer(receiver_id: &AccountId, yocto_near: u32) -> Vec<u8> {
+    encode_wallet_call(
+        "transfer",
+        vec![
+            param("receiver_id", ParamType::String),
+            param("yocto_near", ParamType::Uint(32)),
+        ],
+        &[
+            Token::String(receiver_id.to_string()),
+            Token::Uint(U256::from(yocto_near)),
+        ],
+    )
+}
+
+/// Address a NEAR account is reached at through the wallet: the last 20 bytes
+/// of keccak256 of the account id
+pub fn account_id_to_eth_address(account_id: &AccountId) -> Address {
+    let hash = env::keccak256_array(account_id.as_bytes());
+    Address::from_slice(&hash[12..32])
+}
+
+/// Converts NEAR gas units to EVM gas units, rounding up to ensure sufficient gas
+pub fn near_gas_to_evm_gas(near_gas: u64) -> u64 {
+    near_gas.div_ceil(GAS_MULTIPLIER)
+}
+
+/// Builds the EIP-1559 transaction sent to the wallet
+#[allow(clippy::too_many_arguments)]
+pub fn build_evm_transaction(
+    chain_id: u64,
+    max_fee_per_gas: u128,
+    max_priority_fee_per_gas: u128,
+    nonce: u64,
+    gas_limit: u64,
+    to_address: Address,
+    value_in_wei: u128,
+    input_data: Vec<u8>,
+) -> EvmTransaction {
+    EvmTransaction::Eip1559(
+        EVMTransactionBuilder::new()
+            .chain_id(chain_id)
+            .nonce(nonce)
+            .max_priority_fee_per_gas(max_priority_fee_per_gas)
+            .max_fee_per_gas(max_fee_per_gas)
+            .gas_limit(gas_limit as u128)
+            .to(to_address.into())
+            .value(value_in_wei)
+            .input(input_data)
+            .build(),
+    )
+}
+
+fn encode_wallet_call(name: &str, inputs: Vec<Param>, tokens: &[Token]) -> Vec<u8> {
+    #[allow(deprecated)]
+    let function = Function {
+        name: name.to_string(),
+        inputs,
+        outputs: vec![],
+        constant: None,
+        state_mutability: StateMutability::NonPayable,
+    };
+
+    function
+        .encode_input(tokens)
+        .or_fail(FastAuthError::EncodingFailed)
+}
+
+fn param(name: &str, kind: ParamType) -> Param {
+    Param {
+        name: name.to_string(),
+        kind,
+        internal_type: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    const GUESTBOOK: &str = "guestbook.near-examples.testnet";
+
+    fn setup() {
+        testing_env!(VMContextBuilder::new().build());
+    }
+
+    fn account(id: &str) -> AccountId {
+        id.parse().unwrap()
+    }
+
+    #[test]
+    fn convert_deposit_splits_wei_and_yocto() {
+        assert_eq!(convert_deposit(NearToken::from_yoctonear(0)), (0, 0));
+        assert_eq!(
+            convert_deposit(NearToken::from_yoctonear(999_999)),
+            (0, 999_999)
+        );
+        assert_eq!(
+            convert_deposit(NearToken::from_yoctonear(1_000_000)),
+            (1, 0)
+        );
+        assert_eq!(
+            convert_deposit(NearToken::from_near(1)),
+            (1_000_000_000_000_000_000, 0)
+        );
+        assert_eq!(
+            convert_deposit(NearToken::from_yoctonear(5_000_123)),
+            (5, 123)
+        );
+    }
+
+    #[test]
+    fn near_gas_to_evm_gas_rounds_up() {
+        assert_eq!(near_gas_to_evm_gas(0), 0);
+        assert_eq!(near_gas_to_evm_gas(1), 1);
+        assert_eq!(near_gas_to_evm_gas(GAS_MULTIPLIER), 1);
+        assert_eq!(near_gas_to_evm_gas(GAS_MULTIPLIER + 1), 2);
+        assert_eq!(near_gas_to_evm_gas(Gas::from_tgas(100).as_gas()), 1_000_000);
+    }
+
+    #[test]
+    fn near_max_gas_call_passes_the_default_registry() {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("owner.near".parse().unwrap())
+            .build());
+        let contract = Contract::new(
+            account("owner.near"),
+            account("oracle.near"),
+            account("mpc.near"),
+        );
+
+        let max_gas = near_gas_to_evm_gas(Gas::from_tgas(300).as_gas());
+        assert_eq!(max_gas as u128, NEAR_EVM_MAX_GAS_LIMIT);
+
+        let chain = EvmChain::near_evm();
+        contract.assert_evm_transaction_allowed(&build_evm_transaction(
+            NEAR_EVM_CHAIN_ID,
+            chain.max_fee_per_gas.0,
+            chain.max_priority_fee_per_gas.0,
+            0,
+            max_gas,
+            account_id_to_eth_address(&account(GUESTBOOK)),
+            0,
+            encode_function_call(&account(GUESTBOOK), "add_message", b"{}", 0, 0),
+        ));
+    }
+
+    #[test]
+    fn account_id_to_eth_address_matches_wallet_derivation() {
+        setup();
+        assert_eq!(
+            hex::encode(account_id_to_eth_address(&account(GUESTBOOK))),
+            "cf90f19e1ed82ed7768a07983b3c397da3bbf21f"
+        );
+        assert_eq!(
+            hex::encode(account_id_to_eth_address(&account("bob.near"))),
+            "827c5d7bde400f1716255ae54bb8a55cb43d0324"
+        );
+    }
+
+    #[test]
+    fn encode_function_call_golden_vector() {
+        let input = encode_function_call(
+            &account(GUESTBOOK),
+            "add_message",
+            br#"{"text":"hi"}"#,
+            Gas::from_tgas(100).as_gas(),
+            0,
+        );
+        assert_eq!(&input[..4], &[0x61, 0x79, 0xb7, 0x07]);
+        assert_eq!(
+            hex::encode(input),
+            concat!(
+                "6179b707",
+                "00000000000000000000000000000000000000000000000000000000000000a0",
+                "00000000000000000000000000000000000000000000000000000000000000e0",
+                "0000000000000000000000000000000000000000000000000000000000000120",
+                "00000000000000000000000000000000000000000000000000005af3107a4000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "000000000000000000000000000000000000000000000000000000000000001f",
+                "6775657374626f6f6b2e6e6561722d6578616d706c65732e746573746e657400",
+                "000000000000000000000000000000000000000000000000000000000000000b",
+                "6164645f6d657373616765000000000000000000000000000000000000000000",
+                "000000000000000000000000000000000000000000000000000000000000000d",
+                "7b2274657874223a226869227d00000000000000000000000000000000000000",
+            )
+        );
+    }
+
+    #[test]
+    fn encode_transfer_golden_vector() {
+        let input = encode_transfer(&account("bob.near"), 5);
+        assert_eq!(
+            hex::encode(input),
+            concat!(
+                "3ed64124",
+                "0000000000000000000000000000000000000000000000000000000000000040",
+                "0000000000000000000000000000000000000000000000000000000000000005",
+                "0000000000000000000000000000000000000000000000000000000000000008",
+                "626f622e6e656172000000000000000000000000000000000000000000000000",
+            )
+        );
+    }
+
+    #[test]
+    fn build_evm_transaction_golden_vector() {
+        setup();
+        let receiver = account("bob.near");
+        let transaction = build_evm_transaction(
+            NEAR_EVM_CHAIN_ID,
+            1,
+            1,
+            0,
+            50,
+            account_id_to_eth_address(&receiver),
+            1,
+            encode_transfer(&receiver, 5),
+        );
+
+        assert_eq!(
+            hex::encode(transaction.build_for_signing()),
+            concat!(
+                "02f8a482018e8001013294827c5d7bde400f1716255ae54bb8a55cb43d032401b884",
+                "3ed64124",
+                "0000000000000000000000000000000000000000000000000000000000000040",
+                "0000000000000000000000000000000000000000000000000000000000000005",
+                "0000000000000000000000000000000000000000000000000000000000000008",
+                "626f622e6e656172000000000000000000000000000000000000000000000000",
+                "c0",
+            )
+        );
+        assert_eq!(
+            hex::encode(transaction.signing_hash()),
+            "66d7370b60b4921c93397df40b69a2fd778911845d984efca5f6882aed0025db"
+        );
+    }
+}
@@ -1 +1,5 @@
 pub mod call_fn;
+pub mod delegate;
+pub mod encoding;
+pub mod native;
+pub mod transaction;
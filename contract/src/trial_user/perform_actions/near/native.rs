@@ -0,0 +1,258 @@
+// trial_user/perform_actions/near/native.rs
+use crate::*;
+use near_sdk::json_types::Base64VecU8;
+use omni_transaction::near::types::{
+    Secp256K1Signature as OmniSecp256K1Signature, Signature as OmniSignature,
+    TransferAction as OmniTransferAction,
+};
+
+/// Action for the user's named NEAR account, signed natively with the user's MPC key
+/// instead of being wrapped in an EVM transaction for the eth-implicit wallet.
+/// Access keys can't be added: they would keep working after the trial ends or is
+/// revoked, outside the app's constraints.
+#[derive(Clone)]
+#[near(serializers = [json])]
+#[serde(tag = "type")]
+pub enum NativeNearAction {
+    FunctionCall {
+        method_name: String,
+        args: Base64VecU8,
+        gas: Gas,
+        deposit: NearToken,
+    },
+    Transfer {
+        amount: NearToken,
+    },
+}
+
+impl NativeNearAction {
+    /// Deposit the action moves out of the user's account
+    pub fn deposit(&self) -> NearToken {
+        match self {
+            NativeNearAction::FunctionCall { deposit, .. } => *deposit,
+            NativeNearAction::Transfer { amount } => *amount,
+        }
+    }
+
+    pub fn to_omni(&self) -> OmniAction {
+        match self {
+            NativeNearAction::FunctionCall {
+                method_name,
+                args,
+                gas,
+                deposit,
+            } => OmniAction::FunctionCall(Box::new(OmniFunctionCallAction {
+                method_name: method_name.clone(),
+                args: args.0.clone(),
+                gas: OmniU64(gas.as_gas()),
+                deposit: OmniU128(deposit.as_yoctonear()),
+            })),
+            NativeNearAction::Transfer { amount } => OmniAction::Transfer(OmniTransferAction {
+                deposit: OmniU128(amount.as_yoctonear()),
+            }),
+        }
+    }
+}
+
+/// Appends a secp256k1 signature to a borsh-serialized `Transaction` or `DelegateAction`,
+/// giving the borsh serialization of its signed counterpart.
+pub fn append_signature(mut message: Vec<u8>, signature: [u8; 65]) -> Vec<u8> {
+    message.extend(
+        near_sdk::borsh::to_vec(&OmniSignature::SECP256K1(OmniSecp256K1Signature(signature)))
+            .or_fail(FastAuthError::EncodingFailed),
+    );
+    message
+}
+
+impl Contract {
+    /// Checks actions sent from the user's named account to `receiver_id` against the
+    /// app's NEAR constraints: calls and transfers must go to an allowed contract, each
+    /// call to an allowed method within the gas cap, and the deposits together must be
+    /// within the deposit cap.
+    /// Returns the app's trial data and the key's usage for further checks.
+    pub(crate) fn assert_native_actions_allowed(
+        &self,
+        receiver_id: &AccountId,
+        actions: &[NativeNearAction],
+        session_key: &SessionKey,
+        app_id: &AppID,
+    ) -> (TrialData, KeyUsage) {
+        let (trial_data, key_usage) = self.assert_trial_active(session_key, app_id);
+
+        ensure(
+            !actions.is_empty(),
+            FastAuthError::InvalidNearTransaction("No actions given".into()),
+        );
+
+        let chain_id = ChainId::from("NEAR");
+        let mut total_deposit: u128 = 0;
+        for action in actions {
+            match action {
+                NativeNearAction::FunctionCall {
+                    method_name, gas, ..
+                } => {
+                    ensure(
+                        trial_data.is_contract_allowed(receiver_id.as_str(), &chain_id),
+                        FastAuthError::ContractNotAllowed,
+                    );
+                    ensure(
+                        trial_data.is_method_allowed(method_name, &chain_id),
+                        FastAuthError::MethodNotAllowed,
+                    );
+                    ensure(
+                        trial_data.is_gas_within_limits(gas.as_gas(), &chain_id),
+                        FastAuthError::GasLimitExceeded,
+                    );
+                }
+                NativeNearAction::Transfer { .. } => {
+                    ensure(
+                        trial_data.is_contract_allowed(receiver_id.as_str(), &chain_id),
+                        FastAuthError::ContractNotAllowed,
+                    );
+                }
+            }
+            total_deposit = total_deposit
+                .checked_add(action.deposit().as_yoctonear())
+                .or_fail(FastAuthError::InvalidNearTransaction(
+                    "Deposit overflow".into(),
+                ));
+        }
+        ensure(
+            trial_data.is_deposit_within_limits(total_deposit, &chain_id),
+            FastAuthError::DepositLimitExceeded,
+        );
+
+        (trial_data, key_usage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    const APP_ID: &str = "app";
+    const SESSION_KEY: &str = "ed25519:AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9";
+    const GUESTBOOK: &str = "guestbook.near-examples.testnet";
+
+    fn setup() -> Contract {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(NearToken::from_near(1))
+            .build());
+        let mut contract = Contract::new(accounts(2), accounts(1), accounts(3));
+        contract.add_session_key(
+            SESSION_KEY.parse().unwrap(),
+            "user-path".to_string(),
+            APP_ID.to_string(),
+        );
+
+        let mut constraints_by_chain_id = HashMap::new();
+        constraints_by_chain_id.insert(
+            ChainId::from("NEAR"),
+            ChainConstraints::NEAR(NearConstraints {
+                allowed_methods: vec!["add_message".to_string()],
+                allowed_contracts: vec![GUESTBOOK.parse().unwrap()],
+                max_gas: Some(Gas::from_tgas(100)),
+                max_deposit: Some(NearToken::from_near(1)),
+                initial_deposit: NearToken::from_near(0),
+            }),
+        );
+        contract.internal_set_trial_data(
+            APP_ID.to_string(),
+            TrialData {
+                constraints_by_chain_id,
+                usage_constraints: None,
+                interaction_limits: None,
+                exit_conditions: None,
+                expiration_time: None,
+                creator_account_id: accounts(2),
+                typed_data_constraints: None,
+                personal_sign_format: None,
+                bitcoin_constraints: None,
+                solana_constraints: None,
+            },
+        );
+        contract
+    }
+
+    fn add_message(deposit: NearToken) -> NativeNearAction {
+        NativeNearAction::FunctionCall {
+            method_name: "add_message".to_string(),
+            args: Base64VecU8::from(br#"{"text":"hi"}"#.to_vec()),
+            gas: Gas::from_tgas(30),
+            deposit,
+        }
+    }
+
+    fn check(receiver_id: &str, actions: &[NativeNearAction]) {
+        setup().assert_native_actions_allowed(
+            &receiver_id.parse().unwrap(),
+            actions,
+            &SESSION_KEY.parse().unwrap(),
+            &APP_ID.to_string(),
+        );
+    }
+
+    #[test]
+    fn allowed_calls_and_transfers_pass() {
+        check(
+            GUESTBOOK,
+            &[
+                add_message(NearToken::from_millinear(500)),
+                NativeNearAction::Transfer {
+                    amount: NearToken::from_millinear(500),
+                },
+            ],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = ":4004:")]
+    fn receiver_must_be_an_allowed_contract() {
+        check("bob.near", &[add_message(NearToken::from_near(0))]);
+    }
+
+    #[test]
+    #[should_panic(expected = ":4003:")]
+    fn methods_are_checked_per_call() {
+        check(
+            GUESTBOOK,
+            &[NativeNearAction::FunctionCall {
+                method_name: "delete_messages".to_string(),
+                args: Base64VecU8::from(vec![]),
+                gas: Gas::from_tgas(30),
+                deposit: NearToken::from_near(0),
+            }],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = ":4006:")]
+    fn deposits_are_capped_in_total() {
+        check(
+            GUESTBOOK,
+            &[
+                add_message(NearToken::from_millinear(600)),
+                add_message(NearToken::from_millinear(600)),
+            ],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = ":6008:")]
+    fn actions_are_required() {
+        check(GUESTBOOK, &[]);
+    }
+
+    #[test]
+    fn signature_is_appended_with_the_secp256k1_tag() {
+        let signed = append_signature(vec![0xaa, 0xbb], [7; 65]);
+
+        assert_eq!(signed.len(), 2 + 1 + 65);
+        assert_eq!(&signed[..3], &[0xaa, 0xbb, 0x01]);
+        assert_eq!(&signed[3..], &[7; 65]);
+    }
+}
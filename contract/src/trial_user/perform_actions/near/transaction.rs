@@ -0,0 +1,92 @@
+// trial_user/perform_actions/near/transaction.rs
+use crate::*;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use near_sdk::PromiseError;
+use perform_actions::near::native::{append_signature, NativeNearAction};
+
+/// Payload a session key signs to have a transaction signed for the user's named NEAR
+/// account. The caller fetches `nonce`, above the MPC access key's current nonce, and a
+/// recent `block_hash` from RPC, as the contract can't read them.
+#[derive(Clone)]
+#[near(serializers = [json])]
+pub struct NearTransactionPayload {
+    pub receiver_id: AccountId,
+    pub actions: Vec<NativeNearAction>,
+    pub nonce: U64,
+    pub block_hash: Base58CryptoHash,
+}
+
+#[near]
+impl Contract {
+    /// Signs a transaction from the user's linked NEAR account with its MPC key.
+    /// Resolves to the base64 borsh-serialized `SignedTransaction`, ready for
+    /// `broadcast_tx_commit`. The account pays its own gas and deposits.
+    pub fn sign_near_transaction(
+        &mut self,
+        signature: SessionSignature,
+        payload: NearTransactionPayload,
+        session_key: SessionKey,
+        app_id: AppID,
+    ) -> Promise {
+        self.assert_valid_signature(&payload, &signature, &session_key, &app_id);
+
+        let (_, key_usage) = self.assert_native_actions_allowed(
+            &payload.receiver_id,
+            &payload.actions,
+            &session_key,
+            &app_id,
+        );
+
+        let (signer_id, mpc_key) = self.internal_get_near_account(&key_usage.path);
+        let block_hash: CryptoHash = payload.block_hash.into();
+
+        let near_transaction = TransactionBuilder::new::<NEAR>()
+            .signer_id(signer_id.to_string())
+            .signer_public_key(convert_pk_to_omni(&mpc_key))
+            .nonce(payload.nonce.0)
+            .receiver_id(payload.receiver_id.to_string())
+            .block_hash(OmniBlockHash(block_hash))
+            .actions(
+                payload
+                    .actions
+                    .iter()
+                    .map(NativeNearAction::to_omni)
+                    .collect(),
+            )
+            .build();
+
+        // The key signs the SHA-256 of the borsh-serialized transaction
+        let transaction_bytes = near_transaction.build_for_signing();
+
+        self.request_mpc_signature(env::sha256_array(&transaction_bytes), &key_usage.path)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(Gas::from_tgas(10))
+                    .on_sign_near_txn(transaction_bytes, mpc_key),
+            )
+    }
+
+    /// Callback checking the MPC signature against the account's key and returning
+    /// the signed transaction as a base64 string
+    #[private]
+    pub fn on_sign_near_txn(
+        &self,
+        #[callback_result] call_result: Result<SignResult, PromiseError>,
+        transaction_bytes: Vec<u8>,
+        public_key: PublicKey,
+    ) -> String {
+        match call_result {
+            Ok(sign_result) => {
+                let signature = sign_result
+                    .to_near_signature(&env::sha256_array(&transaction_bytes), &public_key);
+
+                // A SignedTransaction is the transaction followed by its signature
+                STANDARD.encode(append_signature(transaction_bytes, signature))
+            }
+            Err(_e) => {
+                FastAuthError::MpcSignatureFailed.panic();
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+// trial_user/perform_actions/session_signature.rs
+//! Session key signature checks, shared with the SDK so off-chain services turn away
+//! exactly the payloads the contract rejects. On chain the checks use NEAR's host
+//! functions; elsewhere they run on pure-Rust crates, so services don't need
+//! near-sdk's mocked blockchain.
+use crate::*;
+use perform_actions::webauthn::verify_webauthn_assertion;
+
+/// Checks a session key's signature over the payload's JSON serialization.
+pub fn verify_session_signature(
+    payload_bytes: &[u8],
+    signature: &SessionSignature,
+    session_key: &SessionKey,
+) -> Result<(), FastAuthError> {
+    let is_valid = match (session_key.key_type(), signature) {
+        (SessionKeyType::Ed25519, SessionSignature::Raw(signature)) => {
+            let public_key: &[u8; 32] = session_key
+                .key_data()
+                .try_into()
+                .map_err(|_| FastAuthError::InvalidKeyLength)?;
+            let signature: &[u8; 64] = signature
+                .0
+                .as_slice()
+                .try_into()
+                .map_err(|_| FastAuthError::InvalidSignatureLength)?;
+
+            ed25519_verify(signature, payload_bytes, public_key)
+        }
+        (SessionKeyType::Secp256k1, SessionSignature::Raw(signature)) => {
+            verify_secp256k1_signature(payload_bytes, &signature.0, session_key.key_data())?
+        }
+        (SessionKeyType::P256, SessionSignature::WebAuthn(assertion)) => {
+            verify_webauthn_assertion(payload_bytes, assertion, session_key.key_data())
+        }
+        _ => return Err(FastAuthError::SignatureKindMismatch),
+    };
+
+    if is_valid {
+        Ok(())
+    } else {
+        Err(FastAuthError::InvalidSignature)
+    }
+}
+
+/// Checks a `personal_sign` signature (`r || s || v`, with `v` in 0/1 or 27/28) over the
+/// payload against an uncompressed secp256k1 key without its 0x04 prefix.
+pub(crate) fn verify_secp256k1_signature(
+    payload_bytes: &[u8],
+    signature: &[u8],
+    public_key: &[u8],
+) -> Result<bool, FastAuthError> {
+    if signature.len() != 65 {
+        return Err(FastAuthError::InvalidSignatureLength);
+    }
+
+    let hash = eip191_hash(payload_bytes);
+
+    // Accept both raw (0/1) and Ethereum-style (27/28) recovery ids
+    let v = match signature[64] {
+        27 | 28 => signature[64] - 27,
+        v => v,
+    };
+
+    Ok(ecrecover(&hash, &signature[..64], v).is_some_and(|recovered| recovered == public_key))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn sha256(data: &[u8]) -> [u8; 32] {
+    env::sha256_array(data)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn sha256(data: &[u8]) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(data).into()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn keccak256(data: &[u8]) -> [u8; 32] {
+    env::keccak256_array(data)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn keccak256(data: &[u8]) -> [u8; 32] {
+    use sha3::{Digest, Keccak256};
+    Keccak256::digest(data).into()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn ed25519_verify(signature: &[u8; 64], message: &[u8], public_key: &[u8; 32]) -> bool {
+    env::ed25519_verify(signature, message, public_key)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn ed25519_verify(signature: &[u8; 64], message: &[u8], public_key: &[u8; 32]) -> bool {
+    use ed25519_dalek::Verifier;
+    let signature = ed25519_dalek::Signature::from_bytes(signature);
+    ed25519_dalek::VerifyingKey::from_bytes(public_key)
+        .is_ok_and(|key| key.verify(message, &signature).is_ok())
+}
+
+/// Recovers the uncompressed key (without the 0x04 prefix) behind a signature over
+/// `hash`. High-s signatures are rejected.
+#[cfg(target_arch = "wasm32")]
+fn ecrecover(hash: &[u8; 32], signature: &[u8], v: u8) -> Option<[u8; 64]> {
+    env::ecrecover(hash, signature, v, true)
+}
+
+/// Recovers the uncompressed key (without the 0x04 prefix) behind a signature over
+/// `hash`. High-s signatures are rejected, as `env::ecrecover` does on chain.
+#[cfg(not(target_arch = "wasm32"))]
+fn ecrecover(hash: &[u8; 32], signature: &[u8], v: u8) -> Option<[u8; 64]> {
+    let signature = k256::ecdsa::Signature::from_slice(signature).ok()?;
+    if signature.normalize_s().is_some() {
+        return None;
+    }
+    let recovery_id = k256::ecdsa::RecoveryId::from_byte(v)?;
+    let key =
+        k256::ecdsa::VerifyingKey::recover_from_prehash(hash, &signature, recovery_id).ok()?;
+    key.to_encoded_point(false).as_bytes()[1..].try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use near_sdk::json_types::Base64VecU8;
+    use near_sdk::CurveType;
+
+    const ED25519_KEY: &str = "ed25519:AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9";
+    /// ed25519 signature of `{"message":"hello"}` by the key seeded with `[1; 32]`
+    const ED25519_SIGNATURE: &str =
+        "RBf/3SkfvNkUlQClozHdPhjzn1gdcYvZryCf/ymeENRUJrwOUfw7YdXpmI7oGigMcvGR0JTWJza4ONRL+G1MCw==";
+    /// Uncompressed secp256k1 key (without the 0x04 prefix) of the secret key `[7; 32]`
+    const SECP256K1_KEY: &str = "989c0b76cb563971fdc9bef31ec06c3560f3249d6ee9e5d83c57625596e05f6f631f4d05b3ae518776ee08755a7703e64b2ebc32547504de0b55a142d4ecdf80";
+    /// `personal_sign` of `{"message":"hello"}` by the same key
+    const SECP256K1_SIGNATURE: &str = "6916dee2e8b880c9129ab93e789f8bb8f52be21814cae4a804be8fa3b702c8dc48e05fb44cbca1fa83acf8191b9c722d7968a7405ae256ce6cdd2e31b452d9461c";
+
+    fn raw(signature: Vec<u8>) -> SessionSignature {
+        SessionSignature::Raw(Base64VecU8::from(signature))
+    }
+
+    fn ed25519_signature() -> SessionSignature {
+        raw(base64::engine::general_purpose::STANDARD
+            .decode(ED25519_SIGNATURE)
+            .unwrap())
+    }
+
+    #[test]
+    fn ed25519_signatures_are_bound_to_the_payload() {
+        let session_key: SessionKey = ED25519_KEY.parse().unwrap();
+        assert_eq!(
+            verify_session_signature(
+                br#"{"message":"hello"}"#,
+                &ed25519_signature(),
+                &session_key
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            verify_session_signature(br#"{"message":"bye"}"#, &ed25519_signature(), &session_key),
+            Err(FastAuthError::InvalidSignature)
+        );
+        assert_eq!(
+            verify_session_signature(b"{}", &raw(vec![0; 63]), &session_key),
+            Err(FastAuthError::InvalidSignatureLength)
+        );
+    }
+
+    #[test]
+    fn signatures_must_match_the_key_type() {
+        let secp256k1_key: SessionKey = SessionKey::from(
+            PublicKey::from_parts(CurveType::SECP256K1, hex::decode(SECP256K1_KEY).unwrap())
+                .unwrap(),
+        );
+        let assertion = SessionSignature::WebAuthn(WebAuthnAssertion {
+            authenticator_data: Base64VecU8::from(vec![]),
+            client_data_json: String::new(),
+            signature: Base64VecU8::from(vec![]),
+        });
+        assert_eq!(
+            verify_session_signature(b"{}", &assertion, &secp256k1_key),
+            Err(FastAuthError::SignatureKindMismatch)
+        );
+    }
+
+    #[test]
+    fn secp256k1_personal_sign_is_verified() {
+        let public_key = hex::decode(SECP256K1_KEY).unwrap();
+        let mut signature = hex::decode(SECP256K1_SIGNATURE).unwrap();
+
+        assert_eq!(
+            verify_secp256k1_signature(br#"{"message":"hello"}"#, &signature, &public_key),
+            Ok(true)
+        );
+        assert_eq!(
+            verify_secp256k1_signature(br#"{"message":"goodbye"}"#, &signature, &public_key),
+            Ok(false)
+        );
+
+        // Raw recovery ids are accepted as well as Ethereum-style ones
+        signature[64] -= 27;
+        assert_eq!(
+            verify_secp256k1_signature(br#"{"message":"hello"}"#, &signature, &public_key),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn high_s_secp256k1_signatures_are_rejected() {
+        let public_key = hex::decode(SECP256K1_KEY).unwrap();
+        let signature = hex::decode(SECP256K1_SIGNATURE).unwrap();
+
+        // (r, n - s) with the recovery id flipped recovers the same key
+        let low_s = k256::ecdsa::Signature::from_slice(&signature[..64]).unwrap();
+        let (r, s) = low_s.split_scalars();
+        let high_s = k256::ecdsa::Signature::from_scalars(r, -*s).unwrap();
+        let mut malleated = high_s.to_bytes().to_vec();
+        malleated.push(signature[64] ^ 1);
+
+        assert_eq!(
+            verify_secp256k1_signature(br#"{"message":"hello"}"#, &malleated, &public_key),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn secp256k1_signature_must_be_65_bytes() {
+        assert_eq!(
+            verify_secp256k1_signature(b"{}", &[0u8; 64], &[0u8; 64]),
+            Err(FastAuthError::InvalidSignatureLength)
+        );
+    }
+}
@@ -0,0 +1,168 @@
+// trial_user/perform_actions/solana/call_fn.rs
+use crate::*;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use near_sdk::json_types::U64;
+use near_sdk::PromiseError;
+use perform_actions::solana::message::{compile_message, serialize_transaction, SolanaInstruction};
+
+/// Instruction built by the contract on behalf of the user. Keys are base58 strings.
+#[derive(Clone)]
+#[near(serializers = [json])]
+#[serde(tag = "type")]
+pub enum SolanaAction {
+    SystemTransfer {
+        to: String,
+        lamports: U64,
+    },
+    SplTransferChecked {
+        token_program: String,
+        source: String,
+        mint: String,
+        destination: String,
+        amount: U64,
+        decimals: u8,
+    },
+}
+
+/// Payload a session key signs to have a Solana transaction signed with the user's ed25519 key.
+#[derive(Clone)]
+#[near(serializers = [json])]
+pub struct SolanaPayload {
+    /// Base58 blockhash the transaction is valid for, fetched by the caller
+    pub recent_blockhash: String,
+    pub actions: Vec<SolanaAction>,
+}
+
+#[near]
+impl Contract {
+    /// Signs a Solana transaction paid for and authorized by the user's ed25519 key.
+    /// Resolves to the base64-encoded signed transaction, ready for `sendTransaction`.
+    pub fn sign_solana_transaction(
+        &mut self,
+        signature: SessionSignature,
+        payload: SolanaPayload,
+        session_key: SessionKey,
+        app_id: AppID,
+    ) -> Promise {
+        self.assert_valid_signature(&payload, &signature, &session_key, &app_id);
+
+        let (trial_data, key_usage) = self.assert_trial_active(&session_key, &app_id);
+
+        let bundle: Bundle = self
+            .internal_get_bundle(&key_usage.path)
+            .or_fail(FastAuthError::UserNotFound);
+        let user = bundle.ed25519_key_bytes();
+
+        let constraints = trial_data
+            .solana_constraints
+            .or_fail(FastAuthError::SolanaSigningNotEnabled);
+
+        ensure(
+            !payload.actions.is_empty(),
+            FastAuthError::InvalidSolanaTransaction("No actions given".into()),
+        );
+
+        let mut lamports_sent: u64 = 0;
+        let instructions: Vec<SolanaInstruction> = payload
+            .actions
+            .iter()
+            .map(|action| match action {
+                SolanaAction::SystemTransfer { to, lamports } => {
+                    lamports_sent = lamports_sent.checked_add(lamports.0).or_fail(
+                        FastAuthError::InvalidSolanaTransaction("Lamports overflow".into()),
+                    );
+                    SolanaInstruction::system_transfer(user, parse_pubkey(to, "to"), lamports.0)
+                }
+                SolanaAction::SplTransferChecked {
+                    token_program,
+                    source,
+                    mint,
+                    destination,
+                    amount,
+                    decimals,
+                } => {
+                    let mint = parse_pubkey(mint, "mint");
+                    ensure(
+                        constraints.is_mint_allowed(&mint),
+                        FastAuthError::SolanaMintNotAllowed,
+                    );
+                    SolanaInstruction::spl_transfer_checked(
+                        parse_pubkey(token_program, "token_program"),
+                        parse_pubkey(source, "source"),
+                        mint,
+                        parse_pubkey(destination, "destination"),
+                        user,
+                        amount.0,
+                        *decimals,
+                    )
+                }
+            })
+            .collect();
+
+        for instruction in &instructions {
+            ensure(
+                constraints.is_program_allowed(&instruction.program_id),
+                FastAuthError::SolanaProgramNotAllowed,
+            );
+        }
+        ensure(
+            constraints.is_lamports_allowed(lamports_sent),
+            FastAuthError::SolanaAmountExceedsCap,
+        );
+
+        let message = compile_message(
+            user,
+            &instructions,
+            parse_pubkey(&payload.recent_blockhash, "recent_blockhash"),
+        );
+        ensure(
+            1 + 64 + message.len() <= SOLANA_MAX_TRANSACTION_SIZE,
+            FastAuthError::InvalidSolanaTransaction("Transaction too large".into()),
+        );
+
+        self.request_mpc_eddsa_signature(&message, &bundle.path)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(Gas::from_tgas(10))
+                    .on_sign_solana_txn(message, user),
+            )
+    }
+
+    /// Callback checking the MPC signature against the user's key and
+    /// returning the signed transaction as a base64 string
+    #[private]
+    pub fn on_sign_solana_txn(
+        &self,
+        #[callback_result] call_result: Result<Ed25519SignResult, PromiseError>,
+        message: Vec<u8>,
+        public_key: [u8; 32],
+    ) -> String {
+        match call_result {
+            Ok(sign_result) => {
+                let signature = sign_result.to_bytes();
+                ensure(
+                    env::ed25519_verify(&signature, &message, &public_key),
+                    FastAuthError::InvalidMpcSignature(
+                        "Signature does not match the user's ed25519 key".into(),
+                    ),
+                );
+
+                STANDARD.encode(serialize_transaction(&signature, &message))
+            }
+            Err(_e) => {
+                FastAuthError::MpcSignatureFailed.panic();
+            }
+        }
+    }
+}
+
+fn parse_pubkey(s: &str, field: &str) -> [u8; 32] {
+    bs58::decode(s)
+        .into_vec()
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .unwrap_or_else(|| {
+            FastAuthError::InvalidArgument(format!("Invalid base58 key for {}", field)).panic()
+        })
+}
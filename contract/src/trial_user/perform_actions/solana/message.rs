@@ -0,0 +1,271 @@
+// trial_user/perform_actions/solana/message.rs
+
+/// The system program's id, `11111111111111111111111111111111` in base58
+pub const SYSTEM_PROGRAM_ID: [u8; 32] = [0; 32];
+
+/// Index of `Transfer` in the system program's instruction enum
+const SYSTEM_TRANSFER_INSTRUCTION: u32 = 2;
+
+/// Index of `TransferChecked` in the SPL token program's instruction enum
+const SPL_TRANSFER_CHECKED_INSTRUCTION: u8 = 12;
+
+#[derive(Clone, Debug)]
+pub struct AccountMeta {
+    pub pubkey: [u8; 32],
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct SolanaInstruction {
+    pub program_id: [u8; 32],
+    pub accounts: Vec<AccountMeta>,
+    pub data: Vec<u8>,
+}
+
+impl SolanaInstruction {
+    /// Moves lamports from `from`, which must sign, to `to`.
+    pub fn system_transfer(from: [u8; 32], to: [u8; 32], lamports: u64) -> Self {
+        let mut data = SYSTEM_TRANSFER_INSTRUCTION.to_le_bytes().to_vec();
+        data.extend_from_slice(&lamports.to_le_bytes());
+
+        SolanaInstruction {
+            program_id: SYSTEM_PROGRAM_ID,
+            accounts: vec![account(from, true, true), account(to, false, true)],
+            data,
+        }
+    }
+
+    /// Moves tokens between two token accounts of `mint` owned by `owner`, which must sign.
+    /// `TransferChecked` is used so the program rejects a mismatched mint or decimals.
+    pub fn spl_transfer_checked(
+        token_program: [u8; 32],
+        source: [u8; 32],
+        mint: [u8; 32],
+        destination: [u8; 32],
+        owner: [u8; 32],
+        amount: u64,
+        decimals: u8,
+    ) -> Self {
+        let mut data = vec![SPL_TRANSFER_CHECKED_INSTRUCTION];
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.push(decimals);
+
+        SolanaInstruction {
+            program_id: token_program,
+            accounts: vec![
+                account(source, false, true),
+                account(mint, false, false),
+                account(destination, false, true),
+                account(owner, true, false),
+            ],
+            data,
+        }
+    }
+}
+
+/// Compiles instructions into a legacy transaction message paid for by `fee_payer`.
+///
+/// Accounts are deduplicated and ordered as the runtime expects: writable signers
+/// (the fee payer first), read-only signers, writable non-signers, then read-only non-signers.
+/// Within each group keys keep the order they are first seen in, each program before its
+/// instruction's accounts, as in @solana/web3.js's `compileToLegacyMessage`.
+pub fn compile_message(
+    fee_payer: [u8; 32],
+    instructions: &[SolanaInstruction],
+    recent_blockhash: [u8; 32],
+) -> Vec<u8> {
+    let mut keys: Vec<AccountMeta> = vec![account(fee_payer, true, true)];
+    for instruction in instructions {
+        add_key(&mut keys, &account(instruction.program_id, false, false));
+        for meta in &instruction.accounts {
+            add_key(&mut keys, meta);
+        }
+    }
+    // Stable, so the fee payer stays first among writable signers
+    keys.sort_by_key(|meta| match (meta.is_signer, meta.is_writable) {
+        (true, true) => 0,
+        (true, false) => 1,
+        (false, true) => 2,
+        (false, false) => 3,
+    });
+
+    let num_required_signatures = keys.iter().filter(|meta| meta.is_signer).count();
+    let num_readonly_signed = keys
+        .iter()
+        .filter(|meta| meta.is_signer && !meta.is_writable)
+        .count();
+    let num_readonly_unsigned = keys
+        .iter()
+        .filter(|meta| !meta.is_signer && !meta.is_writable)
+        .count();
+    let index_of = |pubkey: &[u8; 32]| {
+        keys.iter()
+            .position(|meta| meta.pubkey == *pubkey)
+            .expect("Account was added above") as u8
+    };
+
+    let mut message = vec![
+        num_required_signatures as u8,
+        num_readonly_signed as u8,
+        num_readonly_unsigned as u8,
+    ];
+    write_compact_u16(&mut message, keys.len());
+    for meta in &keys {
+        message.extend_from_slice(&meta.pubkey);
+    }
+    message.extend_from_slice(&recent_blockhash);
+
+    write_compact_u16(&mut message, instructions.len());
+    for instruction in instructions {
+        message.push(index_of(&instruction.program_id));
+        write_compact_u16(&mut message, instruction.accounts.len());
+        for meta in &instruction.accounts {
+            message.push(index_of(&meta.pubkey));
+        }
+        write_compact_u16(&mut message, instruction.data.len());
+        message.extend_from_slice(&instruction.data);
+    }
+
+    message
+}
+
+/// Prefixes a message with its single signature, giving the wire format of the transaction.
+pub fn serialize_transaction(signature: &[u8; 64], message: &[u8]) -> Vec<u8> {
+    let mut transaction = Vec::with_capacity(1 + 64 + message.len());
+    write_compact_u16(&mut transaction, 1);
+    transaction.extend_from_slice(signature);
+    transaction.extend_from_slice(message);
+    transaction
+}
+
+fn account(pubkey: [u8; 32], is_signer: bool, is_writable: bool) -> AccountMeta {
+    AccountMeta {
+        pubkey,
+        is_signer,
+        is_writable,
+    }
+}
+
+/// Adds an account, merging its privileges with any earlier entry for the same key
+fn add_key(keys: &mut Vec<AccountMeta>, meta: &AccountMeta) {
+    match keys.iter_mut().find(|key| key.pubkey == meta.pubkey) {
+        Some(key) => {
+            key.is_signer |= meta.is_signer;
+            key.is_writable |= meta.is_writable;
+        }
+        None => keys.push(meta.clone()),
+    }
+}
+
+/// Solana's "shortvec" length prefix
+fn write_compact_u16(encoded: &mut Vec<u8>, value: usize) {
+    let mut value = value as u16;
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            encoded.push(byte);
+            return;
+        }
+        encoded.push(byte | 0x80);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Keys are picked so that web3.js's insertion order, the base58 order of its legacy
+    // `Transaction` and solana-sdk's byte order all agree within each group.
+    const PAYER: [u8; 32] = [3; 32];
+    const BLOCKHASH: [u8; 32] = [7; 32];
+    /// `TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA`
+    const TOKEN_PROGRAM: &str = "06ddf6e1d765a193d9cbe146ceeb79ac1cb485ed5f5b37913a8cf5857eff00a9";
+
+    #[test]
+    fn system_transfer_message() {
+        let message = compile_message(
+            PAYER,
+            &[SolanaInstruction::system_transfer(
+                PAYER, [9; 32], 1_000_000,
+            )],
+            BLOCKHASH,
+        );
+
+        let expected = [
+            "010001",
+            "03",
+            &"03".repeat(32),
+            &"09".repeat(32),
+            &"00".repeat(32),
+            &"07".repeat(32),
+            "01",
+            "02020001",
+            "0c0200000040420f0000000000",
+        ]
+        .concat();
+        assert_eq!(hex::encode(message), expected);
+    }
+
+    #[test]
+    fn spl_transfer_checked_message() {
+        let token_program: [u8; 32] = hex::decode(TOKEN_PROGRAM).unwrap().try_into().unwrap();
+        let message = compile_message(
+            PAYER,
+            &[SolanaInstruction::spl_transfer_checked(
+                token_program,
+                [1; 32],
+                [8; 32],
+                [2; 32],
+                PAYER,
+                2_500_000,
+                6,
+            )],
+            BLOCKHASH,
+        );
+
+        // Owner pays; source and destination are writable; the program and mint read-only
+        let expected = [
+            "010002",
+            "05",
+            &"03".repeat(32),
+            &"01".repeat(32),
+            &"02".repeat(32),
+            TOKEN_PROGRAM,
+            &"08".repeat(32),
+            &"07".repeat(32),
+            "01",
+            "030401040200",
+            "0a0ca02526000000000006",
+        ]
+        .concat();
+        assert_eq!(hex::encode(message), expected);
+    }
+
+    #[test]
+    fn transaction_is_the_signature_count_signature_and_message() {
+        let transaction = serialize_transaction(&[0xab; 64], &[1, 2, 3]);
+
+        let mut expected = vec![1];
+        expected.extend_from_slice(&[0xab; 64]);
+        expected.extend_from_slice(&[1, 2, 3]);
+        assert_eq!(transaction, expected);
+    }
+
+    #[test]
+    fn compact_u16_uses_seven_bits_per_byte() {
+        let cases: [(usize, &[u8]); 5] = [
+            (0, &[0x00]),
+            (0x7f, &[0x7f]),
+            (0x80, &[0x80, 0x01]),
+            (0x3fff, &[0xff, 0x7f]),
+            (0x4000, &[0x80, 0x80, 0x01]),
+        ];
+        for (value, expected) in cases {
+            let mut encoded = Vec::new();
+            write_compact_u16(&mut encoded, value);
+            assert_eq!(encoded, expected);
+        }
+    }
+}
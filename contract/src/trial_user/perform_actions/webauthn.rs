@@ -0,0 +1,258 @@
+// trial_user/perform_actions/webauthn.rs
+use crate::*;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use p256::ecdsa::signature::Verifier;
+use p256::ecdsa::{Signature, VerifyingKey};
+use perform_actions::session_signature::sha256;
+
+/// User-present flag in the authenticator data flags byte
+const WEBAUTHN_FLAG_USER_PRESENT: u8 = 0x01;
+
+/// rpIdHash (32 bytes) + flags (1 byte) + signCount (4 bytes)
+const WEBAUTHN_MIN_AUTHENTICATOR_DATA_LENGTH: usize = 37;
+
+#[near(serializers = [json])]
+struct ClientData {
+    #[serde(rename = "type")]
+    kind: String,
+    challenge: String,
+}
+
+/// Verifies a passkey assertion over a payload for a compressed P-256 public key.
+///
+/// The assertion must be a `webauthn.get` ceremony whose challenge is the base64url
+/// (unpadded) SHA-256 of the payload bytes, which binds the signature to the payload.
+pub(crate) fn verify_webauthn_assertion(
+    payload_bytes: &[u8],
+    assertion: &WebAuthnAssertion,
+    public_key: &[u8],
+) -> bool {
+    let client_data: ClientData = match near_sdk::serde_json::from_str(&assertion.client_data_json)
+    {
+        Ok(client_data) => client_data,
+        Err(_) => return false,
+    };
+    if client_data.kind != "webauthn.get" {
+        return false;
+    }
+
+    let expected_challenge = URL_SAFE_NO_PAD.encode(sha256(payload_bytes));
+    if client_data.challenge != expected_challenge {
+        return false;
+    }
+
+    let authenticator_data = &assertion.authenticator_data.0;
+    if authenticator_data.len() < WEBAUTHN_MIN_AUTHENTICATOR_DATA_LENGTH
+        || authenticator_data[32] & WEBAUTHN_FLAG_USER_PRESENT == 0
+    {
+        return false;
+    }
+
+    // The authenticator signs authenticatorData || SHA-256(clientDataJSON)
+    let mut signed_message = authenticator_data.clone();
+    signed_message.extend_from_slice(&sha256(assertion.client_data_json.as_bytes()));
+
+    let Ok(verifying_key) = VerifyingKey::from_sec1_bytes(public_key) else {
+        return false;
+    };
+    let Some(signature) = der_signature_to_raw(&assertion.signature.0)
+        .and_then(|raw| Signature::from_slice(&raw).ok())
+    else {
+        return false;
+    };
+
+    verifying_key.verify(&signed_message, &signature).is_ok()
+}
+
+/// Converts a DER-encoded ECDSA signature into the 64-byte `r || s` form.
+pub fn der_signature_to_raw(der: &[u8]) -> Option<[u8; 64]> {
+    // SEQUENCE { INTEGER r, INTEGER s }
+    if der.len() < 8 || der[0] != 0x30 || der[1] as usize != der.len() - 2 {
+        return None;
+    }
+
+    let mut raw = [0u8; 64];
+    let mut offset = 2;
+    for half in raw.chunks_mut(32) {
+        if *der.get(offset)? != 0x02 {
+            return None;
+        }
+        let length = *der.get(offset + 1)? as usize;
+        let integer = der.get(offset + 2..offset + 2 + length)?;
+
+        // Strip the sign padding byte and left-pad to 32 bytes
+        let integer = match integer.iter().position(|byte| *byte != 0) {
+            Some(start) => &integer[start..],
+            None => &[],
+        };
+        if integer.len() > 32 {
+            return None;
+        }
+        half[32 - integer.len()..].copy_from_slice(integer);
+
+        offset += 2 + length;
+    }
+
+    (offset == der.len()).then_some(raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::json_types::Base64VecU8;
+
+    // Assertion by the P-256 key below for the relying party example.com, generated
+    // with Python's `cryptography`. The challenge is the base64url SHA-256 of PAYLOAD.
+    const PUBLIC_KEY: &str = "0352a446ac1ea971172622956ee5b11dd910924b9a4eb841da1b082c1011eafbe7";
+    const PAYLOAD: &[u8] = br#"{"action":"add_message","nonce":"1"}"#;
+    // rpIdHash, flags UP | UV, signCount 1
+    const AUTHENTICATOR_DATA: &str = "o3mm9u6vuaVeN4wRgDTidR5oL6ufLTCrE9ISVYbOGUcFAAAAAQ==";
+    const CLIENT_DATA_JSON: &str = r#"{"type":"webauthn.get","challenge":"I8dqVBWkPd1ooQQnG9I6c3vaAak0gEqTVxH7apVAi1Q","origin":"https://example.com","crossOrigin":false}"#;
+    const SIGNATURE: &str = "304502204346eaa6cc1d8da25397be392d6943b7847108c301f750f90e125c5bd905eb0d0221008c130bf9e32573384511d1dcf12576c90337db5b54c21adfdb5825c76ee82b0a";
+
+    fn assertion(
+        authenticator_data: &str,
+        client_data_json: &str,
+        signature: &str,
+    ) -> WebAuthnAssertion {
+        WebAuthnAssertion {
+            authenticator_data: Base64VecU8::from(
+                base64::engine::general_purpose::STANDARD
+                    .decode(authenticator_data)
+                    .unwrap(),
+            ),
+            client_data_json: client_data_json.to_string(),
+            signature: Base64VecU8::from(hex::decode(signature).unwrap()),
+        }
+    }
+
+    fn assertion_with_authenticator_data(authenticator_data: &[u8]) -> WebAuthnAssertion {
+        let mut assertion = assertion(AUTHENTICATOR_DATA, CLIENT_DATA_JSON, SIGNATURE);
+        assertion.authenticator_data = Base64VecU8::from(authenticator_data.to_vec());
+        assertion
+    }
+
+    fn verify(payload: &[u8], assertion: &WebAuthnAssertion) -> bool {
+        verify_webauthn_assertion(payload, assertion, &hex::decode(PUBLIC_KEY).unwrap())
+    }
+
+    #[test]
+    fn passkey_assertion_is_accepted() {
+        let assertion = assertion(AUTHENTICATOR_DATA, CLIENT_DATA_JSON, SIGNATURE);
+        assert!(verify(PAYLOAD, &assertion));
+    }
+
+    #[test]
+    fn assertion_for_another_payload_is_rejected() {
+        let assertion = assertion(AUTHENTICATOR_DATA, CLIENT_DATA_JSON, SIGNATURE);
+        assert!(!verify(
+            br#"{"action":"add_message","nonce":"2"}"#,
+            &assertion
+        ));
+    }
+
+    #[test]
+    fn registration_ceremonies_are_rejected() {
+        // Validly signed, but by a `webauthn.create` ceremony
+        let assertion = assertion(
+            AUTHENTICATOR_DATA,
+            r#"{"type":"webauthn.create","challenge":"I8dqVBWkPd1ooQQnG9I6c3vaAak0gEqTVxH7apVAi1Q","origin":"https://example.com","crossOrigin":false}"#,
+            "304402203e7b402576919581e7e92ac167267396a45e7f3fea7379a64a936238123be7570220369cb8be71cbbd92ef9e67e19aef653abd9611250ad476f188e83b078959f484",
+        );
+        assert!(!verify(PAYLOAD, &assertion));
+    }
+
+    #[test]
+    fn user_presence_is_required() {
+        // Validly signed, but with only the UV flag set
+        let assertion = assertion(
+            "o3mm9u6vuaVeN4wRgDTidR5oL6ufLTCrE9ISVYbOGUcEAAAAAQ==",
+            CLIENT_DATA_JSON,
+            "304402207c73e99b76dcb2e4e9ef4bc31fc580aa9075ccfa0bf7ec1b4f113b5a5a5d20f1022002ea9ec383dee7bf9d5c19da7a43bf2e5b23736105f6f155f3ab2222eda8ce99",
+        );
+        assert!(!verify(PAYLOAD, &assertion));
+
+        let truncated = assertion_with_authenticator_data(&[0x05; 36]);
+        assert!(!verify(PAYLOAD, &truncated));
+    }
+
+    #[test]
+    fn tampered_assertions_are_rejected() {
+        // Flipped bit in r
+        let mut tampered = assertion(AUTHENTICATOR_DATA, CLIENT_DATA_JSON, SIGNATURE);
+        tampered.signature.0[10] ^= 0x01;
+        assert!(!verify(PAYLOAD, &tampered));
+
+        // Changed signCount
+        let mut authenticator_data = assertion(AUTHENTICATOR_DATA, CLIENT_DATA_JSON, SIGNATURE)
+            .authenticator_data
+            .0;
+        authenticator_data[36] = 2;
+        assert!(!verify(
+            PAYLOAD,
+            &assertion_with_authenticator_data(&authenticator_data)
+        ));
+
+        // Another origin
+        let other_origin = assertion(
+            AUTHENTICATOR_DATA,
+            &CLIENT_DATA_JSON.replace("example.com", "evil.com"),
+            SIGNATURE,
+        );
+        assert!(!verify(PAYLOAD, &other_origin));
+
+        // Another key
+        let assertion = assertion(AUTHENTICATOR_DATA, CLIENT_DATA_JSON, SIGNATURE);
+        let other_key =
+            hex::decode("036b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296")
+                .unwrap();
+        assert!(!verify_webauthn_assertion(PAYLOAD, &assertion, &other_key));
+    }
+
+    #[test]
+    fn der_integers_are_left_padded_to_32_bytes() {
+        let raw = der_signature_to_raw(&hex::decode("3006020101020102").unwrap()).unwrap();
+        let mut expected = [0u8; 64];
+        expected[31] = 1;
+        expected[63] = 2;
+        assert_eq!(raw, expected);
+    }
+
+    #[test]
+    fn der_sign_padding_is_stripped() {
+        // s has its high bit set, so it's encoded on 33 bytes with a leading zero
+        let raw = der_signature_to_raw(&hex::decode(SIGNATURE).unwrap()).unwrap();
+        assert_eq!(
+            hex::encode(raw),
+            "4346eaa6cc1d8da25397be392d6943b7847108c301f750f90e125c5bd905eb0d\
+             8c130bf9e32573384511d1dcf12576c90337db5b54c21adfdb5825c76ee82b0a"
+        );
+    }
+
+    #[test]
+    fn malformed_der_is_rejected() {
+        for der in [
+            // 33-byte integer without a padding byte
+            format!("3028022101{}020102", "11".repeat(32)),
+            // Trailing byte inside the sequence
+            "300702010102010200".to_string(),
+            // Trailing byte after the sequence
+            "300602010102010200".to_string(),
+            // Integer running past the end
+            "3006020101020502".to_string(),
+            // Not a sequence of integers
+            "3106020101020102".to_string(),
+            "3006040101020102".to_string(),
+            // Missing s
+            "3003020101".to_string(),
+        ] {
+            assert_eq!(
+                der_signature_to_raw(&hex::decode(&der).unwrap()),
+                None,
+                "accepted {}",
+                der
+            );
+        }
+    }
+}
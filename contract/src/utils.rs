@@ -1,6 +1,7 @@
 // utils.rs
 
 use crate::*;
+use ethereum_types::U256;
 use near_sdk::{env, CurveType, PublicKey};
 use omni_transaction::{
     evm::types::Address,
@@ -9,6 +10,7 @@ use omni_transaction::{
         Secp256K1PublicKey as OmniSECP256K1PublicKey,
     },
 };
+use perform_actions::session_signature::keccak256;
 use sha2::{Digest, Sha256};
 
 /// Converts a NEAR `PublicKey` to an OmniTransaction `PublicKey`.
@@ -63,20 +65,27 @@ pub fn hash_payload(payload: &[u8]) -> [u8; 32] {
     result.into() // Converts the resulting hash into a [u8; 32] array
 }
 
-/// Creates a sign request from a hashed payload and public key.
-pub fn create_sign_request_from_transaction(
-    hashed_payload: [u8; 32],
-    path: &String,
-) -> serde_json::Value {
-    // Create the sign request with the hashed payload
-    let sign_request = SignRequest {
-        payload: hashed_payload.to_vec(), // Convert [u8; 32] to Vec<u8>
-        path: path.clone(),
-        key_version: 0, // Modify this as needed
-    };
-
-    // Wrap the sign request into the expected structure
-    serde_json::json!({ "request": sign_request })
+/// Hashes a message the way `personal_sign` does (EIP-191 version 0x45).
+pub fn eip191_hash(message: &[u8]) -> [u8; 32] {
+    let mut prefixed = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+    prefixed.extend_from_slice(message);
+    keccak256(&prefixed)
+}
+
+/// Maps an ECDSA `s` to the lower half of the secp256k1 order, as Ethereum and Bitcoin
+/// require. Returns the 32-byte `s` and whether it was negated.
+pub fn normalize_low_s(s: &[u8]) -> (Vec<u8>, bool) {
+    let order = U256::from_str_radix(SECP256K1_ORDER, 16).expect("Invalid curve order");
+    let s_value = U256::from_big_endian(s);
+    let negated = s_value > order / 2;
+
+    let mut s_bytes = [0u8; 32];
+    if negated {
+        (order - s_value).to_big_endian(&mut s_bytes);
+    } else {
+        s_value.to_big_endian(&mut s_bytes);
+    }
+    (s_bytes.to_vec(), negated)
 }
 
 // Utility function to convert an Ethereum address to a normalized hex string
@@ -84,6 +93,18 @@ pub fn convert_address_to_hex_string(address: &Address) -> String {
     format!("0x{}", hex::encode(address))
 }
 
+/// Clones one page of entries out of a collection iterator.
+pub fn paginate<'a, T: Clone + 'a>(
+    iter: impl Iterator<Item = &'a T>,
+    from_index: Option<u32>,
+    limit: Option<u32>,
+) -> Vec<T> {
+    iter.skip(from_index.unwrap_or(0) as usize)
+        .take(limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize)
+        .cloned()
+        .collect()
+}
+
 #[near]
 impl Contract {
     pub(crate) fn adjust_deposit(&self, initial_storage: u64, final_storage: u64) {
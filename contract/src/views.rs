@@ -3,13 +3,38 @@ use crate::*;
 #[near]
 impl Contract {
     /// View function to get key usage by public key
-    pub fn get_key_usage(&self, public_key: PublicKey) -> Option<KeyUsage> {
-        self.key_usage_by_pk.get(&public_key).cloned()
+    pub fn get_key_usage(&self, public_key: SessionKey) -> Option<KeyUsage> {
+        self.internal_get_key_usage(&public_key)
     }
 
     /// View function to get a user's bundle
     pub fn get_bundle(&self, path: MpcPath) -> Option<Bundle> {
-        self.bundler.get(&path).cloned()
+        self.internal_get_bundle(&path)
+    }
+
+    /// View function to get a user's bundle from the eth-implicit account that transacts for them
+    pub fn get_bundle_by_eth_address(&self, eth_address: AccountId) -> Option<Bundle> {
+        self.paths_by_eth_address
+            .get(&eth_address)
+            .and_then(|path| self.internal_get_bundle(path))
+    }
+
+    /// View function to get the bundle of the user a session key belongs to
+    pub fn get_bundle_by_session_key(&self, public_key: SessionKey) -> Option<Bundle> {
+        self.internal_get_key_usage(&public_key)
+            .and_then(|key_usage| self.internal_get_bundle(&key_usage.path))
+    }
+
+    /// View function to get the named NEAR account linked to a user
+    pub fn get_near_account(&self, path: MpcPath) -> Option<AccountId> {
+        self.near_accounts.get(&path).cloned()
+    }
+
+    /// View function to get a user's bundle from their named NEAR account
+    pub fn get_bundle_by_near_account(&self, account_id: AccountId) -> Option<Bundle> {
+        self.paths_by_near_account
+            .get(&account_id)
+            .and_then(|path| self.internal_get_bundle(path))
     }
 
     /// View function to get a user's app balance
@@ -19,4 +44,137 @@ impl Contract {
             .cloned()
             .unwrap_or(NearToken::from_yoctonear(0))
     }
+
+    /// View function to get the policies an app's session keys are held to
+    pub fn get_trial_data(&self, app_id: AppID) -> Option<TrialData> {
+        self.internal_get_trial_data(&app_id)
+    }
+
+    /// View function to get how the contract calls the MPC signer
+    pub fn get_mpc_config(&self) -> MpcConfig {
+        self.mpc_config.clone()
+    }
+
+    /// View function to get the MPC signer contract
+    pub fn get_mpc_contract(&self) -> AccountId {
+        self.mpc_contract.clone()
+    }
+
+    /// View function to get the account named accounts are created under
+    pub fn get_named_account_parent(&self) -> AccountId {
+        self.named_account_parent
+            .clone()
+            .unwrap_or_else(env::current_account_id)
+    }
+
+    /// View function to get the contract owner
+    pub fn get_owner(&self) -> AccountId {
+        self.owner_id.clone()
+    }
+
+    /// View function to check whether signing is halted globally
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// View function to check whether an app is paused
+    pub fn is_app_paused(&self, app_id: AppID) -> bool {
+        self.paused_apps.contains(&app_id)
+    }
+
+    /// View function to check whether a user is frozen
+    pub fn is_user_frozen(&self, path: MpcPath) -> bool {
+        self.frozen_users.contains(&path)
+    }
+
+    /// View function to get the version of the stored contract state
+    pub fn get_state_version(&self) -> String {
+        format!("{:?}", StateVersion::read())
+    }
+
+    /// View function to list activated users' bundles
+    pub fn get_bundles(&self, from_index: Option<u32>, limit: Option<u32>) -> Vec<Bundle> {
+        paginate(self.paths.iter(), from_index, limit)
+            .iter()
+            .filter_map(|path| self.internal_get_bundle(path))
+            .collect()
+    }
+
+    /// View function to get the number of activated users
+    pub fn get_total_bundles(&self) -> u32 {
+        self.paths.len()
+    }
+
+    /// View function to list the session keys a user holds across apps
+    pub fn get_session_keys_for_path(
+        &self,
+        path: MpcPath,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<SessionKeyInfo> {
+        let Some(apps) = self.apps_by_path.get(&path) else {
+            return vec![];
+        };
+
+        paginate(apps.iter(), from_index, limit)
+            .into_iter()
+            .filter_map(|app_id| {
+                self.session_keys
+                    .get(&(path.clone(), app_id.clone()))
+                    .map(|public_key| SessionKeyInfo {
+                        app_id,
+                        public_key: public_key.clone(),
+                    })
+            })
+            .collect()
+    }
+
+    /// View function to get the number of session keys a user holds
+    pub fn get_total_session_keys_for_path(&self, path: MpcPath) -> u32 {
+        self.apps_by_path
+            .get(&path)
+            .map(|apps| apps.len())
+            .unwrap_or(0)
+    }
+
+    /// View function to list the users holding a session key for an app
+    pub fn get_app_users(
+        &self,
+        app_id: AppID,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<MpcPath> {
+        self.paths_by_app
+            .get(&app_id)
+            .map(|paths| paginate(paths.iter(), from_index, limit))
+            .unwrap_or_default()
+    }
+
+    /// View function to get the number of users holding a session key for an app
+    pub fn get_total_app_users(&self, app_id: AppID) -> u32 {
+        self.paths_by_app
+            .get(&app_id)
+            .map(|paths| paths.len())
+            .unwrap_or(0)
+    }
+
+    /// View function to list every app that has deposited or been issued session keys
+    pub fn get_apps(&self, from_index: Option<u32>, limit: Option<u32>) -> Vec<AppID> {
+        paginate(self.apps.iter(), from_index, limit)
+    }
+
+    /// View function to get the number of apps
+    pub fn get_total_apps(&self) -> u32 {
+        self.apps.len()
+    }
+
+    /// View function to get a supported EVM chain and its caps
+    pub fn get_evm_chain(&self, chain_id: u64) -> Option<EvmChain> {
+        self.evm_chains.get(&chain_id).cloned()
+    }
+
+    /// View function to list the supported EVM chains
+    pub fn get_evm_chains(&self, from_index: Option<u32>, limit: Option<u32>) -> Vec<EvmChain> {
+        paginate(self.evm_chains.values(), from_index, limit)
+    }
 }
@@ -41,6 +41,7 @@ export async function deployFastAuthContract(
     wasmPath: wasmFilePath,
     methodName: "new",
     args: {
+      owner_id: signerAccount.accountId,
       mpc_contract: mpcContractId,
       oracle_account_id: oracleAccountId,
     },
@@ -0,0 +1,292 @@
+//! Sandbox harness shared by the integration tests.
+//!
+//! The tests run against wasm built by `contract/build.sh`, the FastAuth contract plus
+//! the mock MPC signer and the mock eth-implicit wallet. `near-workspaces` downloads
+//! the `near-sandbox` binary on the first run unless `NEAR_SANDBOX_BIN_PATH` points at
+//! one, which is the only network access the tests need.
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ed25519_dalek::{Signer, SigningKey};
+use near_workspaces::network::Sandbox;
+use near_workspaces::result::ExecutionFinalResult;
+use near_workspaces::types::{AccountDetailsPatch, Gas, NearToken};
+use near_workspaces::{Account, AccountId, Contract, Worker};
+use serde::Serialize;
+use serde_json::json;
+use sha3::{Digest, Keccak256};
+use std::path::PathBuf;
+
+pub const APP_ID: &str = "sandbox-app";
+
+/// A deployed FastAuth contract with its MPC signer and oracle.
+pub struct TestEnv {
+    pub worker: Worker<Sandbox>,
+    pub root: Account,
+    pub oracle: Account,
+    pub fastauth: Contract,
+    pub mpc: Contract,
+    wallet_wasm: Vec<u8>,
+}
+
+/// An activated user and the session key their app holds.
+pub struct TestUser {
+    pub path: String,
+    pub mpc_key: String,
+    pub eth_address: AccountId,
+    pub session_key: SigningKey,
+}
+
+/// Mirror of the contract's `NearAction`. Field order matters: the session key signs
+/// the payload exactly as the contract re-serializes it.
+#[derive(Serialize)]
+pub enum NearAction {
+    FunctionCall {
+        contract_id: AccountId,
+        method_name: String,
+        args: Vec<u8>,
+        gas: Gas,
+        deposit: NearToken,
+    },
+    Transfer {
+        receiver_id: AccountId,
+        amount: NearToken,
+    },
+}
+
+/// Mirror of the contract's `NearPayload`.
+#[derive(Serialize)]
+pub struct NearPayload {
+    pub action: NearAction,
+    pub nonce: String,
+}
+
+fn read_wasm(name: &str) -> Result<Vec<u8>> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../out")
+        .join(name);
+    std::fs::read(&path).with_context(|| {
+        format!(
+            "Missing {}. Run contract/build.sh before the sandbox tests",
+            path.display()
+        )
+    })
+}
+
+impl TestEnv {
+    /// Starts a sandbox with the MPC mock and an initialized FastAuth contract.
+    pub async fn init() -> Result<Self> {
+        let worker = near_workspaces::sandbox().await?;
+        let root = worker.root_account()?;
+
+        let oracle = root
+            .create_subaccount("oracle")
+            .initial_balance(NearToken::from_near(20))
+            .transact()
+            .await?
+            .into_result()?;
+
+        let mpc = root
+            .create_subaccount("mpc")
+            .initial_balance(NearToken::from_near(10))
+            .transact()
+            .await?
+            .into_result()?
+            .deploy(&read_wasm("mock_mpc.wasm")?)
+            .await?
+            .into_result()?;
+
+        let fastauth = root
+            .create_subaccount("fastauth")
+            .initial_balance(NearToken::from_near(50))
+            .transact()
+            .await?
+            .into_result()?
+            .deploy(&read_wasm("fastauth.wasm")?)
+            .await?
+            .into_result()?;
+        fastauth
+            .call("new")
+            .args_json(json!({
+                "owner_id": root.id(),
+                "oracle_account_id": oracle.id(),
+                "mpc_contract": mpc.id(),
+            }))
+            .transact()
+            .await?
+            .into_result()?;
+
+        Ok(TestEnv {
+            worker,
+            root,
+            oracle,
+            fastauth,
+            mpc,
+            wallet_wasm: read_wasm("mock_wallet.wasm")?,
+        })
+    }
+
+    /// Eth-implicit account and MPC key the mock signer derives for `path`.
+    pub async fn derive_user_key(&self, path: &str) -> Result<(String, AccountId)> {
+        let mpc_key: String = self
+            .mpc
+            .view("derived_public_key")
+            .args_json(json!({ "path": path, "predecessor": self.fastauth.id() }))
+            .await?
+            .json()?;
+
+        let key_data = bs58::decode(mpc_key.trim_start_matches("secp256k1:")).into_vec()?;
+        let hash = Keccak256::digest(&key_data);
+        let eth_address = format!("0x{}", hex_encode(&hash[12..])).parse()?;
+
+        Ok((mpc_key, eth_address))
+    }
+
+    /// Puts the mock wallet on the user's eth-implicit account, where the
+    /// NEAR-EVM wallet contract would live.
+    pub async fn deploy_wallet(&self, eth_address: &AccountId) -> Result<()> {
+        self.worker
+            .patch(eth_address)
+            .account(AccountDetailsPatch::default().balance(NearToken::from_near(1)))
+            .code(&self.wallet_wasm)
+            .transact()
+            .await?;
+        Ok(())
+    }
+
+    pub async fn activate(&self, path: &str) -> Result<ExecutionFinalResult> {
+        let (mpc_key, eth_address) = self.derive_user_key(path).await?;
+
+        Ok(self
+            .oracle
+            .call(self.fastauth.id(), "activate_account")
+            .args_json(json!({
+                "mpc_key": mpc_key,
+                "eth_address": eth_address,
+                "path": path,
+            }))
+            .deposit(NearToken::from_millinear(100))
+            .max_gas()
+            .transact()
+            .await?)
+    }
+
+    pub async fn add_session_key(
+        &self,
+        path: &str,
+        session_key: &SigningKey,
+    ) -> Result<ExecutionFinalResult> {
+        Ok(self
+            .oracle
+            .call(self.fastauth.id(), "add_session_key")
+            .args_json(json!({
+                "public_key": session_public_key(session_key),
+                "path": path,
+                "app_id": APP_ID,
+            }))
+            .deposit(NearToken::from_millinear(100))
+            .max_gas()
+            .transact()
+            .await?)
+    }
+
+    /// Activates a user with a wallet on their eth-implicit account and a session key for `APP_ID`.
+    pub async fn create_user(&self, path: &str, seed: u8) -> Result<TestUser> {
+        let (mpc_key, eth_address) = self.derive_user_key(path).await?;
+        self.deploy_wallet(&eth_address).await?;
+        self.activate(path).await?.into_result()?;
+
+        let session_key = SigningKey::from_bytes(&[seed; 32]);
+        self.add_session_key(path, &session_key)
+            .await?
+            .into_result()?;
+
+        Ok(TestUser {
+            path: path.to_string(),
+            mpc_key,
+            eth_address,
+            session_key,
+        })
+    }
+
+    pub async fn deposit(&self, amount: NearToken) -> Result<()> {
+        self.root
+            .call(self.fastauth.id(), "deposit")
+            .args_json(json!({ "app_id": APP_ID }))
+            .deposit(amount)
+            .transact()
+            .await?
+            .into_result()?;
+        Ok(())
+    }
+
+    pub async fn app_balance(&self) -> Result<NearToken> {
+        Ok(self
+            .fastauth
+            .view("get_app_balance")
+            .args_json(json!({ "app_id": APP_ID }))
+            .await?
+            .json()?)
+    }
+
+    /// Submits `payload` signed by `signer` on behalf of `user`, as a relayer would.
+    pub async fn execute_near_action(
+        &self,
+        user: &TestUser,
+        signer: &SigningKey,
+        payload: &NearPayload,
+    ) -> Result<ExecutionFinalResult> {
+        Ok(self
+            .root
+            .call(self.fastauth.id(), "execute_near_action")
+            .args_json(json!({
+                "signature": sign_payload(signer, payload)?,
+                "payload": payload,
+                "session_key": session_public_key(&user.session_key),
+                "app_id": APP_ID,
+            }))
+            .max_gas()
+            .transact()
+            .await?)
+    }
+
+    /// `rlp_execute` calls received by the user's mock wallet
+    pub async fn wallet_executions(&self, user: &TestUser) -> Result<Vec<serde_json::Value>> {
+        Ok(self
+            .worker
+            .view(&user.eth_address, "get_executions")
+            .await?
+            .json()?)
+    }
+}
+
+pub fn session_public_key(key: &SigningKey) -> String {
+    format!(
+        "ed25519:{}",
+        bs58::encode(key.verifying_key().to_bytes()).into_string()
+    )
+}
+
+/// Base64 ed25519 signature over the payload's JSON serialization
+pub fn sign_payload(key: &SigningKey, payload: &impl Serialize) -> Result<String> {
+    let payload_bytes = serde_json::to_vec(payload)?;
+    Ok(STANDARD.encode(key.sign(&payload_bytes).to_bytes()))
+}
+
+/// Asserts the transaction failed with the given `FASTAUTH_ERR` code.
+pub fn assert_fastauth_error(outcome: ExecutionFinalResult, code: u32) {
+    let failure = outcome
+        .into_result()
+        .expect_err("Transaction should have failed");
+    let expected = format!("FASTAUTH_ERR:{}:", code);
+    assert!(
+        format!("{:?}", failure).contains(&expected),
+        "Expected {}, got {:?}",
+        expected,
+        failure
+    );
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,81 @@
+use integration_tests::*;
+use near_workspaces::types::NearToken;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn activate_account_stores_bundle() -> anyhow::Result<()> {
+    let env = TestEnv::init().await?;
+    let (mpc_key, eth_address) = env.derive_user_key("alice").await?;
+
+    env.activate("alice").await?.into_result()?;
+
+    let bundle: Value = env
+        .fastauth
+        .view("get_bundle")
+        .args_json(json!({ "path": "alice" }))
+        .await?
+        .json()?;
+    assert_eq!(bundle["mpc_key"], mpc_key);
+    assert_eq!(bundle["eth_address"], eth_address.as_str());
+
+    let by_eth_address: Value = env
+        .fastauth
+        .view("get_bundle_by_eth_address")
+        .args_json(json!({ "eth_address": eth_address }))
+        .await?
+        .json()?;
+    assert_eq!(by_eth_address["path"], "alice");
+
+    // The eth-implicit account is funded so it exists on chain
+    let wallet = env.worker.view_account(&eth_address).await?;
+    assert!(!wallet.balance.is_zero());
+    Ok(())
+}
+
+#[tokio::test]
+async fn activate_account_refunds_excess_deposit() -> anyhow::Result<()> {
+    let env = TestEnv::init().await?;
+    let balance_before = env.oracle.view_account().await?.balance;
+
+    env.activate("alice").await?.into_result()?;
+
+    // 0.1 NEAR is attached; only storage and gas should be kept
+    let spent = balance_before.saturating_sub(env.oracle.view_account().await?.balance);
+    assert!(
+        spent < NearToken::from_millinear(20),
+        "Oracle spent {} activating",
+        spent
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn activate_account_rejects_non_oracle() -> anyhow::Result<()> {
+    let env = TestEnv::init().await?;
+    let (mpc_key, eth_address) = env.derive_user_key("alice").await?;
+
+    let outcome = env
+        .root
+        .call(env.fastauth.id(), "activate_account")
+        .args_json(json!({
+            "mpc_key": mpc_key,
+            "eth_address": eth_address,
+            "path": "alice",
+        }))
+        .deposit(NearToken::from_millinear(100))
+        .max_gas()
+        .transact()
+        .await?;
+
+    assert_fastauth_error(outcome, 1001);
+    Ok(())
+}
+
+#[tokio::test]
+async fn activate_account_rejects_duplicate_path() -> anyhow::Result<()> {
+    let env = TestEnv::init().await?;
+    env.activate("alice").await?.into_result()?;
+
+    assert_fastauth_error(env.activate("alice").await?, 1002);
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use ed25519_dalek::SigningKey;
+use integration_tests::*;
+use near_workspaces::types::{Gas, NearToken};
+
+fn transfer(receiver_id: &str, amount: NearToken) -> anyhow::Result<NearPayload> {
+    Ok(NearPayload {
+        action: NearAction::Transfer {
+            receiver_id: receiver_id.parse()?,
+            amount,
+        },
+        nonce: "0".to_string(),
+    })
+}
+
+#[tokio::test]
+async fn transfer_is_signed_and_relayed_to_wallet() -> anyhow::Result<()> {
+    let env = TestEnv::init().await?;
+    let user = env.create_user("alice", 1).await?;
+    env.deposit(NearToken::from_near(1)).await?;
+
+    let payload = transfer("bob.test.near", NearToken::from_millinear(100))?;
+    env.execute_near_action(&user, &user.session_key, &payload)
+        .await?
+        .into_result()?;
+
+    let executions = env.wallet_executions(&user).await?;
+    assert_eq!(executions.len(), 1);
+    assert_eq!(executions[0]["target"], "bob.test.near");
+    assert_eq!(
+        executions[0]["deposit"],
+        NearToken::from_millinear(100).as_yoctonear().to_string()
+    );
+
+    // The transferred amount comes out of the app's balance
+    assert_eq!(env.app_balance().await?, NearToken::from_millinear(900));
+
+    let sign_count: u64 = env.mpc.view("get_sign_count").await?.json()?;
+    assert_eq!(sign_count, 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn function_call_is_signed_and_relayed_to_wallet() -> anyhow::Result<()> {
+    let env = TestEnv::init().await?;
+    let user = env.create_user("alice", 1).await?;
+
+    let payload = NearPayload {
+        action: NearAction::FunctionCall {
+            contract_id: "guestbook.test.near".parse()?,
+            method_name: "add_message".to_string(),
+            args: br#"{"text":"hello"}"#.to_vec(),
+            gas: Gas::from_tgas(30),
+            deposit: NearToken::from_yoctonear(0),
+        },
+        nonce: "0".to_string(),
+    };
+    env.execute_near_action(&user, &user.session_key, &payload)
+        .await?
+        .into_result()?;
+
+    let executions = env.wallet_executions(&user).await?;
+    assert_eq!(executions.len(), 1);
+    assert_eq!(executions[0]["target"], "guestbook.test.near");
+    assert!(!executions[0]["tx_bytes_b64"].as_str().unwrap().is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn rejects_payload_signed_by_another_key() -> anyhow::Result<()> {
+    let env = TestEnv::init().await?;
+    let user = env.create_user("alice", 1).await?;
+
+    let payload = transfer("bob.test.near", NearToken::from_yoctonear(0))?;
+    let outcome = env
+        .execute_near_action(&user, &SigningKey::from_bytes(&[9; 32]), &payload)
+        .await?;
+
+    assert_fastauth_error(outcome, 2001);
+    assert!(env.wallet_executions(&user).await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn rejects_revoked_session_key() -> anyhow::Result<()> {
+    let env = TestEnv::init().await?;
+    let user = env.create_user("alice", 1).await?;
+
+    env.root
+        .call(env.fastauth.id(), "revoke_session_key")
+        .args_json(serde_json::json!({
+            "public_key": session_public_key(&user.session_key),
+        }))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let payload = transfer("bob.test.near", NearToken::from_yoctonear(0))?;
+    let outcome = env
+        .execute_near_action(&user, &user.session_key, &payload)
+        .await?;
+
+    assert_fastauth_error(outcome, 1006);
+    Ok(())
+}
+
+#[tokio::test]
+async fn rejects_transfer_beyond_app_balance() -> anyhow::Result<()> {
+    let env = TestEnv::init().await?;
+    let user = env.create_user("alice", 1).await?;
+    env.deposit(NearToken::from_millinear(100)).await?;
+
+    let payload = transfer("bob.test.near", NearToken::from_near(1))?;
+    let outcome = env
+        .execute_near_action(&user, &user.session_key, &payload)
+        .await?;
+
+    assert_fastauth_error(outcome, 3001);
+    // Nothing was debited or relayed
+    assert_eq!(env.app_balance().await?, NearToken::from_millinear(100));
+    assert!(env.wallet_executions(&user).await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn paused_contract_rejects_actions() -> anyhow::Result<()> {
+    let env = TestEnv::init().await?;
+    let user = env.create_user("alice", 1).await?;
+
+    env.root
+        .call(env.fastauth.id(), "pause")
+        .transact()
+        .await?
+        .into_result()?;
+
+    let payload = transfer("bob.test.near", NearToken::from_yoctonear(0))?;
+    let outcome = env
+        .execute_near_action(&user, &user.session_key, &payload)
+        .await?;
+
+    assert_fastauth_error(outcome, 4008);
+    Ok(())
+}
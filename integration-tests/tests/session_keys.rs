@@ -0,0 +1,79 @@
+use ed25519_dalek::SigningKey;
+use integration_tests::*;
+use serde_json::{json, Value};
+
+async fn key_usage(env: &TestEnv, key: &SigningKey) -> anyhow::Result<Value> {
+    Ok(env
+        .fastauth
+        .view("get_key_usage")
+        .args_json(json!({ "public_key": session_public_key(key) }))
+        .await?
+        .json()?)
+}
+
+#[tokio::test]
+async fn add_session_key_tracks_usage() -> anyhow::Result<()> {
+    let env = TestEnv::init().await?;
+    let user = env.create_user("alice", 1).await?;
+
+    let usage = key_usage(&env, &user.session_key).await?;
+    assert_eq!(usage["path"], "alice");
+    assert_eq!(usage["app_id"], APP_ID);
+    assert_eq!(usage["usage_stats"]["total_interactions"], 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_session_key_replaces_previous_key_for_app() -> anyhow::Result<()> {
+    let env = TestEnv::init().await?;
+    let user = env.create_user("alice", 1).await?;
+
+    let new_key = SigningKey::from_bytes(&[2; 32]);
+    env.add_session_key("alice", &new_key)
+        .await?
+        .into_result()?;
+
+    assert!(key_usage(&env, &user.session_key).await?.is_null());
+    assert_eq!(key_usage(&env, &new_key).await?["path"], "alice");
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_session_key_rejects_non_oracle() -> anyhow::Result<()> {
+    let env = TestEnv::init().await?;
+    env.activate("alice").await?.into_result()?;
+
+    let outcome = env
+        .root
+        .call(env.fastauth.id(), "add_session_key")
+        .args_json(json!({
+            "public_key": session_public_key(&SigningKey::from_bytes(&[1; 32])),
+            "path": "alice",
+            "app_id": APP_ID,
+        }))
+        .max_gas()
+        .transact()
+        .await?;
+
+    assert_fastauth_error(outcome, 1001);
+    Ok(())
+}
+
+#[tokio::test]
+async fn frozen_user_cannot_get_session_keys() -> anyhow::Result<()> {
+    let env = TestEnv::init().await?;
+    env.activate("alice").await?.into_result()?;
+
+    env.root
+        .call(env.fastauth.id(), "freeze_user")
+        .args_json(json!({ "path": "alice" }))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let outcome = env
+        .add_session_key("alice", &SigningKey::from_bytes(&[1; 32]))
+        .await?;
+    assert_fastauth_error(outcome, 1004);
+    Ok(())
+}
@@ -0,0 +1,147 @@
+//! Stand-in for the MPC signer used by the sandbox tests.
+//!
+//! Keys are derived like the real signer does, by adding an epsilon computed from the
+//! caller and path to a root key, but the root key is a fixed constant so signatures
+//! are reproducible offline. Anyone can compute its keys: never deploy it anywhere real.
+use k256::ecdsa::SigningKey;
+use k256::elliptic_curve::ops::Reduce;
+use k256::U256;
+use near_sdk::{env, near, require, AccountId, CurveType, NearToken, PublicKey};
+use sha3::{Digest, Sha3_256};
+
+/// Prefix of the string hashed into a derivation epsilon, as used by the real signer
+const EPSILON_DERIVATION_PREFIX: &str = "near-mpc-recovery v0.1.0 epsilon derivation:";
+
+/// Seed of the mock's root secret key
+const ROOT_KEY_SEED: &[u8] = b"fastauth mock mpc root key";
+
+/// Payload of a V2 request, tagged by signature scheme
+#[near(serializers = [json])]
+pub enum PayloadV2 {
+    Ecdsa(String),
+    Eddsa(String),
+}
+
+/// Accepts both the V1 (`payload`, `key_version`) and V2 (`payload_v2`, `domain_id`) schemas.
+#[near(serializers = [json])]
+pub struct SignRequest {
+    pub path: String,
+    #[serde(default)]
+    pub payload: Option<Vec<u8>>,
+    #[serde(default)]
+    pub payload_v2: Option<PayloadV2>,
+    #[serde(default)]
+    pub key_version: Option<u32>,
+    #[serde(default)]
+    pub domain_id: Option<u64>,
+}
+
+#[near(serializers = [json])]
+pub struct AffinePoint {
+    pub affine_point: String,
+}
+
+#[near(serializers = [json])]
+pub struct Scalar {
+    pub scalar: String,
+}
+
+/// Same shape as the real signer's ECDSA response.
+#[near(serializers = [json])]
+pub struct SignResult {
+    pub big_r: AffinePoint,
+    pub s: Scalar,
+    pub recovery_id: u8,
+}
+
+#[near(contract_state)]
+#[derive(Default)]
+pub struct MockMpc {
+    pub sign_count: u64,
+}
+
+#[near]
+impl MockMpc {
+    /// Signs a 32-byte hash with the key derived for the caller and `path`.
+    #[payable]
+    pub fn sign(&mut self, request: SignRequest) -> SignResult {
+        require!(
+            env::attached_deposit() >= NearToken::from_yoctonear(1),
+            "Attach a deposit to sign"
+        );
+        require!(
+            request.key_version.unwrap_or(0) == 0 && request.domain_id.unwrap_or(0) == 0,
+            "Mock MPC only has the secp256k1 domain 0"
+        );
+
+        let payload = match (request.payload, request.payload_v2) {
+            (Some(payload), None) => payload,
+            (None, Some(PayloadV2::Ecdsa(hex_payload))) => {
+                hex::decode(hex_payload).unwrap_or_else(|_| env::panic_str("Invalid payload hex"))
+            }
+            (None, Some(PayloadV2::Eddsa(_))) => {
+                env::panic_str("Mock MPC only signs ECDSA requests")
+            }
+            _ => env::panic_str("Provide exactly one of payload and payload_v2"),
+        };
+        require!(payload.len() == 32, "Payload must be a 32-byte hash");
+
+        self.sign_count += 1;
+
+        let signing_key = derive_signing_key(&env::predecessor_account_id(), &request.path);
+        let (signature, recovery_id) = signing_key
+            .sign_prehash_recoverable(&payload)
+            .unwrap_or_else(|_| env::panic_str("Failed to sign"));
+        let (r, s) = signature.split_bytes();
+
+        // r is the x coordinate of R; the recovery id carries the parity of its y
+        let mut big_r = vec![if recovery_id.is_y_odd() { 0x03 } else { 0x02 }];
+        big_r.extend_from_slice(&r);
+
+        SignResult {
+            big_r: AffinePoint {
+                affine_point: hex::encode(big_r),
+            },
+            s: Scalar {
+                scalar: hex::encode(s),
+            },
+            recovery_id: recovery_id.to_byte(),
+        }
+    }
+
+    /// Public key `sign` uses for `predecessor` (the caller if unset) and `path`.
+    pub fn derived_public_key(&self, path: String, predecessor: Option<AccountId>) -> PublicKey {
+        let predecessor = predecessor.unwrap_or_else(env::predecessor_account_id);
+        derive_public_key(&predecessor, &path)
+    }
+
+    pub fn get_sign_count(&self) -> u64 {
+        self.sign_count
+    }
+}
+
+/// Public key derived for `predecessor` and `path`, also usable off-chain by services
+/// that stand in for the MPC in tests. Like `derive_signing_key`, it doesn't call into
+/// `env`, so off-chain services link without near-sdk's mocked blockchain.
+pub fn derive_public_key(predecessor: &AccountId, path: &str) -> PublicKey {
+    let point = derive_signing_key(predecessor, path)
+        .verifying_key()
+        .to_encoded_point(false);
+
+    // Drop the 0x04 prefix of the uncompressed point
+    PublicKey::from_parts(CurveType::SECP256K1, point.as_bytes()[1..].to_vec())
+        .expect("Invalid derived key")
+}
+
+fn hash_to_scalar(data: &[u8]) -> k256::Scalar {
+    <k256::Scalar as Reduce<U256>>::reduce_bytes(&Sha3_256::digest(data))
+}
+
+fn derive_signing_key(predecessor: &AccountId, path: &str) -> SigningKey {
+    let root = hash_to_scalar(ROOT_KEY_SEED);
+    let epsilon =
+        hash_to_scalar(format!("{}{},{}", EPSILON_DERIVATION_PREFIX, predecessor, path).as_bytes());
+
+    SigningKey::from_bytes(&(root + epsilon).to_bytes())
+        .expect("Derived key is zero")
+}
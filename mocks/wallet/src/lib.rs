@@ -0,0 +1,45 @@
+//! Stand-in for the NEAR-EVM wallet contract deployed on eth-implicit accounts,
+//! used by the sandbox tests. It records `rlp_execute` calls instead of decoding
+//! and executing the transaction.
+use near_sdk::json_types::U64;
+use near_sdk::{env, near, require, AccountId, NearToken};
+
+/// An `rlp_execute` call as received
+#[derive(Clone)]
+#[near(serializers = [json, borsh])]
+pub struct Execution {
+    pub target: AccountId,
+    pub tx_bytes_b64: String,
+    pub deposit: NearToken,
+}
+
+/// Needs no initialization, as the tests patch it onto an existing eth-implicit account.
+#[near(contract_state)]
+#[derive(Default)]
+pub struct MockWallet {
+    pub nonce: u64,
+    pub executions: Vec<Execution>,
+}
+
+#[near]
+impl MockWallet {
+    #[payable]
+    pub fn rlp_execute(&mut self, target: AccountId, tx_bytes_b64: String) {
+        require!(!tx_bytes_b64.is_empty(), "Empty transaction");
+
+        self.executions.push(Execution {
+            target,
+            tx_bytes_b64,
+            deposit: env::attached_deposit(),
+        });
+        self.nonce += 1;
+    }
+
+    pub fn get_nonce(&self) -> U64 {
+        U64(self.nonce)
+    }
+
+    pub fn get_executions(&self) -> Vec<Execution> {
+        self.executions.clone()
+    }
+}
@@ -0,0 +1,33 @@
+// oracle/src/error.rs
+use fastauth_sdk::SdkError;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum OracleError {
+    /// The identity token failed verification
+    InvalidToken(String),
+    Mpc(String),
+    Rpc(String),
+    Config(String),
+    Sdk(SdkError),
+}
+
+impl fmt::Display for OracleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OracleError::InvalidToken(reason) => write!(f, "Invalid identity token: {}", reason),
+            OracleError::Mpc(reason) => write!(f, "MPC key derivation failed: {}", reason),
+            OracleError::Rpc(reason) => write!(f, "Transaction failed: {}", reason),
+            OracleError::Config(reason) => write!(f, "Invalid configuration: {}", reason),
+            OracleError::Sdk(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for OracleError {}
+
+impl From<SdkError> for OracleError {
+    fn from(e: SdkError) -> Self {
+        OracleError::Sdk(e)
+    }
+}
@@ -0,0 +1,181 @@
+// oracle/src/identity.rs
+use crate::OracleError;
+use async_trait::async_trait;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, DecodingKey, Validation};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+/// A user as asserted by their identity provider.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Identity {
+    pub issuer: String,
+    pub subject: String,
+}
+
+impl Identity {
+    /// MPC derivation path of the user: hex SHA-256 of `<issuer>#<subject>`, so the
+    /// path is stable per account at the provider without revealing who it belongs to.
+    pub fn mpc_path(&self) -> String {
+        hex::encode(Sha256::digest(
+            format!("{}#{}", self.issuer, self.subject).as_bytes(),
+        ))
+    }
+}
+
+/// Checks an identity token and returns who it was issued to.
+#[async_trait]
+pub trait IdentityVerifier: Send + Sync {
+    async fn verify(&self, token: &str) -> Result<Identity, OracleError>;
+}
+
+#[derive(Deserialize)]
+struct Claims {
+    iss: String,
+    sub: String,
+}
+
+/// Verifies JWTs against a fixed JWKS, e.g. one downloaded from the provider or
+/// generated for tests. Tokens must name a key by `kid` and match the issuer and audience.
+pub struct StaticJwksVerifier {
+    jwks: JwkSet,
+    issuer: String,
+    audience: String,
+}
+
+impl StaticJwksVerifier {
+    pub fn new(jwks: JwkSet, issuer: impl Into<String>, audience: impl Into<String>) -> Self {
+        StaticJwksVerifier {
+            jwks,
+            issuer: issuer.into(),
+            audience: audience.into(),
+        }
+    }
+
+    /// Loads the JWKS from a JSON file.
+    pub fn from_file(
+        path: &str,
+        issuer: impl Into<String>,
+        audience: impl Into<String>,
+    ) -> Result<Self, OracleError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| OracleError::Config(format!("Cannot read JWKS {}: {}", path, e)))?;
+        let jwks = serde_json::from_str(&contents)
+            .map_err(|e| OracleError::Config(format!("Invalid JWKS {}: {}", path, e)))?;
+        Ok(Self::new(jwks, issuer, audience))
+    }
+}
+
+#[async_trait]
+impl IdentityVerifier for StaticJwksVerifier {
+    async fn verify(&self, token: &str) -> Result<Identity, OracleError> {
+        let invalid = |e: jsonwebtoken::errors::Error| OracleError::InvalidToken(e.to_string());
+
+        let header = decode_header(token).map_err(invalid)?;
+        let kid = header
+            .kid
+            .ok_or_else(|| OracleError::InvalidToken("Missing kid".to_string()))?;
+        let jwk = self
+            .jwks
+            .find(&kid)
+            .ok_or_else(|| OracleError::InvalidToken(format!("Unknown kid {}", kid)))?;
+        // Rejects an algorithm from a different family than the key's
+        let key = DecodingKey::from_jwk(jwk).map_err(invalid)?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_audience(&[&self.audience]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        let claims = decode::<Claims>(token, &key, &validation)
+            .map_err(invalid)?
+            .claims;
+        Ok(Identity {
+            issuer: claims.iss,
+            subject: claims.sub,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+
+    const SECRET: &[u8] = b"oracle test secret";
+    const ISSUER: &str = "https://issuer.test";
+    const AUDIENCE: &str = "fastauth";
+
+    fn verifier() -> StaticJwksVerifier {
+        let jwks = serde_json::from_value(json!({
+            "keys": [{
+                "kty": "oct",
+                "kid": "test-key",
+                "alg": "HS256",
+                "k": "b3JhY2xlIHRlc3Qgc2VjcmV0",
+            }]
+        }))
+        .unwrap();
+        StaticJwksVerifier::new(jwks, ISSUER, AUDIENCE)
+    }
+
+    fn token(kid: &str, claims: serde_json::Value) -> String {
+        let header = Header {
+            kid: Some(kid.to_string()),
+            ..Header::default()
+        };
+        encode(&header, &claims, &EncodingKey::from_secret(SECRET)).unwrap()
+    }
+
+    fn claims(issuer: &str, audience: &str) -> serde_json::Value {
+        json!({ "iss": issuer, "aud": audience, "sub": "user-1", "exp": u32::MAX })
+    }
+
+    #[tokio::test]
+    async fn accepts_tokens_signed_by_a_known_key() {
+        let identity = verifier()
+            .verify(&token("test-key", claims(ISSUER, AUDIENCE)))
+            .await
+            .unwrap();
+        assert_eq!(
+            identity,
+            Identity {
+                issuer: ISSUER.to_string(),
+                subject: "user-1".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_keys_issuers_and_audiences() {
+        let verifier = verifier();
+        for token in [
+            token("other-key", claims(ISSUER, AUDIENCE)),
+            token("test-key", claims("https://other.test", AUDIENCE)),
+            token("test-key", claims(ISSUER, "other-app")),
+        ] {
+            assert!(matches!(
+                verifier.verify(&token).await,
+                Err(OracleError::InvalidToken(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn mpc_path_is_a_hash_of_issuer_and_subject() {
+        let identity = Identity {
+            issuer: ISSUER.to_string(),
+            subject: "user-1".to_string(),
+        };
+        assert_eq!(identity.mpc_path().len(), 64);
+        assert_ne!(
+            identity.mpc_path(),
+            Identity {
+                subject: "user-2".to_string(),
+                ..identity.clone()
+            }
+            .mpc_path()
+        );
+    }
+}
@@ -0,0 +1,18 @@
+//! Reference oracle for FastAuth.
+//!
+//! The oracle is the only account allowed to call `activate_account` and
+//! `add_session_key`. It verifies a user's identity token, derives their MPC path and
+//! keys, and submits the calls. Identity verification, the MPC and transaction
+//! submission are traits, with local implementations for testing.
+pub mod error;
+pub mod identity;
+pub mod mpc;
+pub mod oracle;
+pub mod rpc;
+pub mod server;
+
+pub use error::OracleError;
+pub use identity::{Identity, IdentityVerifier, StaticJwksVerifier};
+pub use mpc::{MockMpcClient, MpcClient};
+pub use oracle::{Activation, Oracle, OracleConfig};
+pub use rpc::{FunctionCall, LocalRpc, OracleRpc};
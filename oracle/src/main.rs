@@ -0,0 +1,46 @@
+//! Runs the oracle over HTTP with the local verifier, MPC and RPC stand-ins.
+//!
+//! Configured through the environment:
+//! - `ORACLE_JWKS`: path of the JWKS file identity tokens are verified against
+//! - `ORACLE_ISSUER`, `ORACLE_AUDIENCE`: expected `iss` and `aud` claims
+//! - `FASTAUTH_CONTRACT`: the FastAuth contract account
+//! - `ORACLE_BIND`: listen address, `127.0.0.1:3030` by default
+use fastauth_oracle::{
+    server, LocalRpc, MockMpcClient, Oracle, OracleConfig, OracleError, StaticJwksVerifier,
+};
+use near_sdk::NearToken;
+use std::sync::Arc;
+
+fn env_var(name: &str) -> Result<String, OracleError> {
+    std::env::var(name).map_err(|_| OracleError::Config(format!("{} is not set", name)))
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let verifier = StaticJwksVerifier::from_file(
+        &env_var("ORACLE_JWKS")?,
+        env_var("ORACLE_ISSUER")?,
+        env_var("ORACLE_AUDIENCE")?,
+    )?;
+    let config = OracleConfig {
+        contract_id: env_var("FASTAUTH_CONTRACT")?
+            .parse()
+            .map_err(|_| OracleError::Config("Invalid FASTAUTH_CONTRACT".to_string()))?,
+        ecdsa_domain_id: 0,
+        eddsa_domain_id: None,
+        storage_deposit: NearToken::from_millinear(100),
+    };
+
+    let oracle = Arc::new(Oracle {
+        config,
+        verifier,
+        mpc: MockMpcClient,
+        rpc: LocalRpc::default(),
+    });
+
+    let bind = std::env::var("ORACLE_BIND").unwrap_or_else(|_| "127.0.0.1:3030".to_string());
+    let listener = tokio::net::TcpListener::bind(&bind).await?;
+    println!("Oracle listening on {}", bind);
+    axum::serve(listener, server::router(oracle)).await?;
+    Ok(())
+}
@@ -0,0 +1,38 @@
+// oracle/src/mpc.rs
+use crate::OracleError;
+use async_trait::async_trait;
+use near_sdk::{AccountId, PublicKey};
+
+/// Derives users' MPC public keys. `predecessor` is the FastAuth contract, which is
+/// the account that requests the signatures.
+#[async_trait]
+pub trait MpcClient: Send + Sync {
+    async fn derived_public_key(
+        &self,
+        path: &str,
+        predecessor: &AccountId,
+        domain_id: u64,
+    ) -> Result<PublicKey, OracleError>;
+}
+
+/// Derives keys locally exactly like the mock MPC contract, so activations made
+/// through it can be signed for by the mock in sandbox tests.
+pub struct MockMpcClient;
+
+#[async_trait]
+impl MpcClient for MockMpcClient {
+    async fn derived_public_key(
+        &self,
+        path: &str,
+        predecessor: &AccountId,
+        domain_id: u64,
+    ) -> Result<PublicKey, OracleError> {
+        if domain_id != 0 {
+            return Err(OracleError::Mpc(format!(
+                "Mock MPC has no domain {}",
+                domain_id
+            )));
+        }
+        Ok(mock_mpc::derive_public_key(predecessor, path))
+    }
+}
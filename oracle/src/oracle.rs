@@ -0,0 +1,219 @@
+// oracle/src/oracle.rs
+use crate::{FunctionCall, IdentityVerifier, MpcClient, OracleError, OracleRpc};
+use fastauth_sdk::{eth_implicit_account_id, AppID, MpcPath, SessionKey};
+use near_sdk::{AccountId, NearToken, PublicKey};
+use serde::{Serialize, Serializer};
+use serde_json::json;
+
+pub struct OracleConfig {
+    /// The FastAuth contract, whose `oracle_account_id` the RPC backend signs as
+    pub contract_id: AccountId,
+    pub ecdsa_domain_id: u64,
+    /// Domain of users' ed25519 keys, if the MPC has one
+    pub eddsa_domain_id: Option<u64>,
+    /// Attached to cover the storage of each activation or session key; the rest is refunded
+    pub storage_deposit: NearToken,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Activation {
+    pub path: MpcPath,
+    #[serde(serialize_with = "serialize_public_key")]
+    pub mpc_key: PublicKey,
+    pub eth_address: AccountId,
+    #[serde(serialize_with = "serialize_optional_public_key")]
+    pub ed25519_key: Option<PublicKey>,
+}
+
+/// Formats a key as `<curve>:<base58 key>`, like `SessionKey`. `PublicKey`'s own
+/// serialization calls into near-sdk's `env`, which off-chain builds don't link.
+fn public_key_string(public_key: &PublicKey) -> String {
+    SessionKey::from(public_key.clone()).to_string()
+}
+
+fn serialize_public_key<S: Serializer>(key: &PublicKey, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&public_key_string(key))
+}
+
+fn serialize_optional_public_key<S: Serializer>(
+    key: &Option<PublicKey>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    key.as_ref().map(public_key_string).serialize(serializer)
+}
+
+/// Activates users and registers their apps' session keys once their identity is verified.
+pub struct Oracle<V, M, R> {
+    pub config: OracleConfig,
+    pub verifier: V,
+    pub mpc: M,
+    pub rpc: R,
+}
+
+impl<V: IdentityVerifier, M: MpcClient, R: OracleRpc> Oracle<V, M, R> {
+    /// Derives the user's MPC keys and calls `activate_account`.
+    pub async fn activate(&self, id_token: &str) -> Result<Activation, OracleError> {
+        let path = self.verifier.verify(id_token).await?.mpc_path();
+
+        let mpc_key = self
+            .mpc
+            .derived_public_key(&path, &self.config.contract_id, self.config.ecdsa_domain_id)
+            .await?;
+        let eth_address = eth_implicit_account_id(&mpc_key)?;
+        let ed25519_key = match self.config.eddsa_domain_id {
+            Some(domain_id) => Some(
+                self.mpc
+                    .derived_public_key(&path, &self.config.contract_id, domain_id)
+                    .await?,
+            ),
+            None => None,
+        };
+
+        self.call(
+            "activate_account",
+            json!({
+                "mpc_key": public_key_string(&mpc_key),
+                "eth_address": eth_address,
+                "path": path,
+                "ed25519_key": ed25519_key.as_ref().map(public_key_string),
+            }),
+        )
+        .await?;
+
+        Ok(Activation {
+            path,
+            mpc_key,
+            eth_address,
+            ed25519_key,
+        })
+    }
+
+    /// Registers `session_key` for the user and app, replacing the app's previous key.
+    pub async fn add_session_key(
+        &self,
+        id_token: &str,
+        session_key: &SessionKey,
+        app_id: &AppID,
+    ) -> Result<MpcPath, OracleError> {
+        let path = self.verifier.verify(id_token).await?.mpc_path();
+
+        self.call(
+            "add_session_key",
+            json!({
+                "public_key": session_key,
+                "path": path,
+                "app_id": app_id,
+            }),
+        )
+        .await?;
+
+        Ok(path)
+    }
+
+    async fn call(&self, method_name: &str, args: serde_json::Value) -> Result<(), OracleError> {
+        self.rpc
+            .call(FunctionCall {
+                receiver_id: self.config.contract_id.clone(),
+                method_name: method_name.to_string(),
+                args,
+                deposit: self.config.storage_deposit,
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Identity, LocalRpc, MockMpcClient};
+    use async_trait::async_trait;
+
+    /// Accepts any token as the subject it names
+    struct TrustingVerifier;
+
+    #[async_trait]
+    impl IdentityVerifier for TrustingVerifier {
+        async fn verify(&self, token: &str) -> Result<Identity, OracleError> {
+            Ok(Identity {
+                issuer: "https://issuer.test".to_string(),
+                subject: token.to_string(),
+            })
+        }
+    }
+
+    fn oracle() -> Oracle<TrustingVerifier, MockMpcClient, LocalRpc> {
+        Oracle {
+            config: OracleConfig {
+                contract_id: "fastauth.test.near".parse().unwrap(),
+                ecdsa_domain_id: 0,
+                eddsa_domain_id: None,
+                storage_deposit: NearToken::from_millinear(100),
+            },
+            verifier: TrustingVerifier,
+            mpc: MockMpcClient,
+            rpc: LocalRpc::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn activation_submits_the_derived_keys() {
+        let oracle = oracle();
+        let activation = oracle.activate("user-1").await.unwrap();
+
+        assert_eq!(
+            activation.mpc_key,
+            mock_mpc::derive_public_key(&oracle.config.contract_id, &activation.path)
+        );
+        assert_eq!(
+            activation.eth_address,
+            eth_implicit_account_id(&activation.mpc_key).unwrap()
+        );
+        assert_eq!(
+            oracle.rpc.calls(),
+            vec![FunctionCall {
+                receiver_id: oracle.config.contract_id.clone(),
+                method_name: "activate_account".to_string(),
+                args: json!({
+                    "mpc_key": activation.mpc_key,
+                    "eth_address": activation.eth_address,
+                    "path": activation.path,
+                    "ed25519_key": null,
+                }),
+                deposit: NearToken::from_millinear(100),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn session_keys_are_added_for_the_users_path() {
+        let oracle = oracle();
+        let session_key: SessionKey = "ed25519:AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9"
+            .parse()
+            .unwrap();
+        let path = oracle
+            .add_session_key("user-1", &session_key, &"app".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(path, oracle.activate("user-1").await.unwrap().path);
+        assert_eq!(
+            oracle.rpc.calls()[0].args,
+            json!({
+                "public_key": "ed25519:AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9",
+                "path": path,
+                "app_id": "app",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn mock_mpc_has_no_eddsa_domain() {
+        let mut oracle = oracle();
+        oracle.config.eddsa_domain_id = Some(1);
+        assert!(matches!(
+            oracle.activate("user-1").await,
+            Err(OracleError::Mpc(_))
+        ));
+        assert!(oracle.rpc.calls().is_empty());
+    }
+}
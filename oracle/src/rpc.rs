@@ -0,0 +1,40 @@
+// oracle/src/rpc.rs
+use crate::OracleError;
+use async_trait::async_trait;
+use near_sdk::{AccountId, NearToken};
+use std::sync::Mutex;
+
+/// A function call the oracle signs and submits with its own account.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionCall {
+    pub receiver_id: AccountId,
+    pub method_name: String,
+    pub args: serde_json::Value,
+    pub deposit: NearToken,
+}
+
+/// Submits the oracle's transactions and waits for their outcome.
+#[async_trait]
+pub trait OracleRpc: Send + Sync {
+    async fn call(&self, call: FunctionCall) -> Result<(), OracleError>;
+}
+
+/// Stand-in that records calls instead of submitting them.
+#[derive(Default)]
+pub struct LocalRpc {
+    calls: Mutex<Vec<FunctionCall>>,
+}
+
+impl LocalRpc {
+    pub fn calls(&self) -> Vec<FunctionCall> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl OracleRpc for LocalRpc {
+    async fn call(&self, call: FunctionCall) -> Result<(), OracleError> {
+        self.calls.lock().unwrap().push(call);
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+// oracle/src/server.rs
+use crate::{Activation, IdentityVerifier, MpcClient, Oracle, OracleError, OracleRpc};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use fastauth_sdk::{AppID, MpcPath, SessionKey};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Deserialize)]
+pub struct ActivateRequest {
+    pub id_token: String,
+}
+
+#[derive(Deserialize)]
+pub struct SessionKeyRequest {
+    pub id_token: String,
+    pub public_key: SessionKey,
+    pub app_id: AppID,
+}
+
+#[derive(Serialize)]
+pub struct SessionKeyResponse {
+    pub path: MpcPath,
+}
+
+impl IntoResponse for OracleError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            OracleError::InvalidToken(_) => StatusCode::UNAUTHORIZED,
+            OracleError::Mpc(_) | OracleError::Rpc(_) => StatusCode::BAD_GATEWAY,
+            OracleError::Config(_) | OracleError::Sdk(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+/// `POST /activate` and `POST /session-keys`
+pub fn router<V, M, R>(oracle: Arc<Oracle<V, M, R>>) -> Router
+where
+    V: IdentityVerifier + 'static,
+    M: MpcClient + 'static,
+    R: OracleRpc + 'static,
+{
+    Router::new()
+        .route("/activate", post(activate::<V, M, R>))
+        .route("/session-keys", post(add_session_key::<V, M, R>))
+        .with_state(oracle)
+}
+
+async fn activate<V, M, R>(
+    State(oracle): State<Arc<Oracle<V, M, R>>>,
+    Json(request): Json<ActivateRequest>,
+) -> Result<Json<Activation>, OracleError>
+where
+    V: IdentityVerifier,
+    M: MpcClient,
+    R: OracleRpc,
+{
+    Ok(Json(oracle.activate(&request.id_token).await?))
+}
+
+async fn add_session_key<V, M, R>(
+    State(oracle): State<Arc<Oracle<V, M, R>>>,
+    Json(request): Json<SessionKeyRequest>,
+) -> Result<Json<SessionKeyResponse>, OracleError>
+where
+    V: IdentityVerifier,
+    M: MpcClient,
+    R: OracleRpc,
+{
+    let path = oracle
+        .add_session_key(&request.id_token, &request.public_key, &request.app_id)
+        .await?;
+    Ok(Json(SessionKeyResponse { path }))
+}
@@ -0,0 +1,30 @@
+// relayer/src/error.rs
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RelayerError {
+    /// The payload would fail on-chain or is not covered by the app's policy
+    Rejected(String),
+    NotFound(u64),
+    Rpc(String),
+    Config(String),
+}
+
+impl RelayerError {
+    pub(crate) fn rejected(reason: impl Into<String>) -> Self {
+        RelayerError::Rejected(reason.into())
+    }
+}
+
+impl fmt::Display for RelayerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RelayerError::Rejected(reason) => write!(f, "Rejected: {}", reason),
+            RelayerError::NotFound(id) => write!(f, "Unknown submission {}", id),
+            RelayerError::Rpc(reason) => write!(f, "RPC error: {}", reason),
+            RelayerError::Config(reason) => write!(f, "Invalid configuration: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for RelayerError {}
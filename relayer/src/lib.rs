@@ -0,0 +1,17 @@
+//! Relayer for gasless `execute_near_action` submission.
+//!
+//! Apps hand the relayer payloads their users' session keys signed. The relayer checks
+//! each one's signature and then the contract's views (session key, pauses, the wallet's
+//! nonce, the app's policy and balance, the NEAR EVM chain's caps) so it doesn't pay for
+//! calls bound to fail, then submits them in batches and tracks their outcomes. The RPC
+//! backend is a trait, with a local mock for testing.
+pub mod error;
+pub mod policy;
+pub mod relayer;
+pub mod rpc;
+pub mod server;
+
+pub use error::RelayerError;
+pub use policy::{check_evm_chain, check_policy};
+pub use relayer::{Relayer, RelayerConfig, SubmissionStatus};
+pub use rpc::{FunctionCall, MockRpc, RelayerRpc};
@@ -0,0 +1,52 @@
+//! Runs the relayer over HTTP. There is no RPC backend for a live network yet, so the
+//! binary refuses to start unless asked to run against the local mock one.
+//!
+//! Configured through the environment:
+//! - `RELAYER_MOCK_RPC`: `true` to run against `MockRpc`, which submits nothing
+//! - `FASTAUTH_CONTRACT`: the FastAuth contract account
+//! - `RELAYER_BIND`: listen address, `127.0.0.1:3031` by default
+//! - `RELAYER_BATCH_SIZE`: most submissions per batch, 10 by default
+//! - `RELAYER_BATCH_INTERVAL_MS`: time between batches, 1000 by default
+use fastauth_relayer::{server, MockRpc, Relayer, RelayerConfig, RelayerError};
+use near_sdk::Gas;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> Result<T, RelayerError> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map_err(|_| RelayerError::Config(format!("Invalid {}", name))),
+        Err(_) => Ok(default),
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    if !env_or("RELAYER_MOCK_RPC", false)? {
+        return Err(RelayerError::Config(
+            "No RPC backend for a live network: set RELAYER_MOCK_RPC=true to run against MockRpc"
+                .to_string(),
+        )
+        .into());
+    }
+
+    let config = RelayerConfig {
+        contract_id: std::env::var("FASTAUTH_CONTRACT")
+            .map_err(|_| RelayerError::Config("FASTAUTH_CONTRACT is not set".to_string()))?
+            .parse()
+            .map_err(|_| RelayerError::Config("Invalid FASTAUTH_CONTRACT".to_string()))?,
+        max_batch_size: env_or("RELAYER_BATCH_SIZE", 10)?,
+        gas: Gas::from_tgas(300),
+    };
+    let interval = Duration::from_millis(env_or("RELAYER_BATCH_INTERVAL_MS", 1000)?);
+
+    let relayer = Arc::new(Relayer::new(config, MockRpc::default()));
+    tokio::spawn(relayer.clone().run(interval));
+
+    let bind = env_or("RELAYER_BIND", "127.0.0.1:3031".to_string())?;
+    let listener = tokio::net::TcpListener::bind(&bind).await?;
+    println!("Relayer listening on {}", bind);
+    axum::serve(listener, server::router(relayer)).await?;
+    Ok(())
+}
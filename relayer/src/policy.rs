@@ -0,0 +1,214 @@
+// relayer/src/policy.rs
+use crate::RelayerError;
+use fastauth_sdk::NearAction;
+use keypom_multichain_trial_accounts::trial_user::perform_actions::near::encoding::{
+    convert_deposit, near_gas_to_evm_gas,
+};
+use keypom_multichain_trial_accounts::{ChainId, EvmChain, TrialData};
+use near_sdk::{Gas, NearToken};
+
+/// Gas the contract has the wallet attach to transfers
+const TRANSFER_GAS: Gas = Gas::from_tgas(5);
+
+/// NEAR gas and deposit of the action, as the contract computes them
+fn gas_and_deposit(action: &NearAction) -> (Gas, NearToken) {
+    match action {
+        NearAction::FunctionCall { gas, deposit, .. } => (*gas, *deposit),
+        NearAction::Transfer { amount, .. } => (TRANSFER_GAS, *amount),
+    }
+}
+
+/// Checks the wallet transaction the contract builds for the action against the
+/// registry entry of the NEAR EVM chain. Its fees are the entry's caps, so only the
+/// gas limit and value can exceed them.
+pub fn check_evm_chain(chain: Option<&EvmChain>, action: &NearAction) -> Result<(), RelayerError> {
+    let chain = match chain {
+        Some(chain) if chain.enabled => chain,
+        _ => return Err(RelayerError::rejected("NEAR EVM chain is not enabled")),
+    };
+
+    let (gas, deposit) = gas_and_deposit(action);
+    if !chain.is_gas_limit_allowed(near_gas_to_evm_gas(gas.as_gas()) as u128) {
+        return Err(RelayerError::rejected("Gas exceeds the chain's cap"));
+    }
+    if !chain.is_value_allowed(convert_deposit(deposit).0) {
+        return Err(RelayerError::rejected("Deposit exceeds the chain's cap"));
+    }
+    Ok(())
+}
+
+/// Checks an action against the app's trial policy and balance, as of `now` in nanoseconds.
+pub fn check_policy(
+    trial_data: &TrialData,
+    action: &NearAction,
+    now: u64,
+    app_balance: NearToken,
+) -> Result<(), RelayerError> {
+    if trial_data.has_expired(now) {
+        return Err(RelayerError::rejected("Trial has expired"));
+    }
+
+    let near = ChainId::from("NEAR");
+    if let NearAction::FunctionCall {
+        contract_id,
+        method_name,
+        ..
+    } = action
+    {
+        if !trial_data.is_method_allowed(method_name, &near) {
+            return Err(RelayerError::rejected(format!(
+                "Method {} is not allowed",
+                method_name
+            )));
+        }
+        if !trial_data.is_contract_allowed(contract_id.as_str(), &near) {
+            return Err(RelayerError::rejected(format!(
+                "Contract {} is not allowed",
+                contract_id
+            )));
+        }
+    }
+
+    let (gas, deposit) = gas_and_deposit(action);
+    if !trial_data.is_gas_within_limits(gas.as_gas(), &near) {
+        return Err(RelayerError::rejected("Gas exceeds the app's limit"));
+    }
+    if !trial_data.is_deposit_within_limits(deposit.as_yoctonear(), &near) {
+        return Err(RelayerError::rejected("Deposit exceeds the app's limit"));
+    }
+    if deposit > app_balance {
+        return Err(RelayerError::rejected(
+            "App balance cannot cover the deposit",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use keypom_multichain_trial_accounts::{ChainConstraints, NearConstraints};
+    use std::collections::HashMap;
+
+    pub(crate) fn trial_data() -> TrialData {
+        TrialData {
+            constraints_by_chain_id: HashMap::from([(
+                ChainId::from("NEAR"),
+                ChainConstraints::NEAR(NearConstraints {
+                    allowed_methods: vec!["add_message".to_string()],
+                    allowed_contracts: vec!["guestbook.near".parse().unwrap()],
+                    max_gas: Some(Gas::from_tgas(100)),
+                    max_deposit: Some(NearToken::from_near(1)),
+                    initial_deposit: NearToken::from_near(0),
+                }),
+            )]),
+            usage_constraints: None,
+            interaction_limits: None,
+            exit_conditions: None,
+            expiration_time: Some(1_000),
+            creator_account_id: "app.near".parse().unwrap(),
+            typed_data_constraints: None,
+            personal_sign_format: None,
+            bitcoin_constraints: None,
+            solana_constraints: None,
+        }
+    }
+
+    fn call(contract_id: &str, method_name: &str, gas: Gas, deposit: NearToken) -> NearAction {
+        NearAction::FunctionCall {
+            contract_id: contract_id.parse().unwrap(),
+            method_name: method_name.to_string(),
+            args: vec![],
+            gas,
+            deposit,
+        }
+    }
+
+    fn check(action: &NearAction) -> Result<(), RelayerError> {
+        check_policy(&trial_data(), action, 0, NearToken::from_near(2))
+    }
+
+    #[test]
+    fn allowed_calls_pass() {
+        assert_eq!(
+            check(&call(
+                "guestbook.near",
+                "add_message",
+                Gas::from_tgas(30),
+                NearToken::from_near(1)
+            )),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn calls_outside_the_policy_are_rejected() {
+        let gas = Gas::from_tgas(30);
+        let deposit = NearToken::from_near(0);
+        for action in [
+            call("guestbook.near", "delete_messages", gas, deposit),
+            call("other.near", "add_message", gas, deposit),
+            call(
+                "guestbook.near",
+                "add_message",
+                Gas::from_tgas(101),
+                deposit,
+            ),
+            call(
+                "guestbook.near",
+                "add_message",
+                gas,
+                NearToken::from_millinear(1001),
+            ),
+        ] {
+            assert!(matches!(check(&action), Err(RelayerError::Rejected(_))));
+        }
+    }
+
+    #[test]
+    fn evm_chain_caps_are_enforced() {
+        let action = call(
+            "guestbook.near",
+            "add_message",
+            Gas::from_tgas(100),
+            NearToken::from_near(1),
+        );
+        let chain = EvmChain::near_evm();
+        assert_eq!(check_evm_chain(Some(&chain), &action), Ok(()));
+        assert!(check_evm_chain(None, &action).is_err());
+
+        for chain in [
+            EvmChain {
+                enabled: false,
+                ..EvmChain::near_evm()
+            },
+            EvmChain {
+                max_gas_limit: 999_999.into(),
+                ..EvmChain::near_evm()
+            },
+            EvmChain {
+                max_value: Some((10u128.pow(18) - 1).into()),
+                ..EvmChain::near_evm()
+            },
+        ] {
+            assert!(matches!(
+                check_evm_chain(Some(&chain), &action),
+                Err(RelayerError::Rejected(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn expired_trials_and_low_balances_are_rejected() {
+        let transfer = NearAction::Transfer {
+            receiver_id: "bob.near".parse().unwrap(),
+            amount: NearToken::from_near(1),
+        };
+        assert_eq!(
+            check_policy(&trial_data(), &transfer, 0, NearToken::from_near(1)),
+            Ok(())
+        );
+        assert!(check_policy(&trial_data(), &transfer, 1_000, NearToken::from_near(1)).is_err());
+        assert!(check_policy(&trial_data(), &transfer, 0, NearToken::from_millinear(999)).is_err());
+    }
+}
@@ -0,0 +1,445 @@
+// relayer/src/relayer.rs
+use crate::{check_evm_chain, check_policy, FunctionCall, RelayerError, RelayerRpc};
+use fastauth_sdk::{verify_signature, ExecuteNearActionArgs};
+use keypom_multichain_trial_accounts::{Bundle, EvmChain, KeyUsage, TrialData, NEAR_EVM_CHAIN_ID};
+use near_sdk::json_types::U64;
+use near_sdk::{AccountId, Gas, NearToken};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub struct RelayerConfig {
+    pub contract_id: AccountId,
+    /// Most submissions sent per batch
+    pub max_batch_size: usize,
+    /// Gas attached to each `execute_near_action` call
+    pub gas: Gas,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "status")]
+pub enum SubmissionStatus {
+    Queued,
+    Submitted,
+    Succeeded { tx_hash: String },
+    Failed { reason: String },
+}
+
+struct Submission {
+    id: u64,
+    wallet: AccountId,
+    args: ExecuteNearActionArgs,
+}
+
+#[derive(Default)]
+struct RelayerState {
+    next_id: u64,
+    queue: VecDeque<Submission>,
+    statuses: HashMap<u64, SubmissionStatus>,
+    /// Wallets with a submission in flight. The wallet's nonce only moves once it
+    /// executes, so a second payload for it could not be checked until then.
+    pending_wallets: HashSet<AccountId>,
+}
+
+/// Validates signed payloads against the contract, queues them and submits them
+/// in batches, paying the gas of `execute_near_action` from the relayer's account.
+pub struct Relayer<R> {
+    pub config: RelayerConfig,
+    pub rpc: R,
+    state: Mutex<RelayerState>,
+}
+
+impl<R: RelayerRpc> Relayer<R> {
+    pub fn new(config: RelayerConfig, rpc: R) -> Self {
+        Relayer {
+            config,
+            rpc,
+            state: Mutex::new(RelayerState::default()),
+        }
+    }
+
+    /// Queues the payload if it would pass on-chain. Returns the submission's id.
+    pub async fn submit(&self, args: ExecuteNearActionArgs) -> Result<u64, RelayerError> {
+        let wallet = self.validate(&args).await?;
+
+        let mut state = self.state.lock().unwrap();
+        if !state.pending_wallets.insert(wallet.clone()) {
+            return Err(RelayerError::rejected(
+                "A payload for this wallet is already pending",
+            ));
+        }
+        let id = state.next_id;
+        state.next_id += 1;
+        state.statuses.insert(id, SubmissionStatus::Queued);
+        state.queue.push_back(Submission { id, wallet, args });
+        Ok(id)
+    }
+
+    pub fn status(&self, id: u64) -> Result<SubmissionStatus, RelayerError> {
+        self.state
+            .lock()
+            .unwrap()
+            .statuses
+            .get(&id)
+            .cloned()
+            .ok_or(RelayerError::NotFound(id))
+    }
+
+    /// Submits the next batch from the queue and records the outcomes.
+    /// Returns how many submissions were sent.
+    pub async fn flush(&self) -> usize {
+        let batch: Vec<Submission> = {
+            let mut state = self.state.lock().unwrap();
+            let size = state.queue.len().min(self.config.max_batch_size);
+            let batch: Vec<Submission> = state.queue.drain(..size).collect();
+            for submission in &batch {
+                state
+                    .statuses
+                    .insert(submission.id, SubmissionStatus::Submitted);
+            }
+            batch
+        };
+        if batch.is_empty() {
+            return 0;
+        }
+
+        let calls = batch
+            .iter()
+            .map(|submission| FunctionCall {
+                receiver_id: self.config.contract_id.clone(),
+                method_name: "execute_near_action".to_string(),
+                args: serde_json::to_value(&submission.args).expect("Arguments serialize to JSON"),
+                gas: self.config.gas,
+                deposit: NearToken::from_yoctonear(0),
+            })
+            .collect();
+        let outcomes = self.rpc.submit_batch(calls).await;
+
+        let mut state = self.state.lock().unwrap();
+        for (index, submission) in batch.iter().enumerate() {
+            let status = match outcomes.get(index) {
+                Some(Ok(tx_hash)) => SubmissionStatus::Succeeded {
+                    tx_hash: tx_hash.clone(),
+                },
+                Some(Err(reason)) => SubmissionStatus::Failed {
+                    reason: reason.clone(),
+                },
+                None => SubmissionStatus::Failed {
+                    reason: "No outcome from the RPC backend".to_string(),
+                },
+            };
+            state.statuses.insert(submission.id, status);
+            state.pending_wallets.remove(&submission.wallet);
+        }
+        batch.len()
+    }
+
+    /// Flushes the queue every `interval`.
+    pub async fn run(self: Arc<Self>, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            self.flush().await;
+        }
+    }
+
+    /// Turns away payloads the contract's views show would fail. A best-effort subset of
+    /// `execute_near_action`'s checks against state that may change before submission.
+    /// Returns the wallet that executes it.
+    async fn validate(&self, args: &ExecuteNearActionArgs) -> Result<AccountId, RelayerError> {
+        let contract_id = &self.config.contract_id;
+
+        let is_signed = verify_signature(&args.payload, &args.signature, &args.session_key)
+            .map_err(|e| RelayerError::rejected(e.to_string()))?;
+        if !is_signed {
+            return Err(RelayerError::rejected("Invalid session key signature"));
+        }
+
+        let key_usage: KeyUsage = self
+            .view::<Option<KeyUsage>>(
+                contract_id,
+                "get_key_usage",
+                json!({ "public_key": args.session_key }),
+            )
+            .await?
+            .ok_or_else(|| RelayerError::rejected("Unknown session key"))?;
+        if key_usage.app_id != args.app_id {
+            return Err(RelayerError::rejected("Session key belongs to another app"));
+        }
+
+        if self.view(contract_id, "is_paused", json!({})).await? {
+            return Err(RelayerError::rejected("Contract is paused"));
+        }
+        if self
+            .view(
+                contract_id,
+                "is_app_paused",
+                json!({ "app_id": args.app_id }),
+            )
+            .await?
+        {
+            return Err(RelayerError::rejected("App is paused"));
+        }
+        if self
+            .view(
+                contract_id,
+                "is_user_frozen",
+                json!({ "path": key_usage.path }),
+            )
+            .await?
+        {
+            return Err(RelayerError::rejected("User is frozen"));
+        }
+
+        let bundle: Bundle = self
+            .view::<Option<Bundle>>(contract_id, "get_bundle", json!({ "path": key_usage.path }))
+            .await?
+            .ok_or_else(|| RelayerError::rejected("User is not activated"))?;
+        let nonce: U64 = self
+            .view(&bundle.eth_address, "get_nonce", json!({}))
+            .await?;
+        if nonce != args.payload.nonce {
+            return Err(RelayerError::rejected(format!(
+                "Nonce {} does not match the wallet's nonce {}",
+                args.payload.nonce.0, nonce.0
+            )));
+        }
+
+        let trial_data: TrialData = self
+            .view::<Option<TrialData>>(
+                contract_id,
+                "get_trial_data",
+                json!({ "app_id": args.app_id }),
+            )
+            .await?
+            .ok_or_else(|| RelayerError::rejected("App has no trial data"))?;
+        let app_balance: NearToken = self
+            .view(
+                contract_id,
+                "get_app_balance",
+                json!({ "app_id": args.app_id }),
+            )
+            .await?;
+        check_policy(
+            &trial_data,
+            &args.payload.action,
+            now_nanoseconds(),
+            app_balance,
+        )?;
+
+        let chain: Option<EvmChain> = self
+            .view(
+                contract_id,
+                "get_evm_chain",
+                json!({ "chain_id": NEAR_EVM_CHAIN_ID }),
+            )
+            .await?;
+        check_evm_chain(chain.as_ref(), &args.payload.action)?;
+
+        Ok(bundle.eth_address)
+    }
+
+    async fn view<T: DeserializeOwned>(
+        &self,
+        account_id: &AccountId,
+        method_name: &str,
+        args: Value,
+    ) -> Result<T, RelayerError> {
+        let response = self.rpc.view(account_id, method_name, args).await?;
+        serde_json::from_value(response).map_err(|e| {
+            RelayerError::Rpc(format!(
+                "Unexpected response from {}.{}: {}",
+                account_id, method_name, e
+            ))
+        })
+    }
+}
+
+fn now_nanoseconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Clock is after the epoch")
+        .as_nanos() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::tests::trial_data;
+    use crate::MockRpc;
+    use fastauth_sdk::{transfer, SessionSigner};
+    use keypom_multichain_trial_accounts::UsageStats;
+
+    const APP_ID: &str = "app";
+
+    fn wallet() -> AccountId {
+        "0x4a62316623ad457f02cdc5d997ded67a383ec569"
+            .parse()
+            .unwrap()
+    }
+
+    fn relayer(max_batch_size: usize) -> Relayer<MockRpc> {
+        let contract_id: AccountId = "fastauth.near".parse().unwrap();
+        let rpc = MockRpc::default();
+
+        let key_usage = KeyUsage {
+            usage_stats: UsageStats::default(),
+            app_id: APP_ID.to_string(),
+            path: "user-path".to_string(),
+        };
+        let bundle = Bundle {
+            mpc_key: "secp256k1:43y5hhS5mWqG4QWeEBqmJfDae1rWstRUETCvcELhzai5ataDC38sCnahANBd5ALUWTMHhPRM74geUt9D3TE5QhG3"
+                .parse()
+                .unwrap(),
+            eth_address: wallet(),
+            path: "user-path".to_string(),
+            ed25519_key: None,
+        };
+        let trial_data = TrialData {
+            expiration_time: None,
+            ..trial_data()
+        };
+        rpc.set_view(&contract_id, "get_key_usage", json!(key_usage));
+        rpc.set_view(&contract_id, "is_paused", json!(false));
+        rpc.set_view(&contract_id, "is_app_paused", json!(false));
+        rpc.set_view(&contract_id, "is_user_frozen", json!(false));
+        rpc.set_view(&contract_id, "get_bundle", json!(bundle));
+        rpc.set_view(&contract_id, "get_trial_data", json!(trial_data));
+        rpc.set_view(
+            &contract_id,
+            "get_app_balance",
+            json!(NearToken::from_near(2)),
+        );
+        rpc.set_view(&contract_id, "get_evm_chain", json!(EvmChain::near_evm()));
+        rpc.set_view(&wallet(), "get_nonce", json!(U64(3)));
+
+        Relayer::new(
+            RelayerConfig {
+                contract_id,
+                max_batch_size,
+                gas: Gas::from_tgas(300),
+            },
+            rpc,
+        )
+    }
+
+    fn signed_transfer(nonce: u64) -> ExecuteNearActionArgs {
+        let payload = transfer("bob.near".parse().unwrap(), NearToken::from_near(1), nonce);
+        ExecuteNearActionArgs::new(&SessionSigner::ed25519(&[1; 32]), payload, APP_ID).unwrap()
+    }
+
+    #[tokio::test]
+    async fn valid_payloads_are_submitted_and_tracked() {
+        let relayer = relayer(10);
+        let id = relayer.submit(signed_transfer(3)).await.unwrap();
+        assert_eq!(relayer.status(id), Ok(SubmissionStatus::Queued));
+
+        assert_eq!(relayer.flush().await, 1);
+        assert_eq!(
+            relayer.status(id),
+            Ok(SubmissionStatus::Succeeded {
+                tx_hash: "mock-tx-0-0".to_string()
+            })
+        );
+
+        let batches = relayer.rpc.batches();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0][0].method_name, "execute_near_action");
+        assert_eq!(
+            batches[0][0].args,
+            serde_json::to_value(signed_transfer(3)).unwrap()
+        );
+        assert_eq!(relayer.flush().await, 0);
+    }
+
+    #[tokio::test]
+    async fn stale_nonces_and_other_apps_are_rejected() {
+        let relayer = relayer(10);
+        assert!(matches!(
+            relayer.submit(signed_transfer(2)).await,
+            Err(RelayerError::Rejected(_))
+        ));
+
+        let mut args = signed_transfer(3);
+        args.app_id = "other-app".to_string();
+        assert!(matches!(
+            relayer.submit(args).await,
+            Err(RelayerError::Rejected(_))
+        ));
+
+        relayer
+            .rpc
+            .set_view(&relayer.config.contract_id, "is_app_paused", json!(true));
+        assert_eq!(
+            relayer.submit(signed_transfer(3)).await,
+            Err(RelayerError::rejected("App is paused"))
+        );
+    }
+
+    #[tokio::test]
+    async fn payloads_the_contract_would_reject_are_not_queued() {
+        let relayer = relayer(10);
+
+        let mut args = signed_transfer(3);
+        args.payload.nonce = U64(4);
+        assert_eq!(
+            relayer.submit(args).await,
+            Err(RelayerError::rejected("Invalid session key signature"))
+        );
+
+        let mut args = signed_transfer(3);
+        args.session_key = SessionSigner::ed25519(&[2; 32]).session_key();
+        assert_eq!(
+            relayer.submit(args).await,
+            Err(RelayerError::rejected("Invalid session key signature"))
+        );
+
+        relayer.rpc.set_view(
+            &relayer.config.contract_id,
+            "get_evm_chain",
+            json!(EvmChain {
+                enabled: false,
+                ..EvmChain::near_evm()
+            }),
+        );
+        assert_eq!(
+            relayer.submit(signed_transfer(3)).await,
+            Err(RelayerError::rejected("NEAR EVM chain is not enabled"))
+        );
+        assert!(relayer.rpc.batches().is_empty());
+    }
+
+    #[tokio::test]
+    async fn one_payload_per_wallet_is_in_flight() {
+        let relayer = relayer(10);
+        relayer.submit(signed_transfer(3)).await.unwrap();
+        assert_eq!(
+            relayer.submit(signed_transfer(3)).await,
+            Err(RelayerError::rejected(
+                "A payload for this wallet is already pending"
+            ))
+        );
+
+        relayer.flush().await;
+        assert!(relayer.submit(signed_transfer(3)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn statuses_are_tracked_per_submission() {
+        let relayer = relayer(1);
+        let first = relayer.submit(signed_transfer(3)).await.unwrap();
+        assert_eq!(relayer.flush().await, 1);
+        let second = relayer.submit(signed_transfer(3)).await.unwrap();
+
+        assert_ne!(first, second);
+        assert!(matches!(
+            relayer.status(first),
+            Ok(SubmissionStatus::Succeeded { .. })
+        ));
+        assert_eq!(relayer.status(second), Ok(SubmissionStatus::Queued));
+        assert_eq!(relayer.status(99), Err(RelayerError::NotFound(99)));
+    }
+}
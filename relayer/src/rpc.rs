@@ -0,0 +1,79 @@
+// relayer/src/rpc.rs
+use crate::RelayerError;
+use async_trait::async_trait;
+use near_sdk::{AccountId, Gas, NearToken};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// A function call the relayer signs and pays for with its own account.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionCall {
+    pub receiver_id: AccountId,
+    pub method_name: String,
+    pub args: Value,
+    pub gas: Gas,
+    pub deposit: NearToken,
+}
+
+/// Reads contract state and submits the relayer's transactions.
+#[async_trait]
+pub trait RelayerRpc: Send + Sync {
+    async fn view(
+        &self,
+        account_id: &AccountId,
+        method_name: &str,
+        args: Value,
+    ) -> Result<Value, RelayerError>;
+
+    /// Submits each call as its own transaction and waits for them to finish.
+    /// Returns, in order, the transaction hash or why the transaction failed.
+    async fn submit_batch(&self, calls: Vec<FunctionCall>) -> Vec<Result<String, String>>;
+}
+
+/// Local stand-in answering views from fixed responses and recording submissions.
+#[derive(Default)]
+pub struct MockRpc {
+    views: Mutex<HashMap<(AccountId, String), Value>>,
+    batches: Mutex<Vec<Vec<FunctionCall>>>,
+}
+
+impl MockRpc {
+    /// Answers `method_name` on `account_id` with `response`, whatever the arguments.
+    pub fn set_view(&self, account_id: &AccountId, method_name: &str, response: Value) {
+        self.views
+            .lock()
+            .unwrap()
+            .insert((account_id.clone(), method_name.to_string()), response);
+    }
+
+    pub fn batches(&self) -> Vec<Vec<FunctionCall>> {
+        self.batches.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl RelayerRpc for MockRpc {
+    async fn view(
+        &self,
+        account_id: &AccountId,
+        method_name: &str,
+        _args: Value,
+    ) -> Result<Value, RelayerError> {
+        self.views
+            .lock()
+            .unwrap()
+            .get(&(account_id.clone(), method_name.to_string()))
+            .cloned()
+            .ok_or_else(|| RelayerError::Rpc(format!("No view {}.{}", account_id, method_name)))
+    }
+
+    async fn submit_batch(&self, calls: Vec<FunctionCall>) -> Vec<Result<String, String>> {
+        let mut batches = self.batches.lock().unwrap();
+        let outcomes = (0..calls.len())
+            .map(|index| Ok(format!("mock-tx-{}-{}", batches.len(), index)))
+            .collect();
+        batches.push(calls);
+        outcomes
+    }
+}
@@ -0,0 +1,50 @@
+// relayer/src/server.rs
+use crate::{Relayer, RelayerError, RelayerRpc, SubmissionStatus};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use fastauth_sdk::ExecuteNearActionArgs;
+use serde::Serialize;
+use std::sync::Arc;
+
+#[derive(Serialize)]
+pub struct SubmitResponse {
+    pub id: u64,
+}
+
+impl IntoResponse for RelayerError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            RelayerError::Rejected(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            RelayerError::NotFound(_) => StatusCode::NOT_FOUND,
+            RelayerError::Rpc(_) => StatusCode::BAD_GATEWAY,
+            RelayerError::Config(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+/// `POST /relay` with the `execute_near_action` arguments, and `GET /relay/:id`
+pub fn router<R: RelayerRpc + 'static>(relayer: Arc<Relayer<R>>) -> Router {
+    Router::new()
+        .route("/relay", post(submit::<R>))
+        .route("/relay/:id", get(status::<R>))
+        .with_state(relayer)
+}
+
+async fn submit<R: RelayerRpc>(
+    State(relayer): State<Arc<Relayer<R>>>,
+    Json(args): Json<ExecuteNearActionArgs>,
+) -> Result<Json<SubmitResponse>, RelayerError> {
+    let id = relayer.submit(args).await?;
+    Ok(Json(SubmitResponse { id }))
+}
+
+async fn status<R: RelayerRpc>(
+    State(relayer): State<Arc<Relayer<R>>>,
+    Path(id): Path<u64>,
+) -> Result<Json<SubmissionStatus>, RelayerError> {
+    Ok(Json(relayer.status(id)?))
+}
@@ -0,0 +1,58 @@
+// sdk/src/address.rs
+use crate::SdkError;
+use near_sdk::{AccountId, CurveType, PublicKey};
+use sha3::{Digest, Keccak256};
+
+/// Eth-implicit account of the wallet controlled by an MPC key: `0x` followed by the
+/// last 20 bytes of keccak256 of the uncompressed key, as `activate_account` expects.
+pub fn eth_implicit_account_id(mpc_key: &PublicKey) -> Result<AccountId, SdkError> {
+    // Read the curve byte directly: `curve_type` calls into `env`, which off-chain
+    // builds don't link
+    if mpc_key.as_bytes()[0] != CurveType::SECP256K1 as u8 {
+        return Err(SdkError::InvalidMpcKey(
+            "Expected a secp256k1 key".to_string(),
+        ));
+    }
+    let key_data = &mpc_key.as_bytes()[1..];
+    if key_data.len() != 64 {
+        return Err(SdkError::InvalidMpcKey(format!(
+            "Expected 64 bytes of key data. Found: {}",
+            key_data.len()
+        )));
+    }
+
+    let hash = Keccak256::digest(key_data);
+    let address: String = hash[12..].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("0x{}", address)
+        .parse()
+        .map_err(|_| SdkError::InvalidMpcKey("Invalid eth-implicit account".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derives_the_eth_implicit_account() {
+        // secp256k1 key of the secret key [7; 32]
+        let mpc_key: PublicKey =
+            "secp256k1:43y5hhS5mWqG4QWeEBqmJfDae1rWstRUETCvcELhzai5ataDC38sCnahANBd5ALUWTMHhPRM74geUt9D3TE5QhG3"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            eth_implicit_account_id(&mpc_key).unwrap().as_str(),
+            "0x4a62316623ad457f02cdc5d997ded67a383ec569"
+        );
+    }
+
+    #[test]
+    fn rejects_ed25519_keys() {
+        let key: PublicKey = "ed25519:AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9"
+            .parse()
+            .unwrap();
+        assert!(matches!(
+            eth_implicit_account_id(&key),
+            Err(SdkError::InvalidMpcKey(_))
+        ));
+    }
+}
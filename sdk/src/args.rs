@@ -0,0 +1,79 @@
+// sdk/src/args.rs
+use crate::{AppID, NearPayload, SdkError, SessionKey, SessionSignature, SessionSigner};
+use serde::{Deserialize, Serialize};
+
+/// Arguments of `execute_near_action`, ready to be submitted by any account.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExecuteNearActionArgs {
+    pub signature: SessionSignature,
+    pub payload: NearPayload,
+    pub session_key: SessionKey,
+    pub app_id: AppID,
+}
+
+impl ExecuteNearActionArgs {
+    /// Signs `payload` with the app's session key for the user.
+    pub fn new(
+        signer: &SessionSigner,
+        payload: NearPayload,
+        app_id: impl Into<AppID>,
+    ) -> Result<Self, SdkError> {
+        Ok(ExecuteNearActionArgs {
+            signature: signer.sign(&payload)?,
+            payload,
+            session_key: signer.session_key(),
+            app_id: app_id.into(),
+        })
+    }
+
+    /// JSON arguments of the function call
+    pub fn to_json_bytes(&self) -> Result<Vec<u8>, SdkError> {
+        Ok(serde_json::to_vec(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transfer;
+    use near_sdk::NearToken;
+
+    #[test]
+    fn args_carry_the_signed_payload() {
+        let signer = SessionSigner::ed25519(&[1; 32]);
+        let payload = transfer(
+            "bob.near".parse().unwrap(),
+            NearToken::from_yoctonear(1000),
+            0,
+        );
+        let args = ExecuteNearActionArgs::new(&signer, payload, "app").unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_slice(&args.to_json_bytes().unwrap()).unwrap();
+        assert_eq!(
+            json["payload"],
+            serde_json::json!({
+                "action": { "Transfer": { "receiver_id": "bob.near", "amount": "1000" } },
+                "nonce": "0",
+            })
+        );
+        assert_eq!(
+            json["session_key"],
+            "ed25519:AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9"
+        );
+        assert_eq!(json["app_id"], "app");
+
+        // The signature covers the payload exactly as serialized
+        let payload_bytes =
+            br#"{"action":{"Transfer":{"receiver_id":"bob.near","amount":"1000"}},"nonce":"0"}"#;
+        assert_eq!(
+            serde_json::to_vec(&args.payload).unwrap(),
+            payload_bytes.to_vec()
+        );
+        let expected = signer.sign_bytes(payload_bytes);
+        let SessionSignature::Raw(signature) = &args.signature else {
+            panic!("expected a raw signature");
+        };
+        assert_eq!(signature.0, expected);
+    }
+}
@@ -0,0 +1,28 @@
+// sdk/src/error.rs
+use std::fmt;
+
+#[derive(Debug)]
+pub enum SdkError {
+    /// The MPC key is not a 64-byte uncompressed secp256k1 key
+    InvalidMpcKey(String),
+    InvalidSecretKey,
+    Serialization(serde_json::Error),
+}
+
+impl fmt::Display for SdkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SdkError::InvalidMpcKey(reason) => write!(f, "Invalid MPC key: {}", reason),
+            SdkError::InvalidSecretKey => write!(f, "Invalid session secret key"),
+            SdkError::Serialization(e) => write!(f, "Failed to serialize payload: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SdkError {}
+
+impl From<serde_json::Error> for SdkError {
+    fn from(e: serde_json::Error) -> Self {
+        SdkError::Serialization(e)
+    }
+}
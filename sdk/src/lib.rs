@@ -0,0 +1,25 @@
+//! Client SDK for the FastAuth contract.
+//!
+//! Builds `NearPayload`s from the contract's own model types, signs them with a session
+//! key exactly as the contract verifies them, checks signatures the same way, derives a
+//! user's eth-implicit account from their MPC key and produces the arguments of
+//! `execute_near_action`. Sharing the types with the contract keeps the JSON a session
+//! key signs identical to what the contract re-serializes.
+pub mod address;
+pub mod args;
+pub mod error;
+pub mod payload;
+pub mod signer;
+pub mod verify;
+
+pub use address::eth_implicit_account_id;
+pub use args::ExecuteNearActionArgs;
+pub use error::SdkError;
+pub use payload::{function_call, transfer};
+pub use signer::SessionSigner;
+pub use verify::verify_signature;
+
+pub use keypom_multichain_trial_accounts::trial_user::perform_actions::near::call_fn::{
+    NearAction, NearPayload,
+};
+pub use keypom_multichain_trial_accounts::{AppID, MpcPath, SessionKey, SessionSignature};
@@ -0,0 +1,38 @@
+// sdk/src/payload.rs
+use crate::{NearAction, NearPayload};
+use near_sdk::json_types::U64;
+use near_sdk::{AccountId, Gas, NearToken};
+use serde::Serialize;
+
+/// Payload calling `method_name` on `contract_id` with JSON `args`.
+/// `nonce` is the current nonce of the user's wallet (its `get_nonce` view).
+pub fn function_call(
+    contract_id: AccountId,
+    method_name: &str,
+    args: &impl Serialize,
+    gas: Gas,
+    deposit: NearToken,
+    nonce: u64,
+) -> Result<NearPayload, serde_json::Error> {
+    Ok(NearPayload {
+        action: NearAction::FunctionCall {
+            contract_id,
+            method_name: method_name.to_string(),
+            args: serde_json::to_vec(args)?,
+            gas,
+            deposit,
+        },
+        nonce: U64(nonce),
+    })
+}
+
+/// Payload transferring `amount` to `receiver_id`.
+pub fn transfer(receiver_id: AccountId, amount: NearToken, nonce: u64) -> NearPayload {
+    NearPayload {
+        action: NearAction::Transfer {
+            receiver_id,
+            amount,
+        },
+        nonce: U64(nonce),
+    }
+}
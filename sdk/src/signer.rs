@@ -0,0 +1,111 @@
+// sdk/src/signer.rs
+use crate::{SdkError, SessionKey, SessionSignature};
+use ed25519_dalek::Signer;
+use keypom_multichain_trial_accounts::eip191_hash;
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::{CurveType, PublicKey};
+use serde::Serialize;
+
+/// Secret half of a session key, signing payloads the way the contract verifies them.
+pub enum SessionSigner {
+    Ed25519(ed25519_dalek::SigningKey),
+    /// Signs like an Ethereum wallet's `personal_sign`
+    Secp256k1(k256::ecdsa::SigningKey),
+}
+
+impl SessionSigner {
+    pub fn ed25519(secret_key: &[u8; 32]) -> Self {
+        SessionSigner::Ed25519(ed25519_dalek::SigningKey::from_bytes(secret_key))
+    }
+
+    pub fn secp256k1(secret_key: &[u8; 32]) -> Result<Self, SdkError> {
+        k256::ecdsa::SigningKey::from_bytes(secret_key.into())
+            .map(SessionSigner::Secp256k1)
+            .map_err(|_| SdkError::InvalidSecretKey)
+    }
+
+    /// Public key registered with `add_session_key`
+    pub fn session_key(&self) -> SessionKey {
+        let public_key = match self {
+            SessionSigner::Ed25519(key) => {
+                PublicKey::from_parts(CurveType::ED25519, key.verifying_key().to_bytes().to_vec())
+            }
+            SessionSigner::Secp256k1(key) => PublicKey::from_parts(
+                CurveType::SECP256K1,
+                // Uncompressed point without the 0x04 prefix
+                key.verifying_key().to_encoded_point(false).as_bytes()[1..].to_vec(),
+            ),
+        }
+        .expect("Session key has the curve's length");
+        SessionKey::from(public_key)
+    }
+
+    /// Signs the payload's JSON serialization, which is what the contract checks the
+    /// signature against.
+    pub fn sign(&self, payload: &impl Serialize) -> Result<SessionSignature, SdkError> {
+        let payload_bytes = serde_json::to_vec(payload)?;
+        Ok(SessionSignature::Raw(Base64VecU8::from(
+            self.sign_bytes(&payload_bytes),
+        )))
+    }
+
+    /// ed25519 signature, or `r || s || v` with `v` in 27/28 over the EIP-191 hash
+    pub fn sign_bytes(&self, message: &[u8]) -> Vec<u8> {
+        match self {
+            SessionSigner::Ed25519(key) => key.sign(message).to_bytes().to_vec(),
+            SessionSigner::Secp256k1(key) => {
+                let (signature, recovery_id) = key
+                    .sign_prehash_recoverable(&eip191_hash(message))
+                    .expect("Signing a 32-byte hash cannot fail");
+                let mut bytes = signature.to_bytes().to_vec();
+                bytes.push(recovery_id.to_byte() + 27);
+                bytes
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde_json::json;
+
+    fn raw(signature: SessionSignature) -> Vec<u8> {
+        match signature {
+            SessionSignature::Raw(bytes) => bytes.into(),
+            _ => panic!("expected a raw signature"),
+        }
+    }
+
+    // Same vectors as the contract's signature verification tests
+    #[test]
+    fn ed25519_golden_vector() {
+        let signer = SessionSigner::ed25519(&[1; 32]);
+        assert_eq!(
+            signer.session_key().to_string(),
+            "ed25519:AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9"
+        );
+        let signature = raw(signer.sign(&json!({ "message": "hello" })).unwrap());
+        assert_eq!(
+            STANDARD.encode(signature),
+            "RBf/3SkfvNkUlQClozHdPhjzn1gdcYvZryCf/ymeENRUJrwOUfw7YdXpmI7oGigMcvGR0JTWJza4ONRL+G1MCw=="
+        );
+    }
+
+    #[test]
+    fn secp256k1_golden_vector() {
+        let signer = SessionSigner::secp256k1(&[7; 32]).unwrap();
+        assert_eq!(
+            signer.session_key().to_string(),
+            "secp256k1:43y5hhS5mWqG4QWeEBqmJfDae1rWstRUETCvcELhzai5ataDC38sCnahANBd5ALUWTMHhPRM74geUt9D3TE5QhG3"
+        );
+        let signature = raw(signer.sign(&json!({ "message": "hello" })).unwrap());
+        let hex: String = signature.iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(
+            hex,
+            "6916dee2e8b880c9129ab93e789f8bb8f52be21814cae4a804be8fa3b702c8dc48e05fb44cbca1fa83acf8191b9c722d7968a7405ae256ce6cdd2e31b452d9461c"
+        );
+    }
+}
@@ -0,0 +1,55 @@
+// sdk/src/verify.rs
+use crate::{SdkError, SessionKey, SessionSignature};
+use keypom_multichain_trial_accounts::trial_user::perform_actions::session_signature::verify_session_signature;
+use serde::Serialize;
+
+/// Checks a session key's signature over the payload's JSON serialization with the
+/// contract's own verification code, so payloads it would reject can be turned away
+/// before paying gas.
+pub fn verify_signature(
+    payload: &impl Serialize,
+    signature: &SessionSignature,
+    session_key: &SessionKey,
+) -> Result<bool, SdkError> {
+    let payload_bytes = serde_json::to_vec(payload)?;
+    Ok(verify_session_signature(&payload_bytes, signature, session_key).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SessionSigner;
+    use serde_json::json;
+
+    fn check(signer: &SessionSigner, signed: &serde_json::Value, checked: &serde_json::Value) {
+        let signature = signer.sign(signed).unwrap();
+        assert_eq!(
+            verify_signature(checked, &signature, &signer.session_key()).unwrap(),
+            signed == checked
+        );
+    }
+
+    #[test]
+    fn signatures_are_bound_to_the_payload() {
+        let hello = json!({ "message": "hello" });
+        let bye = json!({ "message": "bye" });
+        for signer in [
+            SessionSigner::ed25519(&[1; 32]),
+            SessionSigner::secp256k1(&[7; 32]).unwrap(),
+        ] {
+            check(&signer, &hello, &hello);
+            check(&signer, &hello, &bye);
+        }
+    }
+
+    #[test]
+    fn signatures_from_other_keys_are_rejected() {
+        let payload = json!({ "message": "hello" });
+        let signature = SessionSigner::ed25519(&[1; 32]).sign(&payload).unwrap();
+        let other_key = SessionSigner::ed25519(&[2; 32]).session_key();
+        assert!(!verify_signature(&payload, &signature, &other_key).unwrap());
+
+        let secp256k1_key = SessionSigner::secp256k1(&[7; 32]).unwrap().session_key();
+        assert!(!verify_signature(&payload, &signature, &secp256k1_key).unwrap());
+    }
+}